@@ -0,0 +1,108 @@
+/**
+ * CLI Generate Command E2E Test
+ *
+ * Tests command actions against the built CLI:
+ * Command: upg generate <TEMPLATE> --dest <DIR> --use-defaults [--skip-commands]
+ * Behavior: command actions run after generation unless --skip-commands is set
+ *           (the desktop runs them itself, after asking the user).
+ */
+
+import { describe, it, expect, beforeEach, afterEach } from 'vitest';
+import { spawnSync } from 'node:child_process';
+import { mkdir, rm, writeFile } from 'node:fs/promises';
+import { join, resolve } from 'node:path';
+import { tmpdir } from 'node:os';
+import { existsSync } from 'node:fs';
+
+// Project root directory - resolve from packages/cli/src/__tests__
+const projectRoot = resolve(__dirname, '../../../..');
+const cliDistPath = resolve(projectRoot, 'packages/cli/dist/bin/upg.js');
+
+const MANIFEST = `
+apiVersion: upg/v1
+metadata:
+  name: command-template
+  version: "1.0.0"
+  description: Template with a command action
+prompts:
+  - id: project_name
+    type: string
+    message: Project name?
+    default: demo
+actions:
+  - type: generate
+    src: template/
+    dest: ./
+  - type: command
+    command: node -e "require('fs').writeFileSync('command-ran.txt', '')"
+    description: Mark the command as run
+`;
+
+/**
+ * Execute the upg CLI with arguments using the built distribution
+ */
+function runCli(args: string[]): { exitCode: number; stdout: string; stderr: string } {
+  const result = spawnSync('node', [cliDistPath, ...args], {
+    cwd: projectRoot,
+    encoding: 'utf-8',
+    env: { ...process.env, NO_COLOR: '1' },
+    timeout: 60000,
+  });
+
+  return {
+    exitCode: result.status ?? 1,
+    stdout: result.stdout || '',
+    stderr: result.stderr || '',
+  };
+}
+
+describe('CLI generate command E2E', () => {
+  let tmpDir: string;
+  let templateDir: string;
+
+  beforeEach(async () => {
+    tmpDir = join(tmpdir(), `upg-test-${Date.now()}-${Math.random().toString(36).slice(2)}`);
+    templateDir = join(tmpDir, 'template-src');
+    await mkdir(join(templateDir, 'template'), { recursive: true });
+    await writeFile(join(templateDir, 'upg.yaml'), MANIFEST);
+    await writeFile(join(templateDir, 'template', 'README.md'), '# {{ project_name }}\n');
+  });
+
+  afterEach(async () => {
+    try {
+      await rm(tmpDir, { recursive: true, force: true });
+    } catch {
+      // Ignore cleanup errors
+    }
+  });
+
+  it('should run command actions by default', () => {
+    const outputDir = join(tmpDir, 'with-commands');
+
+    const result = runCli(['generate', templateDir, '--dest', outputDir, '--use-defaults', '--json']);
+
+    expect(result.exitCode).toBe(0);
+    expect(existsSync(join(outputDir, 'README.md'))).toBe(true);
+    expect(existsSync(join(outputDir, 'command-ran.txt'))).toBe(true);
+  });
+
+  it('should not run command actions with --skip-commands', () => {
+    const outputDir = join(tmpDir, 'without-commands');
+
+    const result = runCli([
+      'generate',
+      templateDir,
+      '--dest',
+      outputDir,
+      '--use-defaults',
+      '--json',
+      '--skip-commands',
+    ]);
+
+    expect(result.exitCode).toBe(0);
+    const payload = JSON.parse(result.stdout.trim());
+    expect(payload.success).toBe(true);
+    expect(existsSync(join(outputDir, 'README.md'))).toBe(true);
+    expect(existsSync(join(outputDir, 'command-ran.txt'))).toBe(false);
+  });
+});
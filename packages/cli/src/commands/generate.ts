@@ -25,6 +25,7 @@ interface GenerateOptions {
   json?: boolean;
   enrich?: boolean;
   enrichDepth?: string;
+  skipCommands?: boolean;
 }
 
 interface UPGManifest {
@@ -550,8 +551,8 @@ export async function generateAction(
       }
     }
 
-    // Execute post-generation commands
-    if (manifest.actions) {
+    // Execute post-generation commands (unless the caller runs them itself)
+    if (manifest.actions && !options.skipCommands) {
       for (const action of manifest.actions) {
         if (action.type === 'command' && action.command) {
           if (!evaluateCondition(action.when, context)) {
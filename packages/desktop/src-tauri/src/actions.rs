@@ -0,0 +1,281 @@
+//! Manifest `command` action execution
+//!
+//! The desktop passes `--skip-commands` to the CLI and runs command actions itself so
+//! the user can approve the exact commands first. `get_template_commands` lists what
+//! would run for a set of answers; only commands the user approved are executed, in
+//! manifest order, with output streamed as `actions://output` events.
+
+use crate::conditions::evaluate_when;
+use crate::error_codes;
+use crate::jinja::with_defaults;
+use crate::manifest::{load_manifest, ManifestAction, ManifestFile, OnError};
+use crate::process::{run_streaming, shell_command, OutputStream};
+use crate::{parse_answers, resolve_template_path};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::path::Path;
+use std::time::Duration;
+use tauri::Emitter;
+
+/// Event carrying one line of command output
+pub const ACTION_OUTPUT_EVENT: &str = "actions://output";
+
+/// Default time a single command may run before it is killed
+pub const DEFAULT_ACTION_TIMEOUT_SECS: u64 = 600;
+
+/// A command action as it would run for a given set of answers
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlannedCommand {
+    /// Position in the manifest's `actions` list
+    pub index: usize,
+    /// Exact command line, as it must appear in `approved_commands`
+    pub command: String,
+    pub description: Option<String>,
+    pub when: Option<String>,
+    pub on_error: OnError,
+    /// False when `when` evaluates to false for the answers
+    pub will_run: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ActionStatus {
+    Succeeded,
+    Failed,
+    TimedOut,
+    /// `when` evaluated to false
+    Skipped,
+    /// The user did not approve the command
+    NotApproved,
+    /// An earlier command failed with `on_error: fail`
+    NotRun,
+}
+
+/// Outcome of one command action
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActionResult {
+    pub index: usize,
+    pub command: String,
+    pub description: Option<String>,
+    pub status: ActionStatus,
+    pub on_error: OnError,
+    /// False only when the command failed and `on_error` is `fail`
+    pub success: bool,
+    /// COMMAND_FAILED or TIMEOUT when the command did not succeed
+    pub code: Option<String>,
+    pub message: String,
+    pub exit_code: Option<i32>,
+    pub stdout: String,
+    pub stderr: String,
+    pub duration_ms: u64,
+}
+
+/// Payload of `actions://output` events
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActionOutputEvent {
+    pub index: usize,
+    pub command: String,
+    pub stream: OutputStream,
+    pub line: String,
+}
+
+/// List the manifest's command actions with their `when` evaluated against `answers`
+///
+/// Unanswered prompts take their resolved defaults, as they do during generation.
+pub fn plan_commands(manifest: &ManifestFile, answers: &Map<String, Value>) -> Vec<PlannedCommand> {
+    let context = with_defaults(manifest, answers);
+    manifest
+        .actions
+        .iter()
+        .enumerate()
+        .filter_map(|(index, action)| match action {
+            ManifestAction::Command(c) => Some(PlannedCommand {
+                index,
+                command: c.command.clone(),
+                description: c.description.clone(),
+                when: c.when.clone(),
+                on_error: c.on_error,
+                will_run: c
+                    .when
+                    .as_deref()
+                    .map_or(true, |w| evaluate_when(w, &context)),
+            }),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Run the approved command actions sequentially in `project_dir`
+///
+/// Commands not listed verbatim in `approved` are reported as `not_approved`. A failure
+/// with `on_error: fail` stops the remaining commands.
+pub async fn run_commands(
+    app: &tauri::AppHandle,
+    manifest: &ManifestFile,
+    project_dir: &Path,
+    answers: &Map<String, Value>,
+    approved: &[String],
+    timeout: Duration,
+) -> Vec<ActionResult> {
+    let mut results = Vec::new();
+    let mut aborted = false;
+
+    for planned in plan_commands(manifest, answers) {
+        let result = |status: ActionStatus, message: String| ActionResult {
+            index: planned.index,
+            command: planned.command.clone(),
+            description: planned.description.clone(),
+            status,
+            on_error: planned.on_error,
+            success: !matches!(status, ActionStatus::Failed | ActionStatus::TimedOut)
+                || planned.on_error != OnError::Fail,
+            code: None,
+            message,
+            exit_code: None,
+            stdout: String::new(),
+            stderr: String::new(),
+            duration_ms: 0,
+        };
+
+        if aborted {
+            results.push(result(
+                ActionStatus::NotRun,
+                "Not run: an earlier command failed".to_string(),
+            ));
+            continue;
+        }
+        if !planned.will_run {
+            results.push(result(
+                ActionStatus::Skipped,
+                format!(
+                    "Skipped: condition '{}' is false",
+                    planned.when.as_deref().unwrap_or_default()
+                ),
+            ));
+            continue;
+        }
+        if !approved.contains(&planned.command) {
+            results.push(result(
+                ActionStatus::NotApproved,
+                "Not run: command was not approved".to_string(),
+            ));
+            continue;
+        }
+
+        let mut command = shell_command(&planned.command);
+        command.current_dir(project_dir);
+
+        let index = planned.index;
+        let outcome = run_streaming(command, None, timeout, |stream, line| {
+            let event = ActionOutputEvent {
+                index,
+                command: planned.command.clone(),
+                stream,
+                line: line.to_string(),
+            };
+            if let Err(e) = app.emit(ACTION_OUTPUT_EVENT, event) {
+                eprintln!("Failed to emit {}: {}", ACTION_OUTPUT_EVENT, e);
+            }
+        })
+        .await;
+
+        let desc = planned.description.as_deref().unwrap_or(&planned.command);
+        let action = match outcome {
+            Err(e) => ActionResult {
+                code: Some(error_codes::COMMAND_FAILED.to_string()),
+                ..result(ActionStatus::Failed, e)
+            },
+            Ok(o) => {
+                let (status, code, message) = if o.timed_out {
+                    (
+                        ActionStatus::TimedOut,
+                        Some(error_codes::TIMEOUT.to_string()),
+                        format!("{} timed out after {}s", desc, timeout.as_secs()),
+                    )
+                } else if o.success {
+                    (ActionStatus::Succeeded, None, desc.to_string())
+                } else {
+                    (
+                        ActionStatus::Failed,
+                        Some(error_codes::COMMAND_FAILED.to_string()),
+                        format!("{} failed with exit code {:?}", desc, o.exit_code),
+                    )
+                };
+                ActionResult {
+                    code,
+                    exit_code: o.exit_code,
+                    stdout: o.stdout,
+                    stderr: o.stderr,
+                    duration_ms: o.duration_ms,
+                    ..result(status, message)
+                }
+            }
+        };
+
+        aborted = !action.success;
+        results.push(action);
+    }
+
+    results
+}
+
+/// List the command actions a template would run for the given answers
+///
+/// The frontend shows these for approval and passes the approved command lines to
+/// `generate_from_template` as `approved_commands`.
+#[tauri::command]
+pub async fn get_template_commands(
+    app: tauri::AppHandle,
+    template_path: String,
+    data: Option<String>,
+) -> Result<Vec<PlannedCommand>, String> {
+    let template_path = resolve_template_path(&template_path, &app)?;
+    let manifest = load_manifest(&template_path)?;
+    Ok(plan_commands(&manifest, &parse_answers(data.as_deref())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MANIFEST: &str = r#"
+metadata:
+  name: demo
+prompts:
+  - id: use_docker
+    type: boolean
+    default: true
+  - id: package_manager
+    type: select
+    default: pnpm
+actions:
+  - type: command
+    command: docker compose build
+    when: use_docker
+  - type: command
+    command: pnpm install
+    when: package_manager == 'pnpm'
+"#;
+
+    fn will_run(answers: Value) -> Vec<bool> {
+        let manifest: ManifestFile = serde_yaml::from_str(MANIFEST).unwrap();
+        let Value::Object(answers) = answers else {
+            unreachable!()
+        };
+        plan_commands(&manifest, &answers)
+            .into_iter()
+            .map(|c| c.will_run)
+            .collect()
+    }
+
+    #[test]
+    fn unanswered_prompts_use_their_defaults() {
+        assert_eq!(will_run(serde_json::json!({})), [true, true]);
+    }
+
+    #[test]
+    fn answers_override_defaults() {
+        let answers = serde_json::json!({ "use_docker": false, "package_manager": "npm" });
+        assert_eq!(will_run(answers), [false, false]);
+    }
+}
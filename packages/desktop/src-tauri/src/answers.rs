@@ -0,0 +1,375 @@
+//! Prompt answer validation
+//!
+//! Answers are checked against the manifest's prompts before the CLI is spawned, so a
+//! bad answer is reported on its form field instead of failing inside the CLI. Hidden
+//! prompts and prompts whose `when` does not hold (with unanswered prompts at their
+//! defaults) are not checked; a missing answer is fine when the prompt has a default.
+
+use crate::conditions::evaluate_when;
+use crate::error_codes;
+use crate::jinja::with_defaults;
+use crate::manifest::{load_manifest, scalar_string, ManifestFile, ManifestPrompt, PromptType};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Problem with one answer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnswerError {
+    /// UPG error code (missing field, wrong type or invalid value)
+    pub code: String,
+    pub message: String,
+}
+
+/// Errors keyed by prompt id
+pub type AnswerErrors = BTreeMap<String, AnswerError>;
+
+fn error(code: &str, message: impl Into<String>) -> Option<AnswerError> {
+    Some(AnswerError {
+        code: code.to_string(),
+        message: message.into(),
+    })
+}
+
+fn required() -> Option<AnswerError> {
+    error(
+        error_codes::MISSING_REQUIRED_FIELD,
+        "This field is required",
+    )
+}
+
+fn is_empty(value: &Value) -> bool {
+    match value {
+        Value::Null => true,
+        Value::String(s) => s.is_empty(),
+        Value::Array(items) => items.is_empty(),
+        _ => false,
+    }
+}
+
+fn choice_list(prompt: &ManifestPrompt) -> String {
+    prompt
+        .choices
+        .iter()
+        .map(|c| c.value.as_str())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn is_choice(prompt: &ManifestPrompt, value: &str) -> bool {
+    prompt.choices.iter().any(|c| c.value == value)
+}
+
+/// A scalar answer as choice values hold it (stringified, like `3.11` or `true`)
+fn choice_value(value: &Value) -> Option<String> {
+    match value {
+        Value::String(_) | Value::Number(_) | Value::Bool(_) => Some(scalar_string(value.clone())),
+        _ => None,
+    }
+}
+
+/// Check a string against the prompt's `validator`
+fn check_pattern(prompt: &ManifestPrompt, value: &str) -> Option<AnswerError> {
+    let pattern = prompt.validator.as_deref()?;
+    // Patterns are written for JavaScript; ones this engine cannot compile are left to the CLI
+    let regex = match Regex::new(pattern) {
+        Ok(regex) => regex,
+        Err(e) => {
+            eprintln!("Skipping validator of prompt '{}': {}", prompt.id, e);
+            return None;
+        }
+    };
+    if regex.is_match(value) {
+        return None;
+    }
+    let message = prompt
+        .error_message
+        .clone()
+        .unwrap_or_else(|| format!("Must match {}", pattern));
+    error(error_codes::INVALID_FIELD_VALUE, message)
+}
+
+/// Check one present, non-empty answer
+fn check_value(prompt: &ManifestPrompt, value: &Value) -> Option<AnswerError> {
+    match prompt.prompt_type {
+        PromptType::String | PromptType::Secret => match value {
+            Value::String(s) => check_pattern(prompt, s),
+            _ => error(error_codes::INVALID_FIELD_TYPE, "Must be text"),
+        },
+        PromptType::Int => {
+            let whole = value.is_i64()
+                || value.is_u64()
+                || value.as_f64().is_some_and(|f| f.fract() == 0.0);
+            if whole {
+                None
+            } else {
+                error(error_codes::INVALID_FIELD_TYPE, "Must be a whole number")
+            }
+        }
+        PromptType::Float => match value {
+            Value::Number(_) => None,
+            _ => error(error_codes::INVALID_FIELD_TYPE, "Must be a number"),
+        },
+        PromptType::Boolean => match value {
+            Value::Bool(_) => None,
+            _ => error(error_codes::INVALID_FIELD_TYPE, "Must be true or false"),
+        },
+        PromptType::Select => match choice_value(value) {
+            Some(s) if is_choice(prompt, &s) => None,
+            Some(s) => error(
+                error_codes::INVALID_FIELD_VALUE,
+                format!("'{}' is not one of: {}", s, choice_list(prompt)),
+            ),
+            None => error(
+                error_codes::INVALID_FIELD_TYPE,
+                "Must be one of the choices",
+            ),
+        },
+        PromptType::Multiselect => {
+            let Some(items) = value.as_array() else {
+                return error(error_codes::INVALID_FIELD_TYPE, "Must be a list of choices");
+            };
+            let mut seen = Vec::new();
+            for item in items {
+                let Some(item) = choice_value(item) else {
+                    return error(error_codes::INVALID_FIELD_TYPE, "Must be a list of choices");
+                };
+                if !is_choice(prompt, &item) {
+                    return error(
+                        error_codes::INVALID_FIELD_VALUE,
+                        format!("'{}' is not one of: {}", item, choice_list(prompt)),
+                    );
+                }
+                if seen.contains(&item) {
+                    return error(
+                        error_codes::INVALID_FIELD_VALUE,
+                        format!("'{}' is selected more than once", item),
+                    );
+                }
+                seen.push(item);
+            }
+            None
+        }
+        PromptType::Unknown => None,
+    }
+}
+
+/// Validate answers against a manifest's prompts
+///
+/// `when` conditions see unanswered prompts at their resolved defaults, as in the CLI.
+pub fn check_answers(manifest: &ManifestFile, answers: &Map<String, Value>) -> AnswerErrors {
+    let mut errors = AnswerErrors::new();
+    let context = with_defaults(manifest, answers);
+
+    for prompt in &manifest.prompts {
+        if prompt.hidden {
+            continue;
+        }
+        if let Some(when) = &prompt.when {
+            if !evaluate_when(when, &context) {
+                continue;
+            }
+        }
+
+        // `false` and `0` are answers; only null, "" and [] count as empty. An empty
+        // answer replaces the default, an absent one does not.
+        let problem = match answers.get(&prompt.id) {
+            Some(value) if !is_empty(value) => check_value(prompt, value),
+            Some(_) if prompt.required => required(),
+            None if prompt.required && prompt.default.is_none() => required(),
+            _ => None,
+        };
+        if let Some(problem) = problem {
+            errors.insert(prompt.id.clone(), problem);
+        }
+    }
+
+    errors
+}
+
+/// Validate answers against the manifest of `template_path`
+pub fn check_template_answers(
+    template_path: &Path,
+    answers: &Map<String, Value>,
+) -> Result<AnswerErrors, String> {
+    let manifest = load_manifest(template_path)?;
+    Ok(check_answers(&manifest, answers))
+}
+
+/// Check answers for a template's prompt form; an empty map means every answer is valid
+#[tauri::command]
+pub async fn validate_answers(
+    app: tauri::AppHandle,
+    template_path: String,
+    answers: Map<String, Value>,
+) -> Result<AnswerErrors, String> {
+    let template_path = crate::resolve_template_path(&template_path, &app)?;
+    check_template_answers(&template_path, &answers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MANIFEST: &str = r#"
+metadata:
+  name: demo
+prompts:
+  - id: project_name
+    type: string
+    required: true
+    validator: "^[a-z][a-z0-9-]*$"
+    error_message: Use lowercase letters, digits and dashes
+  - id: use_database
+    type: boolean
+    default: true
+  - id: database
+    type: select
+    required: true
+    choices: [postgres, sqlite]
+    when: use_database
+  - id: port
+    type: int
+    default: 8080
+  - id: features
+    type: multiselect
+    choices: [auth, api]
+  - id: internal
+    type: string
+    required: true
+    hidden: true
+  - id: python
+    type: select
+    choices: [3.11, "3.12"]
+  - id: levels
+    type: multiselect
+    choices: [1, 2, true]
+"#;
+
+    fn check(answers: Value) -> BTreeMap<String, String> {
+        let manifest: ManifestFile = serde_yaml::from_str(MANIFEST).unwrap();
+        let Value::Object(answers) = answers else {
+            unreachable!()
+        };
+        check_answers(&manifest, &answers)
+            .into_iter()
+            .map(|(id, e)| (id, e.code))
+            .collect()
+    }
+
+    fn codes(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+        pairs
+            .iter()
+            .map(|(id, code)| (id.to_string(), code.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn accepts_valid_answers() {
+        let answers = serde_json::json!({
+            "project_name": "my-app",
+            "database": "sqlite",
+            "port": 3000.0,
+            "features": ["auth", "api"],
+        });
+        assert_eq!(check(answers), codes(&[]));
+    }
+
+    #[test]
+    fn scalar_answers_match_stringified_choices() {
+        let answers = serde_json::json!({
+            "project_name": "app",
+            "database": "sqlite",
+            "python": 3.11,
+            "levels": [1, "2", true],
+        });
+        assert_eq!(check(answers), codes(&[]));
+
+        let answers = serde_json::json!({
+            "project_name": "app",
+            "database": "sqlite",
+            "python": "3.12",
+            "levels": [2, 3],
+        });
+        assert_eq!(
+            check(answers),
+            codes(&[("levels", error_codes::INVALID_FIELD_VALUE)])
+        );
+
+        let answers = serde_json::json!({
+            "project_name": "app",
+            "database": "sqlite",
+            "python": ["3.11"],
+            "levels": [1, 1.0, [2]],
+        });
+        assert_eq!(
+            check(answers),
+            codes(&[
+                ("python", error_codes::INVALID_FIELD_TYPE),
+                ("levels", error_codes::INVALID_FIELD_VALUE),
+            ])
+        );
+    }
+
+    #[test]
+    fn when_conditions_see_unanswered_defaults() {
+        // `use_database` defaults to true, so `database` is asked and required
+        let answers = serde_json::json!({ "project_name": "app" });
+        assert_eq!(
+            check(answers),
+            codes(&[("database", error_codes::MISSING_REQUIRED_FIELD)])
+        );
+
+        let answers = serde_json::json!({ "project_name": "app", "use_database": false });
+        assert_eq!(check(answers), codes(&[]));
+    }
+
+    #[test]
+    fn empty_answers_replace_defaults() {
+        let answers = serde_json::json!({
+            "project_name": "",
+            "use_database": false,
+            "port": null,
+        });
+        assert_eq!(
+            check(answers),
+            codes(&[("project_name", error_codes::MISSING_REQUIRED_FIELD)])
+        );
+    }
+
+    #[test]
+    fn reports_wrong_types_and_values() {
+        let answers = serde_json::json!({
+            "project_name": "My App",
+            "use_database": "yes",
+            "database": "mysql",
+            "port": 80.5,
+            "features": ["auth", "auth"],
+        });
+        assert_eq!(
+            check(answers),
+            codes(&[
+                ("database", error_codes::INVALID_FIELD_VALUE),
+                ("features", error_codes::INVALID_FIELD_VALUE),
+                ("port", error_codes::INVALID_FIELD_TYPE),
+                ("project_name", error_codes::INVALID_FIELD_VALUE),
+                ("use_database", error_codes::INVALID_FIELD_TYPE),
+            ])
+        );
+    }
+
+    #[test]
+    fn validator_failures_use_the_error_message() {
+        let manifest: ManifestFile = serde_yaml::from_str(MANIFEST).unwrap();
+        let answers = serde_json::json!({ "project_name": "1app", "use_database": false });
+        let Value::Object(answers) = answers else {
+            unreachable!()
+        };
+        assert_eq!(
+            check_answers(&manifest, &answers)["project_name"].message,
+            "Use lowercase letters, digits and dashes"
+        );
+    }
+}
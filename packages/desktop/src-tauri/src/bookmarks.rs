@@ -0,0 +1,539 @@
+//! Seed bookmarks
+//!
+//! Named favourites holding a seed with its constraints and enrichment, plus tags,
+//! notes and collections. Bookmarks live in the `bookmarks.json` store keyed by id and
+//! can be exported to / imported from a JSON file so a team can share a curated set.
+
+use crate::{EnrichmentConfig, TechStackConfig};
+use chrono::{DateTime, FixedOffset};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use tauri_plugin_store::StoreExt;
+
+/// Store file holding the bookmarks
+pub const BOOKMARKS_STORE: &str = "bookmarks.json";
+
+/// `format` field of export files
+const EXPORT_FORMAT: &str = "upg-bookmarks";
+
+/// Current export file version
+const EXPORT_VERSION: u32 = 1;
+
+/// A saved seed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Bookmark {
+    pub id: String,
+    pub name: String,
+    pub seed: u64,
+    #[serde(default)]
+    pub stack: Option<TechStackConfig>,
+    #[serde(default)]
+    pub enrichment: Option<EnrichmentConfig>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub notes: String,
+    #[serde(default)]
+    pub collections: Vec<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// Fields supplied when creating or updating a bookmark
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BookmarkInput {
+    pub name: String,
+    pub seed: u64,
+    #[serde(default)]
+    pub stack: Option<TechStackConfig>,
+    #[serde(default)]
+    pub enrichment: Option<EnrichmentConfig>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub notes: String,
+    #[serde(default)]
+    pub collections: Vec<String>,
+}
+
+/// Bookmark search filters; all given filters must match
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BookmarkQuery {
+    /// Case-insensitive text matched against name, notes, tags and the seed number
+    #[serde(default)]
+    pub text: Option<String>,
+    /// Bookmarks must carry every one of these tags
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub collection: Option<String>,
+    #[serde(default)]
+    pub archetype: Option<String>,
+    #[serde(default)]
+    pub language: Option<String>,
+}
+
+/// A collection and how many bookmarks it holds
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BookmarkCollection {
+    pub name: String,
+    pub count: usize,
+}
+
+/// Contents of an export file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BookmarkExport {
+    pub format: String,
+    pub version: u32,
+    pub exported_at: String,
+    pub bookmarks: Vec<Bookmark>,
+}
+
+/// An entry of an export file that was not imported
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RejectedBookmark {
+    /// Position in the file's `bookmarks` list
+    pub index: usize,
+    pub id: String,
+    pub name: String,
+    pub reason: String,
+}
+
+/// Result of `import_bookmarks`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BookmarkImportResult {
+    /// New bookmarks added
+    pub added: usize,
+    /// Existing bookmarks replaced by a newer imported version
+    pub updated: usize,
+    /// Imported bookmarks that were older than, or duplicates of, existing ones
+    pub skipped: usize,
+    /// Invalid entries: empty or repeated id or name, or an unreadable `updated_at`
+    pub rejected: Vec<RejectedBookmark>,
+}
+
+/// Trim, drop empties and sort/dedupe a list of labels
+fn normalize_labels(labels: Vec<String>) -> Vec<String> {
+    labels
+        .into_iter()
+        .map(|l| l.trim().to_string())
+        .filter(|l| !l.is_empty())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect()
+}
+
+fn load_all(app: &tauri::AppHandle) -> Result<Vec<Bookmark>, String> {
+    let store = app.store(BOOKMARKS_STORE).map_err(|e| e.to_string())?;
+    let mut bookmarks: Vec<Bookmark> = store
+        .entries()
+        .into_iter()
+        .filter_map(|(_, value)| serde_json::from_value(value).ok())
+        .collect();
+    bookmarks.sort_by(|a, b| {
+        a.name
+            .to_lowercase()
+            .cmp(&b.name.to_lowercase())
+            .then_with(|| a.id.cmp(&b.id))
+    });
+    Ok(bookmarks)
+}
+
+fn save(app: &tauri::AppHandle, bookmarks: &[Bookmark]) -> Result<(), String> {
+    let store = app.store(BOOKMARKS_STORE).map_err(|e| e.to_string())?;
+    for bookmark in bookmarks {
+        let value = serde_json::to_value(bookmark).map_err(|e| e.to_string())?;
+        store.set(bookmark.id.clone(), value);
+    }
+    store
+        .save()
+        .map_err(|e| format!("Failed to save bookmarks: {}", e))
+}
+
+/// Generate an id not used by any existing bookmark
+fn new_id(existing: &[Bookmark]) -> String {
+    let mut stamp = chrono::Utc::now().timestamp_micros();
+    loop {
+        let id = format!("bookmark-{}", stamp);
+        if !existing.iter().any(|b| b.id == id) {
+            return id;
+        }
+        stamp += 1;
+    }
+}
+
+fn matches(bookmark: &Bookmark, query: &BookmarkQuery) -> bool {
+    let stack_field = |field: fn(&TechStackConfig) -> &Option<String>| {
+        bookmark.stack.as_ref().and_then(|s| field(s).as_deref())
+    };
+
+    if let Some(text) = query
+        .text
+        .as_deref()
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+    {
+        let needle = text.to_lowercase();
+        let found = bookmark.name.to_lowercase().contains(&needle)
+            || bookmark.notes.to_lowercase().contains(&needle)
+            || bookmark
+                .tags
+                .iter()
+                .any(|t| t.to_lowercase().contains(&needle))
+            || bookmark.seed.to_string() == text;
+        if !found {
+            return false;
+        }
+    }
+
+    query.tags.iter().all(|tag| bookmark.tags.contains(tag))
+        && query
+            .collection
+            .as_ref()
+            .map_or(true, |c| bookmark.collections.contains(c))
+        && query
+            .archetype
+            .as_deref()
+            .map_or(true, |a| stack_field(|s| &s.archetype) == Some(a))
+        && query
+            .language
+            .as_deref()
+            .map_or(true, |l| stack_field(|s| &s.language) == Some(l))
+}
+
+fn parse_timestamp(value: &str) -> Option<DateTime<FixedOffset>> {
+    DateTime::parse_from_rfc3339(value).ok()
+}
+
+/// Why an imported entry cannot be merged, checked against the entries before it
+fn import_problem(
+    bookmark: &Bookmark,
+    ids: &BTreeSet<String>,
+    names: &BTreeSet<String>,
+) -> Option<String> {
+    if bookmark.id.is_empty() {
+        Some("empty id".to_string())
+    } else if bookmark.name.is_empty() {
+        Some("empty name".to_string())
+    } else if ids.contains(&bookmark.id) {
+        Some(format!("id '{}' appears more than once", bookmark.id))
+    } else if names.contains(&bookmark.name.to_lowercase()) {
+        Some(format!("name '{}' appears more than once", bookmark.name))
+    } else if parse_timestamp(&bookmark.updated_at).is_none() {
+        Some(format!("invalid updated_at '{}'", bookmark.updated_at))
+    } else {
+        None
+    }
+}
+
+/// Merge imported bookmarks into `existing`, returning the outcome and the bookmarks
+/// to save
+fn merge_import(
+    existing: &mut Vec<Bookmark>,
+    imported: Vec<Bookmark>,
+    collection: Option<&str>,
+) -> (BookmarkImportResult, Vec<Bookmark>) {
+    let mut result = BookmarkImportResult::default();
+    let mut changed = Vec::new();
+    let mut ids = BTreeSet::new();
+    let mut names = BTreeSet::new();
+
+    for (index, mut bookmark) in imported.into_iter().enumerate() {
+        bookmark.id = bookmark.id.trim().to_string();
+        bookmark.name = bookmark.name.trim().to_string();
+        if let Some(reason) = import_problem(&bookmark, &ids, &names) {
+            result.rejected.push(RejectedBookmark {
+                index,
+                id: bookmark.id,
+                name: bookmark.name,
+                reason,
+            });
+            continue;
+        }
+        ids.insert(bookmark.id.clone());
+        names.insert(bookmark.name.to_lowercase());
+
+        if let Some(c) = collection {
+            bookmark.collections.push(c.to_string());
+        }
+        bookmark.tags = normalize_labels(bookmark.tags);
+        bookmark.collections = normalize_labels(bookmark.collections);
+
+        let newer = |current: &Bookmark| {
+            // Stored timestamps that do not parse lose against a valid imported one
+            parse_timestamp(&current.updated_at)
+                .map_or(true, |t| parse_timestamp(&bookmark.updated_at) > Some(t))
+        };
+        match existing.iter().position(|b| b.id == bookmark.id) {
+            Some(i) if newer(&existing[i]) => {
+                existing[i] = bookmark.clone();
+                changed.push(bookmark);
+                result.updated += 1;
+            }
+            Some(_) => result.skipped += 1,
+            None if existing.iter().any(|b| same_generation(b, &bookmark)) => result.skipped += 1,
+            None => {
+                existing.push(bookmark.clone());
+                changed.push(bookmark);
+                result.added += 1;
+            }
+        }
+    }
+
+    (result, changed)
+}
+
+/// Same seed and settings, regardless of name, tags or notes
+fn same_generation(a: &Bookmark, b: &Bookmark) -> bool {
+    let json = |v: &Bookmark| serde_json::to_value((&v.stack, &v.enrichment)).unwrap_or_default();
+    a.seed == b.seed && json(a) == json(b)
+}
+
+/// List all bookmarks, ordered by name
+#[tauri::command]
+pub async fn list_bookmarks(app: tauri::AppHandle) -> Result<Vec<Bookmark>, String> {
+    load_all(&app)
+}
+
+/// Get a bookmark by id
+#[tauri::command]
+pub async fn get_bookmark(app: tauri::AppHandle, id: String) -> Result<Bookmark, String> {
+    load_all(&app)?
+        .into_iter()
+        .find(|b| b.id == id)
+        .ok_or_else(|| format!("Bookmark not found: {}", id))
+}
+
+/// Create a bookmark
+#[tauri::command]
+pub async fn create_bookmark(
+    app: tauri::AppHandle,
+    bookmark: BookmarkInput,
+) -> Result<Bookmark, String> {
+    if bookmark.name.trim().is_empty() {
+        return Err("Bookmark name cannot be empty".to_string());
+    }
+
+    let existing = load_all(&app)?;
+    let now = chrono::Utc::now().to_rfc3339();
+    let created = Bookmark {
+        id: new_id(&existing),
+        name: bookmark.name.trim().to_string(),
+        seed: bookmark.seed,
+        stack: bookmark.stack,
+        enrichment: bookmark.enrichment,
+        tags: normalize_labels(bookmark.tags),
+        notes: bookmark.notes,
+        collections: normalize_labels(bookmark.collections),
+        created_at: now.clone(),
+        updated_at: now,
+    };
+    save(&app, std::slice::from_ref(&created))?;
+    Ok(created)
+}
+
+/// Replace a bookmark's fields
+#[tauri::command]
+pub async fn update_bookmark(
+    app: tauri::AppHandle,
+    id: String,
+    bookmark: BookmarkInput,
+) -> Result<Bookmark, String> {
+    if bookmark.name.trim().is_empty() {
+        return Err("Bookmark name cannot be empty".to_string());
+    }
+
+    let existing = get_bookmark(app.clone(), id).await?;
+    let updated = Bookmark {
+        name: bookmark.name.trim().to_string(),
+        seed: bookmark.seed,
+        stack: bookmark.stack,
+        enrichment: bookmark.enrichment,
+        tags: normalize_labels(bookmark.tags),
+        notes: bookmark.notes,
+        collections: normalize_labels(bookmark.collections),
+        updated_at: chrono::Utc::now().to_rfc3339(),
+        ..existing
+    };
+    save(&app, std::slice::from_ref(&updated))?;
+    Ok(updated)
+}
+
+/// Delete a bookmark
+#[tauri::command]
+pub async fn delete_bookmark(app: tauri::AppHandle, id: String) -> Result<(), String> {
+    let store = app.store(BOOKMARKS_STORE).map_err(|e| e.to_string())?;
+    if !store.delete(&id) {
+        return Err(format!("Bookmark not found: {}", id));
+    }
+    store
+        .save()
+        .map_err(|e| format!("Failed to save bookmarks: {}", e))
+}
+
+/// Search bookmarks by text, tags, collection and stack
+#[tauri::command]
+pub async fn search_bookmarks(
+    app: tauri::AppHandle,
+    query: BookmarkQuery,
+) -> Result<Vec<Bookmark>, String> {
+    Ok(load_all(&app)?
+        .into_iter()
+        .filter(|b| matches(b, &query))
+        .collect())
+}
+
+/// List collections with their bookmark counts
+#[tauri::command]
+pub async fn list_bookmark_collections(
+    app: tauri::AppHandle,
+) -> Result<Vec<BookmarkCollection>, String> {
+    let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+    for bookmark in load_all(&app)? {
+        for collection in bookmark.collections {
+            *counts.entry(collection).or_default() += 1;
+        }
+    }
+    Ok(counts
+        .into_iter()
+        .map(|(name, count)| BookmarkCollection { name, count })
+        .collect())
+}
+
+/// Export bookmarks to a JSON file (all, a selection by id, or one collection)
+///
+/// Returns the number of bookmarks written.
+#[tauri::command]
+pub async fn export_bookmarks(
+    app: tauri::AppHandle,
+    path: String,
+    ids: Option<Vec<String>>,
+    collection: Option<String>,
+) -> Result<usize, String> {
+    let bookmarks: Vec<Bookmark> = load_all(&app)?
+        .into_iter()
+        .filter(|b| ids.as_ref().map_or(true, |ids| ids.contains(&b.id)))
+        .filter(|b| {
+            collection
+                .as_ref()
+                .map_or(true, |c| b.collections.contains(c))
+        })
+        .collect();
+
+    let export = BookmarkExport {
+        format: EXPORT_FORMAT.to_string(),
+        version: EXPORT_VERSION,
+        exported_at: chrono::Utc::now().to_rfc3339(),
+        bookmarks,
+    };
+    let json = serde_json::to_string_pretty(&export)
+        .map_err(|e| format!("Failed to serialize bookmarks: {}", e))?;
+    fs::write(&path, json).map_err(|e| format!("Failed to write {}: {}", path, e))?;
+    Ok(export.bookmarks.len())
+}
+
+/// Import bookmarks from an export file
+///
+/// Bookmarks with a known id replace the existing one only if they were updated more
+/// recently; unknown ids are added unless an existing bookmark already stores the
+/// same seed and settings. `collection` adds every imported bookmark to a collection.
+/// Entries with an empty or repeated id or name are reported in `rejected`.
+#[tauri::command]
+pub async fn import_bookmarks(
+    app: tauri::AppHandle,
+    path: String,
+    collection: Option<String>,
+) -> Result<BookmarkImportResult, String> {
+    let content =
+        fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    let export: BookmarkExport = serde_json::from_str(&content)
+        .map_err(|e| format!("Invalid bookmark file {}: {}", path, e))?;
+    if export.format != EXPORT_FORMAT {
+        return Err(format!(
+            "Not a bookmark export: format is '{}'",
+            export.format
+        ));
+    }
+    if export.version > EXPORT_VERSION {
+        return Err(format!(
+            "Bookmark file version {} is newer than supported version {}",
+            export.version, EXPORT_VERSION
+        ));
+    }
+
+    let mut existing = load_all(&app)?;
+    let (result, changed) = merge_import(&mut existing, export.bookmarks, collection.as_deref());
+
+    save(&app, &changed)?;
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bookmark(id: &str, name: &str, seed: u64, updated_at: &str) -> Bookmark {
+        Bookmark {
+            id: id.to_string(),
+            name: name.to_string(),
+            seed,
+            stack: None,
+            enrichment: None,
+            tags: vec![],
+            notes: String::new(),
+            collections: vec![],
+            created_at: updated_at.to_string(),
+            updated_at: updated_at.to_string(),
+        }
+    }
+
+    #[test]
+    fn compares_timestamps_chronologically() {
+        let mut existing = vec![bookmark("a", "Alpha", 1, "2026-01-01T10:00:00Z")];
+        // 09:00 UTC, although it sorts after the stored value as a string
+        let older = bookmark("a", "Alpha v2", 1, "2026-01-01T11:00:00+02:00");
+
+        let (result, changed) = merge_import(&mut existing, vec![older], None);
+        assert_eq!(result.skipped, 1);
+        assert!(changed.is_empty());
+
+        // 11:00 UTC, although it sorts before the stored value as a string
+        let newer = bookmark("a", "Alpha v3", 1, "2026-01-01T06:00:00-05:00");
+        let (result, _) = merge_import(&mut existing, vec![newer], None);
+        assert_eq!(result.updated, 1);
+        assert_eq!(existing[0].name, "Alpha v3");
+    }
+
+    #[test]
+    fn rejects_empty_and_repeated_ids_and_names() {
+        let mut existing = vec![];
+        let imported = vec![
+            bookmark("a", "Alpha", 1, "2026-01-01T00:00:00Z"),
+            bookmark(" ", "Blank id", 2, "2026-01-01T00:00:00Z"),
+            bookmark("b", "  ", 3, "2026-01-01T00:00:00Z"),
+            bookmark("a ", "Other", 4, "2026-01-01T00:00:00Z"),
+            bookmark("c", "alpha", 5, "2026-01-01T00:00:00Z"),
+            bookmark("d", "Delta", 6, "yesterday"),
+            bookmark("e", "Echo", 7, "2026-01-01T00:00:00Z"),
+        ];
+
+        let (result, changed) = merge_import(&mut existing, imported, Some("shared"));
+
+        assert_eq!(result.added, 2);
+        let rejected: Vec<usize> = result.rejected.iter().map(|r| r.index).collect();
+        assert_eq!(rejected, [1, 2, 3, 4, 5]);
+        assert_eq!(result.rejected[2].reason, "id 'a' appears more than once");
+        assert!(changed.iter().all(|b| b.collections == ["shared"]));
+    }
+
+    #[test]
+    fn skips_new_ids_with_the_same_generation() {
+        let mut existing = vec![bookmark("a", "Alpha", 42, "2026-01-01T00:00:00Z")];
+        let imported = vec![bookmark("b", "Copy", 42, "2026-02-01T00:00:00Z")];
+
+        let (result, changed) = merge_import(&mut existing, imported, None);
+        assert_eq!(result.skipped, 1);
+        assert!(changed.is_empty());
+    }
+}
@@ -0,0 +1,654 @@
+//! Template catalogs
+//!
+//! Catalog files curate templates using the catalog format of `@wcnegentropy/core`
+//! (`template-catalog-schema.ts`): Backstage-style entries with `apiVersion`, `kind`,
+//! `metadata` and `spec`. A file holds one entry, a list of entries or several YAML
+//! documents. UPG-specific fields are annotations:
+//!
+//! - `upg/path`: template directory, relative to the catalog file
+//! - `upg/category`: category to group the template under
+//! - `upg/featured`: `"true"` to feature the template
+//!
+//! Catalogs are listed in the `templateCatalogs` setting (files, or directories whose
+//! `.yaml`/`.yml`/`.json` files are all loaded). A `Template` entry applies to the
+//! discovered template at its path or with its name, overriding the manifest's title,
+//! description, tags, lifecycle and author; an entry whose path holds a template found
+//! under no root adds it, unless a root already has that name and version, in which
+//! case it is recorded as shadowed. The first catalog to describe a template wins;
+//! later entries for it are reported as errors.
+
+use crate::templates::{
+    find_template, load_template, DiscoveredTemplate, TemplateRoot, TemplateRootKind,
+};
+use crate::{discover_templates_with, read_setting, AppPaths};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Settings key holding catalog locations
+pub const TEMPLATE_CATALOGS_SETTING: &str = "templateCatalogs";
+
+const PATH_ANNOTATION: &str = "upg/path";
+const CATEGORY_ANNOTATION: &str = "upg/category";
+const FEATURED_ANNOTATION: &str = "upg/featured";
+
+/// `kind` values allowed by the schema; only `Template` entries are used
+const KINDS: &[&str] = &[
+    "Template",
+    "Component",
+    "API",
+    "Group",
+    "User",
+    "Resource",
+    "System",
+    "Domain",
+];
+
+const LIFECYCLES: &[&str] = &["experimental", "production", "deprecated"];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CatalogLink {
+    pub url: String,
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub icon: Option<String>,
+    #[serde(default, rename = "type")]
+    pub link_type: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct EntryMetadata {
+    name: String,
+    #[serde(default)]
+    title: Option<String>,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    annotations: BTreeMap<String, String>,
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(default)]
+    links: Vec<CatalogLink>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct EntrySpec {
+    #[serde(rename = "type")]
+    template_type: String,
+    lifecycle: String,
+    owner: String,
+}
+
+/// A validated catalog entry
+#[derive(Debug, Clone, Deserialize)]
+struct CatalogEntry {
+    kind: String,
+    metadata: EntryMetadata,
+    spec: EntrySpec,
+}
+
+/// Catalog metadata attached to a template
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CatalogMetadata {
+    /// Catalog file the metadata came from
+    pub catalog: String,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub tags: Vec<String>,
+    pub lifecycle: String,
+    pub owner: String,
+    /// `spec.type` (e.g. service, website, library)
+    #[serde(rename = "type")]
+    pub template_type: String,
+    pub category: Option<String>,
+    pub featured: bool,
+    pub links: Vec<CatalogLink>,
+}
+
+/// Outcome of loading one catalog file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CatalogReport {
+    pub path: String,
+    /// Templates the catalog described or added
+    pub applied: usize,
+    /// Schema violations, unreadable files, entries matching no template and entries
+    /// for templates an earlier catalog already described
+    pub errors: Vec<String>,
+}
+
+/// A parsed catalog file
+pub struct LoadedCatalog {
+    file: PathBuf,
+    entries: Vec<CatalogEntry>,
+    errors: Vec<String>,
+}
+
+/// Catalog locations from the `templateCatalogs` setting value
+pub fn catalog_locations(value: Option<Value>) -> Vec<String> {
+    match value {
+        Some(value) if !value.is_null() => serde_json::from_value(value).unwrap_or_else(|e| {
+            eprintln!(
+                "Ignoring invalid {} setting: {}",
+                TEMPLATE_CATALOGS_SETTING, e
+            );
+            vec![]
+        }),
+        _ => vec![],
+    }
+}
+
+fn is_string(value: &Value, key: &str) -> bool {
+    value.get(key).is_some_and(Value::is_string)
+}
+
+/// Check an entry against the catalog schema
+fn validate_entry(entry: &Value) -> Vec<String> {
+    let mut errors = Vec::new();
+    if !entry.is_object() {
+        return vec!["entry must be an object".to_string()];
+    }
+
+    if !is_string(entry, "apiVersion") {
+        errors.push("apiVersion: required string".to_string());
+    }
+    match entry.get("kind").and_then(Value::as_str) {
+        Some(kind) if KINDS.contains(&kind) => {}
+        Some(kind) => errors.push(format!(
+            "kind: '{}' is not one of {}",
+            kind,
+            KINDS.join(", ")
+        )),
+        None => errors.push("kind: required string".to_string()),
+    }
+
+    match entry.get("metadata") {
+        Some(metadata) if metadata.is_object() => {
+            match metadata.get("name").and_then(Value::as_str) {
+                Some(name) if is_valid_name(name) => {}
+                Some(name) => errors.push(format!(
+                    "metadata.name: '{}' must match ^[a-z0-9][a-z0-9-]*$",
+                    name
+                )),
+                None => errors.push("metadata.name: required string".to_string()),
+            }
+            for key in ["title", "description"] {
+                if metadata.get(key).is_some_and(|v| !v.is_string()) {
+                    errors.push(format!("metadata.{}: must be a string", key));
+                }
+            }
+            for key in ["labels", "annotations"] {
+                let valid = metadata.get(key).map_or(true, |v| {
+                    v.as_object()
+                        .is_some_and(|map| map.values().all(Value::is_string))
+                });
+                if !valid {
+                    errors.push(format!("metadata.{}: must map strings to strings", key));
+                }
+            }
+            let tags_valid = metadata.get("tags").map_or(true, |v| {
+                v.as_array()
+                    .is_some_and(|tags| tags.iter().all(Value::is_string))
+            });
+            if !tags_valid {
+                errors.push("metadata.tags: must be an array of strings".to_string());
+            }
+            match metadata.get("links").map(Value::as_array) {
+                None => {}
+                Some(None) => errors.push("metadata.links: must be an array".to_string()),
+                Some(Some(links)) => {
+                    for (i, link) in links.iter().enumerate() {
+                        let url = link.get("url").and_then(Value::as_str);
+                        if url.map_or(true, |u| url::Url::parse(u).is_err()) {
+                            errors.push(format!("metadata.links[{}].url: required URI", i));
+                        }
+                    }
+                }
+            }
+        }
+        _ => errors.push("metadata: required object".to_string()),
+    }
+
+    match entry.get("spec") {
+        Some(spec) if spec.is_object() => {
+            for key in ["type", "owner"] {
+                if !is_string(spec, key) {
+                    errors.push(format!("spec.{}: required string", key));
+                }
+            }
+            match spec.get("lifecycle").and_then(Value::as_str) {
+                Some(lifecycle) if LIFECYCLES.contains(&lifecycle) => {}
+                _ => errors.push(format!(
+                    "spec.lifecycle: must be one of {}",
+                    LIFECYCLES.join(", ")
+                )),
+            }
+        }
+        _ => errors.push("spec: required object".to_string()),
+    }
+
+    errors
+}
+
+fn is_valid_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_lowercase() || c.is_ascii_digit())
+        && chars.all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+}
+
+/// Parse catalog file contents: one entry, a list, or several YAML documents
+fn parse_catalog(content: &str) -> (Vec<CatalogEntry>, Vec<String>) {
+    let mut raw = Vec::new();
+    let mut errors = Vec::new();
+
+    // YAML is a superset of JSON, so JSON catalogs parse here too
+    for document in serde_yaml::Deserializer::from_str(content) {
+        match Value::deserialize(document) {
+            Ok(Value::Null) => {}
+            Ok(Value::Array(items)) => raw.extend(items),
+            Ok(value) => raw.push(value),
+            Err(e) => {
+                errors.push(format!("Not valid YAML/JSON: {}", e));
+                break;
+            }
+        }
+    }
+
+    let mut entries = Vec::new();
+    for (i, value) in raw.into_iter().enumerate() {
+        let label = value
+            .pointer("/metadata/name")
+            .and_then(Value::as_str)
+            .map(|name| format!("entry {} ({})", i, name))
+            .unwrap_or_else(|| format!("entry {}", i));
+
+        let problems = validate_entry(&value);
+        if !problems.is_empty() {
+            errors.extend(problems.into_iter().map(|p| format!("{}: {}", label, p)));
+            continue;
+        }
+        match serde_json::from_value::<CatalogEntry>(value) {
+            Ok(entry) => entries.push(entry),
+            Err(e) => errors.push(format!("{}: {}", label, e)),
+        }
+    }
+
+    (entries, errors)
+}
+
+fn is_catalog_file(path: &Path) -> bool {
+    path.is_file()
+        && path
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| ["yaml", "yml", "json"].contains(&e))
+}
+
+/// Load the catalogs at the configured locations
+pub fn load_catalogs(paths: &AppPaths, locations: &[String]) -> Vec<LoadedCatalog> {
+    let mut files = Vec::new();
+    for location in locations {
+        let path = PathBuf::from(location);
+        let path = match (&paths.home_dir, path.is_absolute()) {
+            (Some(home), false) => home.join(path),
+            _ => path,
+        };
+
+        if path.is_dir() {
+            let mut in_dir: Vec<PathBuf> = fs::read_dir(&path)
+                .map(|entries| {
+                    entries
+                        .flatten()
+                        .map(|e| e.path())
+                        .filter(|p| is_catalog_file(p))
+                        .collect()
+                })
+                .unwrap_or_default();
+            in_dir.sort();
+            files.extend(in_dir);
+        } else {
+            files.push(path);
+        }
+    }
+
+    files
+        .into_iter()
+        .map(|file| match fs::read_to_string(&file) {
+            Ok(content) => {
+                let (entries, errors) = parse_catalog(&content);
+                LoadedCatalog {
+                    file,
+                    entries,
+                    errors,
+                }
+            }
+            Err(e) => LoadedCatalog {
+                errors: vec![format!("Failed to read catalog: {}", e)],
+                file,
+                entries: vec![],
+            },
+        })
+        .collect()
+}
+
+fn same_dir(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+/// Apply catalog entries to discovered templates, adding templates only catalogs know
+pub fn merge_catalogs(
+    templates: &mut Vec<DiscoveredTemplate>,
+    catalogs: Vec<LoadedCatalog>,
+) -> Vec<CatalogReport> {
+    let base_priority = templates
+        .iter()
+        .map(|t| t.root.priority + 1)
+        .max()
+        .unwrap_or(0);
+    let mut reports = Vec::new();
+
+    for (index, catalog) in catalogs.into_iter().enumerate() {
+        let catalog_path = catalog.file.to_string_lossy().to_string();
+        let catalog_dir = catalog.file.parent().unwrap_or(Path::new("")).to_path_buf();
+        let mut report = CatalogReport {
+            path: catalog_path.clone(),
+            applied: 0,
+            errors: catalog.errors,
+        };
+
+        for entry in catalog.entries.into_iter().filter(|e| e.kind == "Template") {
+            let annotations = &entry.metadata.annotations;
+            let entry_path = annotations
+                .get(PATH_ANNOTATION)
+                .map(|p| catalog_dir.join(p));
+
+            // Entries matched by name describe the template's default version
+            let existing = match &entry_path {
+                Some(path) => templates.iter().position(|t| same_dir(&t.path, path)),
+                None => find_template(templates, &entry.metadata.name, None),
+            };
+            let index_in_templates = match (existing, &entry_path) {
+                (Some(i), _) => i,
+                (None, Some(path)) => {
+                    let Some(manifest) = load_template(path) else {
+                        report.errors.push(format!(
+                            "{}: no template at {}",
+                            entry.metadata.name,
+                            path.display()
+                        ));
+                        continue;
+                    };
+                    // Roots take precedence over catalogs for the same name and version
+                    let meta = &manifest.metadata;
+                    if let Some(winner) = find_template(templates, &meta.name, Some(&meta.version))
+                    {
+                        templates[winner]
+                            .shadows
+                            .push(path.to_string_lossy().to_string());
+                        continue;
+                    }
+                    templates.push(DiscoveredTemplate {
+                        path: path.clone(),
+                        manifest,
+                        root: TemplateRoot {
+                            kind: TemplateRootKind::Catalog,
+                            path: catalog_path.clone(),
+                            priority: base_priority + index,
+                            max_depth: 0,
+                        },
+                        shadows: vec![],
+                        catalog: None,
+                    });
+                    templates.len() - 1
+                }
+                (None, None) => {
+                    report.errors.push(format!(
+                        "{}: no template with this name; set the {} annotation",
+                        entry.metadata.name, PATH_ANNOTATION
+                    ));
+                    continue;
+                }
+            };
+
+            let template = &mut templates[index_in_templates];
+            if let Some(described) = &template.catalog {
+                report.errors.push(format!(
+                    "{}: {} is already described by {}",
+                    entry.metadata.name,
+                    template.path.display(),
+                    described.catalog
+                ));
+                continue;
+            }
+            template.catalog = Some(CatalogMetadata {
+                catalog: catalog_path.clone(),
+                title: entry.metadata.title,
+                description: entry.metadata.description,
+                tags: entry.metadata.tags,
+                lifecycle: entry.spec.lifecycle,
+                owner: entry.spec.owner,
+                template_type: entry.spec.template_type,
+                category: annotations.get(CATEGORY_ANNOTATION).cloned(),
+                featured: annotations
+                    .get(FEATURED_ANNOTATION)
+                    .is_some_and(|v| v.eq_ignore_ascii_case("true")),
+                links: entry.metadata.links,
+            });
+            report.applied += 1;
+        }
+
+        for error in &report.errors {
+            eprintln!("Template catalog {}: {}", report.path, error);
+        }
+        reports.push(report);
+    }
+
+    reports
+}
+
+/// Load the configured catalogs and report what each contributed and any errors
+#[tauri::command]
+pub async fn get_template_catalogs(app: tauri::AppHandle) -> Result<Vec<CatalogReport>, String> {
+    let (_, reports) =
+        discover_templates_with(&AppPaths::from_app(&app), |key| read_setting(&app, key));
+    Ok(reports)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn entry() -> Value {
+        json!({
+            "apiVersion": "backstage.io/v1alpha1",
+            "kind": "Template",
+            "metadata": {
+                "name": "rust-api",
+                "tags": ["rust"],
+                "links": [{"url": "https://example.com/docs", "title": "Docs"}],
+            },
+            "spec": {"type": "service", "lifecycle": "production", "owner": "platform"},
+        })
+    }
+
+    fn with(pointer: &str, value: Value) -> Value {
+        let mut entry = entry();
+        *entry.pointer_mut(pointer).unwrap() = value;
+        entry
+    }
+
+    fn write_template(dir: &Path, name: &str, version: &str) {
+        fs::create_dir_all(dir).unwrap();
+        fs::write(
+            dir.join("upg.yaml"),
+            format!("metadata: {{name: {}, version: {}}}\n", name, version),
+        )
+        .unwrap();
+    }
+
+    fn catalog(file: &Path, content: &str) -> LoadedCatalog {
+        let (entries, errors) = parse_catalog(content);
+        LoadedCatalog {
+            file: file.to_path_buf(),
+            entries,
+            errors,
+        }
+    }
+
+    fn catalog_entry(name: &str, path: Option<&str>, lifecycle: &str) -> String {
+        let annotations = path
+            .map(|p| format!("\n  annotations: {{upg/path: {}}}", p))
+            .unwrap_or_default();
+        format!(
+            "---\napiVersion: v1\nkind: Template\nmetadata:\n  name: {}{}\n\
+             spec: {{type: service, lifecycle: {}, owner: platform}}\n",
+            name, annotations, lifecycle
+        )
+    }
+
+    #[test]
+    fn validates_entries_against_the_schema() {
+        assert!(validate_entry(&entry()).is_empty());
+
+        assert_eq!(
+            validate_entry(&with("/kind", json!("Widget"))),
+            [format!("kind: 'Widget' is not one of {}", KINDS.join(", "))]
+        );
+        for name in ["Rust-API", "-api", "rust_api", ""] {
+            assert_eq!(
+                validate_entry(&with("/metadata/name", json!(name))),
+                [format!(
+                    "metadata.name: '{}' must match ^[a-z0-9][a-z0-9-]*$",
+                    name
+                )]
+            );
+        }
+        assert_eq!(
+            validate_entry(&with("/spec/lifecycle", json!("beta"))),
+            ["spec.lifecycle: must be one of experimental, production, deprecated"]
+        );
+        assert_eq!(
+            validate_entry(&with("/metadata/links/0/url", json!("not a url"))),
+            ["metadata.links[0].url: required URI"]
+        );
+        assert_eq!(
+            validate_entry(&with("/metadata/tags", json!(["rust", 1]))),
+            ["metadata.tags: must be an array of strings"]
+        );
+        assert_eq!(
+            validate_entry(&json!({"kind": "Template"})),
+            [
+                "apiVersion: required string",
+                "metadata: required object",
+                "spec: required object"
+            ]
+        );
+        assert_eq!(validate_entry(&json!([])), ["entry must be an object"]);
+    }
+
+    #[test]
+    fn parses_documents_lists_and_json() {
+        let documents = format!(
+            "{}{}---\n",
+            catalog_entry("one", None, "production"),
+            catalog_entry("two", None, "beta")
+        );
+        let (entries, errors) = parse_catalog(&documents);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].metadata.name, "one");
+        assert_eq!(
+            errors,
+            ["entry 1 (two): spec.lifecycle: must be one of experimental, production, deprecated"]
+        );
+
+        let list = format!("[{}, {}]", entry(), with("/metadata/name", json!("web")));
+        let (entries, errors) = parse_catalog(&list);
+        assert!(errors.is_empty());
+        let names: Vec<&str> = entries.iter().map(|e| e.metadata.name.as_str()).collect();
+        assert_eq!(names, ["rust-api", "web"]);
+
+        let (entries, errors) = parse_catalog(&entry().to_string());
+        assert!(errors.is_empty());
+        assert_eq!(entries[0].spec.owner, "platform");
+        assert_eq!(entries[0].metadata.links[0].title.as_deref(), Some("Docs"));
+
+        let (entries, errors) = parse_catalog("metadata: [unclosed\n");
+        assert!(entries.is_empty());
+        assert!(errors[0].starts_with("Not valid YAML/JSON"));
+    }
+
+    #[test]
+    fn merges_entries_into_discovered_templates() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("root");
+        write_template(&root.join("api"), "api", "1.0.0");
+        write_template(&root.join("web"), "web", "1.0.0");
+        write_template(&dir.path().join("extra"), "extra", "1.0.0");
+        write_template(&dir.path().join("api-copy"), "api", "1.0.0");
+        let roots = [TemplateRoot {
+            kind: TemplateRootKind::User,
+            path: root.to_string_lossy().to_string(),
+            priority: 0,
+            max_depth: 1,
+        }];
+        let mut templates = crate::templates::discover_in_roots(&roots);
+        assert_eq!(templates.len(), 2);
+
+        let first = dir.path().join("first.yaml");
+        let second = dir.path().join("second.yaml");
+        let content = [
+            catalog_entry("api", None, "deprecated"),
+            catalog_entry("web", Some("root/web"), "experimental"),
+            catalog_entry("extra", Some("extra"), "production"),
+            catalog_entry("api-copy", Some("api-copy"), "production"),
+            catalog_entry("missing", None, "production"),
+            catalog_entry("gone", Some("gone"), "production"),
+        ]
+        .concat();
+        let reports = merge_catalogs(
+            &mut templates,
+            vec![
+                catalog(&first, &content),
+                catalog(&second, &catalog_entry("web", None, "production")),
+            ],
+        );
+
+        // Matched by name, by path, and added from the catalog
+        let lifecycle = |name: &str| {
+            let index = find_template(&templates, name, None).unwrap();
+            templates[index].catalog.as_ref().unwrap().lifecycle.clone()
+        };
+        assert_eq!(lifecycle("api"), "deprecated");
+        assert_eq!(lifecycle("web"), "experimental");
+        assert_eq!(lifecycle("extra"), "production");
+        let extra = &templates[find_template(&templates, "extra", None).unwrap()];
+        assert_eq!(extra.root.kind, TemplateRootKind::Catalog);
+
+        // A catalog copy of an installed name and version is shadowed, not added
+        assert_eq!(templates.len(), 3);
+        let api = &templates[find_template(&templates, "api", None).unwrap()];
+        assert_eq!(
+            api.shadows,
+            [dir.path().join("api-copy").to_string_lossy().to_string()]
+        );
+
+        assert_eq!(reports[0].applied, 3);
+        assert_eq!(reports[0].errors.len(), 2);
+        assert!(reports[0].errors[0].starts_with("missing: no template with this name"));
+        assert!(reports[0].errors[1].starts_with("gone: no template at"));
+
+        // The first catalog to describe a template wins; later ones are reported
+        assert_eq!(reports[1].applied, 0);
+        assert_eq!(reports[1].errors.len(), 1);
+        assert!(reports[1].errors[0].starts_with("web: "));
+        assert!(reports[1].errors[0].contains("already described by"));
+    }
+}
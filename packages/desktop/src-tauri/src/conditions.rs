@@ -0,0 +1,459 @@
+//! `when` clause parsing and evaluation
+//!
+//! Same semantics as `parseWhenExpression`/`evaluateWhenClause` in
+//! packages/core/src/transpiler/conditional-logic.ts: `field`, `not field`,
+//! `field == value` and `field != value`, with values parsed as `true`, `false`,
+//! `null`/`none`, quoted strings, integers, decimals or otherwise bare strings, and
+//! compared strictly (`1 == "1"` does not hold). The literals `true` and `false` are also
+//! accepted on their own, as used by `command` actions (`when: "true"`).
+//!
+//! Expressions that do not parse evaluate to `true` (show the field / run the action),
+//! as in TypeScript; `parse_when` reports why and where.
+
+use crate::jinja::with_defaults;
+use crate::manifest::{load_manifest, ManifestFile};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// A parsed `when` expression
+#[derive(Debug, Clone, PartialEq)]
+pub enum WhenExpr {
+    /// `true` or `false`
+    Literal(bool),
+    /// `field`
+    Truthy(String),
+    /// `not field`
+    Falsy(String),
+    /// `field == value` (`negated: false`) or `field != value`
+    Compare {
+        field: String,
+        negated: bool,
+        value: Value,
+    },
+}
+
+/// Why an expression did not parse
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WhenParseError {
+    pub message: String,
+    /// Character offset in the expression where parsing failed
+    pub position: usize,
+}
+
+/// JavaScript-style truthiness of an answer
+fn truthy(value: Option<&Value>) -> bool {
+    match value {
+        None | Some(Value::Null) => false,
+        Some(Value::Bool(b)) => *b,
+        Some(Value::Number(n)) => n.as_f64().is_some_and(|f| f != 0.0),
+        Some(Value::String(s)) => !s.is_empty(),
+        Some(Value::Array(_)) | Some(Value::Object(_)) => true,
+    }
+}
+
+fn is_identifier(s: &str) -> bool {
+    identifier_len(s) == s.len() && !s.is_empty()
+}
+
+/// Byte length of the identifier (`[A-Za-z_][A-Za-z0-9_]*`) at the start of `s`
+fn identifier_len(s: &str) -> usize {
+    let mut chars = s.char_indices();
+    match chars.next() {
+        Some((_, c)) if c.is_ascii_alphabetic() || c == '_' => {}
+        _ => return 0,
+    }
+    chars
+        .find(|(_, c)| !(c.is_ascii_alphanumeric() || *c == '_'))
+        .map_or(s.len(), |(i, _)| i)
+}
+
+fn is_digits(s: &str) -> bool {
+    !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit())
+}
+
+/// Parse the right-hand side of a comparison the way the TypeScript evaluator does
+fn parse_literal(raw: &str) -> Value {
+    let raw = raw.trim();
+    let unsigned = raw.strip_prefix('-').unwrap_or(raw);
+    match raw {
+        "true" => return Value::Bool(true),
+        "false" => return Value::Bool(false),
+        "null" | "none" => return Value::Null,
+        _ => {}
+    }
+
+    // /^["'].*["']$/: any quote at each end, not necessarily the same one
+    let is_quote = |c: Option<char>| matches!(c, Some('"') | Some('\''));
+    if raw.chars().count() >= 2 && is_quote(raw.chars().next()) && is_quote(raw.chars().last()) {
+        let inner = &raw[1..raw.len() - 1];
+        return Value::String(inner.to_string());
+    }
+
+    if is_digits(unsigned) {
+        return match raw.parse::<i64>() {
+            Ok(i) => Value::from(i),
+            // Beyond i64, parseInt loses precision the same way
+            Err(_) => raw.parse::<f64>().map(Value::from).unwrap_or(Value::Null),
+        };
+    }
+    if let Some((whole, fraction)) = unsigned.split_once('.') {
+        if is_digits(whole) && is_digits(fraction) {
+            if let Ok(f) = raw.parse::<f64>() {
+                return Value::from(f);
+            }
+        }
+    }
+    Value::String(raw.to_string())
+}
+
+/// JavaScript `===` between an answer and a literal
+fn strict_equal(actual: Option<&Value>, expected: &Value) -> bool {
+    match (actual, expected) {
+        // A missing answer is `undefined`, which equals nothing, not even `null`
+        (None, _) => false,
+        (Some(Value::Number(x)), Value::Number(y)) => x.as_f64() == y.as_f64(),
+        (Some(Value::Array(_)), _) | (Some(Value::Object(_)), _) => false,
+        (Some(a), b) => a == b,
+    }
+}
+
+/// Character offset of byte offset `byte` in `s`
+fn char_position(s: &str, byte: usize) -> usize {
+    s[..byte].chars().count()
+}
+
+/// Parse a `when` expression
+pub fn parse_when(expression: &str) -> Result<WhenExpr, WhenParseError> {
+    let start = expression.len() - expression.trim_start().len();
+    let trimmed = expression.trim();
+    let error = |message: String, byte: usize| WhenParseError {
+        message,
+        position: char_position(expression, start + byte),
+    };
+
+    match trimmed {
+        "" => return Err(error("Empty condition".to_string(), 0)),
+        "true" => return Ok(WhenExpr::Literal(true)),
+        "false" => return Ok(WhenExpr::Literal(false)),
+        _ => {}
+    }
+
+    let negation = trimmed.strip_prefix("not ").map(|rest| {
+        let field = rest.trim();
+        let offset = trimmed.len() - rest.trim_start().len();
+        (field, offset)
+    });
+    if let Some((field, _)) = negation {
+        if is_identifier(field) {
+            return Ok(WhenExpr::Falsy(field.to_string()));
+        }
+    }
+
+    let field_len = identifier_len(trimmed);
+    if field_len == 0 {
+        return Err(match negation {
+            Some((_, offset)) => error("Expected a field name after 'not'".to_string(), offset),
+            None => error("Expected a field name".to_string(), 0),
+        });
+    }
+    let field = &trimmed[..field_len];
+    let after_field = &trimmed[field_len..];
+    if after_field.is_empty() {
+        return Ok(WhenExpr::Truthy(field.to_string()));
+    }
+
+    let op_start = trimmed.len() - after_field.trim_start().len();
+    let after_ws = &trimmed[op_start..];
+    let negated = if after_ws.starts_with("==") {
+        false
+    } else if after_ws.starts_with("!=") {
+        true
+    } else {
+        // `not x y` reads better as an error about the negation
+        return Err(match negation {
+            Some((_, offset)) => error("Expected a field name after 'not'".to_string(), offset),
+            None => error(format!("Expected '==' or '!=' after '{}'", field), op_start),
+        });
+    };
+
+    let value_part = &trimmed[op_start + 2..];
+    let value_start = trimmed.len() - value_part.trim_start().len();
+    let raw = value_part.trim();
+    if raw.is_empty() {
+        let op = if negated { "!=" } else { "==" };
+        return Err(error(
+            format!("Expected a value after '{}'", op),
+            trimmed.len(),
+        ));
+    }
+    // The TypeScript pattern does not match across lines
+    if let Some(newline) = raw.find('\n') {
+        return Err(error(
+            "Unexpected line break".to_string(),
+            value_start + newline,
+        ));
+    }
+
+    Ok(WhenExpr::Compare {
+        field: field.to_string(),
+        negated,
+        value: parse_literal(raw),
+    })
+}
+
+impl WhenExpr {
+    /// Evaluate against the current answers
+    pub fn evaluate(&self, context: &Map<String, Value>) -> bool {
+        match self {
+            WhenExpr::Literal(value) => *value,
+            WhenExpr::Truthy(field) => truthy(context.get(field)),
+            WhenExpr::Falsy(field) => !truthy(context.get(field)),
+            WhenExpr::Compare {
+                field,
+                negated,
+                value,
+            } => strict_equal(context.get(field), value) != *negated,
+        }
+    }
+}
+
+/// Evaluate a `when` expression against the current answers
+///
+/// Unparseable expressions evaluate to `true` (show the field / run the action),
+/// matching the TypeScript evaluator.
+pub fn evaluate_when(expression: &str, context: &Map<String, Value>) -> bool {
+    parse_when(expression).map_or(true, |expr| expr.evaluate(context))
+}
+
+/// A prompt whose `when` does not parse
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WhenError {
+    pub prompt_id: String,
+    pub expression: String,
+    #[serde(flatten)]
+    pub error: WhenParseError,
+}
+
+/// Prompts to show for the current answers
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VisiblePrompts {
+    /// Ids of the prompts to show, in manifest order (hidden prompts are never shown)
+    pub visible: Vec<String>,
+    /// Conditions that do not parse; their prompts are shown
+    pub errors: Vec<WhenError>,
+}
+
+/// Prompts shown for a (partial) set of answers; unanswered prompts count at their
+/// resolved defaults, as in `validate_answers` and generation
+pub fn visible_prompts(manifest: &ManifestFile, answers: &Map<String, Value>) -> VisiblePrompts {
+    let context = with_defaults(manifest, answers);
+    let mut visible = Vec::new();
+    let mut errors = Vec::new();
+    for prompt in manifest.prompts.iter().filter(|p| !p.hidden) {
+        let shown = match prompt.when.as_deref().map(|w| (w, parse_when(w))) {
+            None => true,
+            Some((_, Ok(expr))) => expr.evaluate(&context),
+            Some((expression, Err(error))) => {
+                errors.push(WhenError {
+                    prompt_id: prompt.id.clone(),
+                    expression: expression.to_string(),
+                    error,
+                });
+                true
+            }
+        };
+        if shown {
+            visible.push(prompt.id.clone());
+        }
+    }
+
+    VisiblePrompts { visible, errors }
+}
+
+/// Compute which prompts of a template are shown for a (partial) set of answers
+#[tauri::command]
+pub async fn get_visible_prompts(
+    app: tauri::AppHandle,
+    template_path: String,
+    answers: Map<String, Value>,
+) -> Result<VisiblePrompts, String> {
+    let template_path = crate::resolve_template_path(&template_path, &app)?;
+    let manifest = load_manifest(&template_path)?;
+    Ok(visible_prompts(&manifest, &answers))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn eval(expression: &str, answers: Value) -> bool {
+        let Value::Object(answers) = answers else {
+            unreachable!()
+        };
+        evaluate_when(expression, &answers)
+    }
+
+    fn parse_error(expression: &str) -> (String, usize) {
+        let error = parse_when(expression).unwrap_err();
+        (error.message, error.position)
+    }
+
+    // Cases from tests/unit/transpiler/conditional-logic.test.ts
+
+    #[test]
+    fn evaluates_truthy_check() {
+        assert!(eval("use_db", json!({ "use_db": true })));
+        assert!(!eval("use_db", json!({ "use_db": false })));
+        assert!(eval("use_db", json!({ "use_db": "yes" })));
+        assert!(!eval("use_db", json!({ "use_db": "" })));
+    }
+
+    #[test]
+    fn evaluates_falsy_check() {
+        assert!(!eval("not use_db", json!({ "use_db": true })));
+        assert!(eval("not use_db", json!({ "use_db": false })));
+    }
+
+    #[test]
+    fn evaluates_equality_check() {
+        assert!(eval(
+            "framework == 'react'",
+            json!({ "framework": "react" })
+        ));
+        assert!(!eval("framework == 'react'", json!({ "framework": "vue" })));
+    }
+
+    #[test]
+    fn evaluates_inequality_check() {
+        assert!(eval("framework != 'react'", json!({ "framework": "vue" })));
+        assert!(!eval(
+            "framework != 'react'",
+            json!({ "framework": "react" })
+        ));
+    }
+
+    #[test]
+    fn evaluates_boolean_equality() {
+        assert!(eval("enabled == true", json!({ "enabled": true })));
+        assert!(eval("enabled == false", json!({ "enabled": false })));
+    }
+
+    #[test]
+    fn evaluates_numeric_equality() {
+        assert!(eval("count == 5", json!({ "count": 5 })));
+        assert!(!eval("count == 5", json!({ "count": 3 })));
+    }
+
+    #[test]
+    fn returns_true_for_unparseable_expressions() {
+        assert!(eval("complex && expression", json!({})));
+    }
+
+    // Behaviour of conditional-logic.ts beyond the TypeScript tests
+
+    #[test]
+    fn compares_strictly() {
+        assert!(!eval("count == '5'", json!({ "count": 5 })));
+        assert!(!eval("count == 5", json!({ "count": "5" })));
+        assert!(eval("ratio == 0.5", json!({ "ratio": 0.5 })));
+        assert!(eval("count == 5", json!({ "count": 5.0 })));
+        assert!(eval("name == react", json!({ "name": "react" })));
+        assert!(eval("value == null", json!({ "value": null })));
+        assert!(eval("value == none", json!({ "value": null })));
+        assert!(!eval("tags == 'a'", json!({ "tags": ["a"] })));
+    }
+
+    #[test]
+    fn missing_answers_are_undefined() {
+        assert!(!eval("use_db", json!({})));
+        assert!(eval("not use_db", json!({})));
+        assert!(!eval("value == null", json!({})));
+        assert!(eval("value != null", json!({})));
+    }
+
+    #[test]
+    fn parses_literals_and_mixed_quotes() {
+        assert_eq!(parse_when(" true ").unwrap(), WhenExpr::Literal(true));
+        assert_eq!(parse_when("false").unwrap(), WhenExpr::Literal(false));
+        assert_eq!(
+            parse_when("a == \"x'").unwrap(),
+            WhenExpr::Compare {
+                field: "a".to_string(),
+                negated: false,
+                value: json!("x"),
+            }
+        );
+        assert_eq!(
+            parse_when("a!=-3").unwrap(),
+            WhenExpr::Compare {
+                field: "a".to_string(),
+                negated: true,
+                value: json!(-3),
+            }
+        );
+    }
+
+    #[test]
+    fn reports_error_positions() {
+        assert_eq!(parse_error(""), ("Empty condition".to_string(), 0));
+        assert_eq!(parse_error("   "), ("Empty condition".to_string(), 3));
+        assert_eq!(
+            parse_error("1 == x"),
+            ("Expected a field name".to_string(), 0)
+        );
+        assert_eq!(
+            parse_error("complex && expression"),
+            ("Expected '==' or '!=' after 'complex'".to_string(), 8)
+        );
+        assert_eq!(
+            parse_error("not 1abc"),
+            ("Expected a field name after 'not'".to_string(), 4)
+        );
+        assert_eq!(
+            parse_error("not a b"),
+            ("Expected a field name after 'not'".to_string(), 4)
+        );
+        assert_eq!(
+            parse_error("  x =="),
+            ("Expected a value after '=='".to_string(), 6)
+        );
+        assert_eq!(
+            parse_error("x == 'a\nb'"),
+            ("Unexpected line break".to_string(), 7)
+        );
+        // Positions count characters, not bytes
+        assert_eq!(
+            parse_error("\u{3000}x y"),
+            ("Expected '==' or '!=' after 'x'".to_string(), 3)
+        );
+    }
+
+    #[test]
+    fn unanswered_prompts_count_at_their_defaults() {
+        let manifest: ManifestFile = serde_yaml::from_str(
+            "metadata: {name: demo}\n\
+             prompts:\n\
+             - {id: use_database, type: boolean, default: true}\n\
+             - {id: database, type: select, choices: [postgres], when: use_database}\n\
+             - {id: cache, type: boolean, when: 'not use_database'}\n\
+             - {id: secret, type: string, hidden: true}\n\
+             - {id: broken, type: string, when: 'a && b'}\n",
+        )
+        .unwrap();
+        let visible = |answers: Value| {
+            let Value::Object(answers) = answers else {
+                unreachable!()
+            };
+            visible_prompts(&manifest, &answers)
+        };
+
+        let shown = visible(json!({}));
+        assert_eq!(shown.visible, ["use_database", "database", "broken"]);
+        assert_eq!(shown.errors.len(), 1);
+        assert_eq!(shown.errors[0].prompt_id, "broken");
+
+        let shown = visible(json!({ "use_database": false }));
+        assert_eq!(shown.visible, ["use_database", "cache", "broken"]);
+    }
+}
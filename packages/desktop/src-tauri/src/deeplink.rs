@@ -0,0 +1,436 @@
+//! `upg://` deep links
+//!
+//! Links such as `upg://seed/42?archetype=backend&language=rust&enrich=full` or
+//! `upg://recipe?path=/path/to/recipe.yaml` open the app pre-configured. Links are
+//! parsed and validated here and forwarded to the window as a `deep-link://request`
+//! event; nothing is generated until the user confirms in the UI. Recipe links only
+//! carry the file's path: the window reads it with `import_recipe` once the user has
+//! agreed to import it.
+//!
+//! Links that arrive before the frontend is listening (e.g. the one that launched the
+//! app) are queued until it calls `take_pending_deep_links`. Command-line requests
+//! forwarded from a second launch (see `instance`) travel the same way.
+
+use crate::{EnrichmentConfig, GenerationMode, GenerationRequest, TechStackConfig};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::{Emitter, Manager};
+use tauri_plugin_deep_link::DeepLinkExt;
+
+/// URL scheme handled by the app (also declared in tauri.conf.json)
+pub const DEEP_LINK_SCHEME: &str = "upg";
+
+/// Event carrying a parsed deep link
+pub const DEEP_LINK_EVENT: &str = "deep-link://request";
+
+/// Archetypes accepted in links (mirrors Archetype in @wcnegentropy/procedural)
+const ARCHETYPES: &[&str] = &[
+    "web", "backend", "cli", "mobile", "desktop", "game", "library",
+];
+
+/// Languages accepted in links (mirrors Language in @wcnegentropy/procedural)
+const LANGUAGES: &[&str] = &[
+    "typescript",
+    "javascript",
+    "python",
+    "go",
+    "rust",
+    "java",
+    "csharp",
+    "cpp",
+    "swift",
+    "kotlin",
+    "php",
+    "ruby",
+];
+
+const ENRICH_LEVELS: &[&str] = &["off", "minimal", "standard", "full"];
+
+/// Extensions of files that recipe links may point at
+const RECIPE_EXTENSIONS: &[&str] = &["json", "yaml", "yml"];
+
+/// Parsed deep link or forwarded command line, as emitted to the frontend
+///
+/// `source` is the link or the space-joined arguments that produced the request.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum DeepLinkPayload {
+    /// `upg://seed/<seed>` or `--seed <seed>`: a procedural request with an empty `output_path`
+    Seed {
+        source: String,
+        request: GenerationRequest,
+    },
+    /// `upg://recipe?path=<file>`: an absolute `.json`, `.yaml` or `.yml` path, not yet read
+    Recipe { source: String, path: String },
+    /// A template directory (or its `upg.yaml`) passed on the command line
+    Template {
+        source: String,
+        template_path: String,
+    },
+    /// A link or command line that could not be parsed or validated
+    Invalid { source: String, error: String },
+}
+
+/// Links received before the frontend is ready
+#[derive(Default)]
+pub struct PendingDeepLinks(Mutex<PendingState>);
+
+#[derive(Default)]
+struct PendingState {
+    /// Set once the frontend has drained the queue; later links are only emitted
+    frontend_ready: bool,
+    queue: Vec<DeepLinkPayload>,
+}
+
+/// Validate a stack value: known values for archetype/language, a safe slug otherwise
+fn check_value(key: &str, value: &str) -> Result<String, String> {
+    let value = value.trim().to_lowercase();
+    let allowed = match key {
+        "archetype" => Some(ARCHETYPES),
+        "language" => Some(LANGUAGES),
+        _ => None,
+    };
+
+    match allowed {
+        Some(list) if !list.contains(&value.as_str()) => Err(format!(
+            "Unknown {} '{}'; expected one of {}",
+            key,
+            value,
+            list.join(", ")
+        )),
+        Some(_) => Ok(value),
+        None => {
+            let valid = !value.is_empty()
+                && value.len() <= 64
+                && value
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || "-_.+".contains(c));
+            if valid {
+                Ok(value)
+            } else {
+                Err(format!("Invalid {} '{}'", key, value))
+            }
+        }
+    }
+}
+
+/// Build a procedural request from a seed and `archetype`/`language`/.../`enrich` parameters
+pub(crate) fn seed_request(
+    seed: &str,
+    params: &[(String, String)],
+) -> Result<GenerationRequest, String> {
+    let seed: u64 = seed
+        .parse()
+        .map_err(|_| format!("Invalid seed '{}': expected a non-negative integer", seed))?;
+
+    let mut stack = TechStackConfig {
+        archetype: None,
+        language: None,
+        framework: None,
+        database: None,
+        packaging: None,
+        cicd: None,
+    };
+    let mut enrichment = None;
+    let mut seen = HashSet::new();
+
+    for (key, value) in params {
+        if !seen.insert(key) {
+            return Err(format!("Parameter '{}' given more than once", key));
+        }
+        let slot = match key.as_ref() {
+            "archetype" => &mut stack.archetype,
+            "language" => &mut stack.language,
+            "framework" => &mut stack.framework,
+            "database" => &mut stack.database,
+            "packaging" => &mut stack.packaging,
+            "cicd" => &mut stack.cicd,
+            "enrich" => {
+                let level = value.trim().to_lowercase();
+                if !ENRICH_LEVELS.contains(&level.as_str()) {
+                    return Err(format!(
+                        "Invalid enrich '{}'; expected one of {}",
+                        value,
+                        ENRICH_LEVELS.join(", ")
+                    ));
+                }
+                enrichment = Some(EnrichmentConfig {
+                    enabled: level != "off",
+                    depth: if level == "off" {
+                        "standard".to_string()
+                    } else {
+                        level
+                    },
+                    cicd: None,
+                    release: None,
+                    fill_logic: None,
+                    tests: None,
+                    docker_prod: None,
+                    linting: None,
+                    env_files: None,
+                    docs: None,
+                });
+                continue;
+            }
+            other => return Err(format!("Unknown parameter '{}'", other)),
+        };
+        *slot = Some(check_value(key, value)?);
+    }
+
+    let has_constraints = [
+        &stack.archetype,
+        &stack.language,
+        &stack.framework,
+        &stack.database,
+        &stack.packaging,
+        &stack.cicd,
+    ]
+    .iter()
+    .any(|v| v.is_some());
+
+    Ok(GenerationRequest {
+        mode: GenerationMode::Procedural,
+        seed: Some(seed),
+        stack: has_constraints.then_some(stack),
+        output_path: String::new(),
+        enrichment,
+    })
+}
+
+fn parse_recipe_link(url: &url::Url) -> Result<String, String> {
+    let mut path = None;
+    for (key, value) in url.query_pairs() {
+        match key.as_ref() {
+            "path" if path.is_none() => path = Some(value.to_string()),
+            "path" => return Err("Parameter 'path' given more than once".to_string()),
+            other => return Err(format!("Unknown parameter '{}'", other)),
+        }
+    }
+    let path = path.ok_or("Recipe links need a 'path' parameter")?;
+
+    let file = PathBuf::from(&path);
+    if !file.is_absolute() {
+        return Err(format!("Recipe path must be absolute: {}", path));
+    }
+    let known = file
+        .extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| RECIPE_EXTENSIONS.contains(&e.to_lowercase().as_str()));
+    if !known {
+        return Err(format!(
+            "Recipe path must end in .{}: {}",
+            RECIPE_EXTENSIONS.join(", ."),
+            path
+        ));
+    }
+    Ok(path)
+}
+
+/// Parse and validate a deep link; recipe files are not read here
+pub fn parse_deep_link(raw: &str) -> DeepLinkPayload {
+    let invalid = |error: String| DeepLinkPayload::Invalid {
+        source: raw.to_string(),
+        error,
+    };
+
+    let url = match url::Url::parse(raw) {
+        Ok(url) => url,
+        Err(e) => return invalid(format!("Malformed link: {}", e)),
+    };
+    if url.scheme() != DEEP_LINK_SCHEME {
+        return invalid(format!("Unsupported scheme '{}'", url.scheme()));
+    }
+
+    // `upg://seed/42` parses with host `seed` and path `/42`
+    let segments: Vec<&str> = url
+        .host_str()
+        .into_iter()
+        .chain(url.path().split('/'))
+        .filter(|s| !s.is_empty())
+        .collect();
+
+    let result = match segments.as_slice() {
+        ["seed", seed] => {
+            let params: Vec<(String, String)> = url.query_pairs().into_owned().collect();
+            seed_request(seed, &params).map(|request| DeepLinkPayload::Seed {
+                source: raw.to_string(),
+                request,
+            })
+        }
+        ["recipe"] => parse_recipe_link(&url).map(|path| DeepLinkPayload::Recipe {
+            source: raw.to_string(),
+            path,
+        }),
+        _ => Err(format!(
+            "Unknown link '{}'; expected upg://seed/<seed> or upg://recipe?path=<file>",
+            raw
+        )),
+    };
+    result.unwrap_or_else(invalid)
+}
+
+/// Forward a parsed request to the window, or queue it until the frontend is ready
+pub(crate) fn dispatch(app: &tauri::AppHandle, payload: DeepLinkPayload) {
+    {
+        let pending = app.state::<PendingDeepLinks>();
+        let mut state = pending.0.lock().unwrap();
+        if !state.frontend_ready {
+            state.queue.push(payload);
+            return;
+        }
+    }
+
+    if let Err(e) = app.emit(DEEP_LINK_EVENT, &payload) {
+        eprintln!("Failed to emit deep link event: {}", e);
+    }
+    focus_main_window(app);
+}
+
+/// Bring the main window to the front
+pub(crate) fn focus_main_window(app: &tauri::AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.unminimize();
+        let _ = window.set_focus();
+    }
+}
+
+/// Parse links and forward them to the window
+pub fn handle_urls(app: &tauri::AppHandle, urls: Vec<String>) {
+    for url in urls {
+        dispatch(app, parse_deep_link(&url));
+    }
+}
+
+/// Register the scheme and start listening for links (called from setup)
+pub fn register(app: &tauri::AppHandle) {
+    // macOS registers schemes from the bundle's Info.plist; elsewhere do it at runtime
+    #[cfg(any(windows, target_os = "linux"))]
+    if let Err(e) = app.deep_link().register_all() {
+        eprintln!("Failed to register {}:// links: {}", DEEP_LINK_SCHEME, e);
+    }
+
+    match app.deep_link().get_current() {
+        Ok(Some(urls)) => handle_urls(app, urls.iter().map(|u| u.to_string()).collect()),
+        Ok(None) => {}
+        Err(e) => eprintln!("Failed to read launch deep link: {}", e),
+    }
+
+    let handle = app.clone();
+    app.deep_link().on_open_url(move |event| {
+        handle_urls(
+            &handle,
+            event.urls().iter().map(|u| u.to_string()).collect(),
+        );
+    });
+}
+
+/// Drain links received before the frontend started listening
+///
+/// Call once after subscribing to `deep-link://request`; later links arrive as events.
+#[tauri::command]
+pub async fn take_pending_deep_links(
+    pending: tauri::State<'_, PendingDeepLinks>,
+) -> Result<Vec<DeepLinkPayload>, String> {
+    let mut state = pending.0.lock().unwrap();
+    state.frontend_ready = true;
+    Ok(std::mem::take(&mut state.queue))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(raw: &str) -> DeepLinkPayload {
+        parse_deep_link(raw)
+    }
+
+    fn error(payload: DeepLinkPayload) -> String {
+        match payload {
+            DeepLinkPayload::Invalid { error, .. } => error,
+            other => panic!("expected an invalid payload, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn parses_seed_links() {
+        let raw = "upg://seed/42?archetype=Backend&language=rust&framework=axum&enrich=full";
+        let DeepLinkPayload::Seed { source, request } = parse(raw) else {
+            panic!("expected a seed payload");
+        };
+
+        assert_eq!(source, raw);
+        assert_eq!(request.seed, Some(42));
+        assert_eq!(request.output_path, "");
+        let stack = request.stack.unwrap();
+        assert_eq!(stack.archetype.as_deref(), Some("backend"));
+        assert_eq!(stack.language.as_deref(), Some("rust"));
+        assert_eq!(stack.framework.as_deref(), Some("axum"));
+        let enrichment = request.enrichment.unwrap();
+        assert!(enrichment.enabled);
+        assert_eq!(enrichment.depth, "full");
+    }
+
+    #[test]
+    fn seed_links_without_parameters_have_no_constraints() {
+        let DeepLinkPayload::Seed { request, .. } = parse("upg://seed/7") else {
+            panic!("expected a seed payload");
+        };
+        assert!(request.stack.is_none());
+        assert!(request.enrichment.is_none());
+    }
+
+    #[test]
+    fn enrich_off_disables_enrichment() {
+        let request = seed_request("1", &[("enrich".to_string(), "off".to_string())]).unwrap();
+        let enrichment = request.enrichment.unwrap();
+        assert!(!enrichment.enabled);
+        assert_eq!(enrichment.depth, "standard");
+    }
+
+    #[test]
+    fn rejects_invalid_seed_links() {
+        assert!(error(parse("upg://seed/-1")).contains("Invalid seed"));
+        assert!(error(parse("upg://seed/abc")).contains("Invalid seed"));
+        assert!(error(parse("upg://seed/1?language=cobol")).contains("Unknown language"));
+        assert!(error(parse("upg://seed/1?framework=a%20b")).contains("Invalid framework"));
+        assert!(error(parse("upg://seed/1?enrich=max")).contains("Invalid enrich"));
+        assert!(error(parse("upg://seed/1?colour=red")).contains("Unknown parameter 'colour'"));
+        assert!(error(parse("upg://seed/1?cicd=none&cicd=none")).contains("more than once"));
+    }
+
+    #[test]
+    fn rejects_unknown_links() {
+        assert!(error(parse("not a link")).contains("Malformed link"));
+        assert!(error(parse("https://seed/1")).contains("Unsupported scheme"));
+        assert!(error(parse("upg://open/1")).contains("Unknown link"));
+        assert!(error(parse("upg://seed/1/2")).contains("Unknown link"));
+    }
+
+    #[test]
+    fn recipe_links_need_one_absolute_path() {
+        assert!(error(parse("upg://recipe")).contains("need a 'path'"));
+        assert!(error(parse("upg://recipe?path=r.yaml")).contains("must be absolute"));
+        assert!(error(parse("upg://recipe?path=/a&path=/b")).contains("more than once"));
+        assert!(error(parse("upg://recipe?path=/a&x=1")).contains("Unknown parameter 'x'"));
+    }
+
+    #[test]
+    fn recipe_links_carry_the_path_without_reading_it() {
+        for (raw, expected) in [
+            ("upg://recipe?path=%2Fmissing%2Fr.yaml", "/missing/r.yaml"),
+            ("upg://recipe?path=/missing/r.YML", "/missing/r.YML"),
+            ("upg://recipe?path=/missing/r.json", "/missing/r.json"),
+        ] {
+            let DeepLinkPayload::Recipe { path, .. } = parse(raw) else {
+                panic!("expected a recipe payload for {}", raw);
+            };
+            assert_eq!(path, expected);
+        }
+
+        assert!(error(parse("upg://recipe?path=/etc/passwd")).contains("must end in"));
+        assert!(error(parse("upg://recipe?path=/home/u/.ssh/id_rsa")).contains("must end in"));
+        assert!(error(parse("upg://recipe?path=/tmp/yaml")).contains("must end in"));
+    }
+}
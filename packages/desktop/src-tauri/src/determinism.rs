@@ -0,0 +1,523 @@
+//! Seed determinism verification
+//!
+//! Generates (or previews) a seed several times, optionally with a second engine
+//! binary, hashes every file and reports files whose content differs between runs.
+//! A seed that passes can be published with confidence that it reproduces exactly.
+
+use crate::diff::unified_diff;
+use crate::files::sha256_hex;
+use crate::update::{regenerate_with, GenerationSpec};
+use crate::{
+    cli_version, get_cli_command, run_preview_with, AppPaths, EnrichmentConfig, GenerationMode,
+    GenerationRequest, TechStackConfig,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Label of the engine bundled with the desktop
+const BUNDLED_ENGINE: &str = "bundled";
+
+/// Most runs per engine a check may ask for
+const MAX_RUNS: usize = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DeterminismMode {
+    /// In-memory `upg preview` (fast)
+    #[default]
+    Preview,
+    /// Full `upg seed` into a temporary directory
+    Generate,
+}
+
+/// Determinism check request from frontend
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeterminismRequest {
+    pub seed: u64,
+    #[serde(default)]
+    pub stack: Option<TechStackConfig>,
+    #[serde(default)]
+    pub enrichment: Option<EnrichmentConfig>,
+    /// Runs per engine (at least 2 when no alternate engine is given, at most 20)
+    #[serde(default = "default_runs")]
+    pub runs: usize,
+    #[serde(default)]
+    pub mode: DeterminismMode,
+    /// Second engine to compare against: a upg binary or a `.js` CLI entry point
+    #[serde(default)]
+    pub alternate_engine: Option<String>,
+}
+
+fn default_runs() -> usize {
+    3
+}
+
+/// One generation of the seed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeterminismRun {
+    /// `bundled` or the alternate engine path
+    pub engine: String,
+    /// 1-based run number for this engine
+    pub run: usize,
+    pub files: usize,
+    /// SHA-256 over every path and file hash; equal fingerprints mean identical output
+    pub fingerprint: String,
+    pub duration_ms: u64,
+}
+
+/// One distinct version of a nondeterministic file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileVariant {
+    /// `None` when the file was missing from these runs
+    pub sha256: Option<String>,
+    /// Runs that produced this version, as `engine#run`
+    pub runs: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VariationScope {
+    /// Differs between runs of the same engine
+    Runs,
+    /// Stable per engine but differs between engines
+    Engines,
+}
+
+/// A file whose content was not the same in every run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NondeterministicFile {
+    pub path: String,
+    pub scope: VariationScope,
+    pub variants: Vec<FileVariant>,
+    /// Unified diff between the first two variants (text files only)
+    pub diff: Option<String>,
+}
+
+/// Result of `verify_seed_determinism`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeterminismReport {
+    pub seed: u64,
+    pub mode: DeterminismMode,
+    /// Engine label → reported version
+    pub engines: BTreeMap<String, String>,
+    pub runs: Vec<DeterminismRun>,
+    /// Each engine produced identical output on all of its runs
+    pub deterministic: bool,
+    /// Both engines' first runs are identical (`None` without an alternate engine)
+    pub engines_agree: Option<bool>,
+    pub files_checked: usize,
+    pub nondeterministic: Vec<NondeterministicFile>,
+    pub duration_ms: u64,
+}
+
+/// CLI command for an engine path (`.js` entry points run through node)
+fn engine_command(path: &str) -> (String, Vec<String>) {
+    let is_script = [".js", ".mjs", ".cjs"]
+        .iter()
+        .any(|ext| path.ends_with(ext));
+    if is_script {
+        ("node".to_string(), vec![path.to_string()])
+    } else {
+        (path.to_string(), vec![])
+    }
+}
+
+fn fingerprint(files: &BTreeMap<String, String>) -> String {
+    let listing: String = files
+        .iter()
+        .map(|(path, hash)| format!("{}\0{}\n", path, hash))
+        .collect();
+    sha256_hex(listing.as_bytes())
+}
+
+/// Generate the seed once with the given engine
+fn generate_once(
+    app: &tauri::AppHandle,
+    cli: (String, Vec<String>),
+    request: &DeterminismRequest,
+) -> Result<BTreeMap<String, Vec<u8>>, String> {
+    match request.mode {
+        DeterminismMode::Preview => {
+            let preview_request = GenerationRequest {
+                mode: GenerationMode::Procedural,
+                seed: Some(request.seed),
+                stack: request.stack.clone(),
+                output_path: String::new(),
+                enrichment: request.enrichment.clone(),
+            };
+            let preview = run_preview_with(&AppPaths::from_app(app), cli, &preview_request)?;
+            Ok(preview
+                .files
+                .into_iter()
+                .map(|(path, content)| (path, content.into_bytes()))
+                .collect())
+        }
+        DeterminismMode::Generate => {
+            let spec = GenerationSpec::Procedural {
+                seed: request.seed,
+                stack: request.stack.clone(),
+                enrichment: request.enrichment.clone(),
+            };
+            regenerate_with(app, cli, &spec)
+        }
+    }
+}
+
+/// Files produced by one run of an engine
+struct RunOutput {
+    engine: String,
+    /// 1-based run number for this engine
+    run: usize,
+    files: BTreeMap<String, Vec<u8>>,
+    duration_ms: u64,
+}
+
+/// What comparing every run's output found
+struct RunComparison {
+    runs: Vec<DeterminismRun>,
+    deterministic: bool,
+    engines_agree: Option<bool>,
+    files_checked: usize,
+    nondeterministic: Vec<NondeterministicFile>,
+}
+
+/// Runs to make per engine: at least 2 with a single engine, never more than 20
+fn runs_per_engine(requested: usize, engines: usize) -> Result<usize, String> {
+    if requested > MAX_RUNS {
+        return Err(format!(
+            "At most {} runs per engine can be checked, got {}",
+            MAX_RUNS, requested
+        ));
+    }
+    Ok(if engines > 1 {
+        requested.max(1)
+    } else {
+        requested.max(2)
+    })
+}
+
+fn verify(
+    app: &tauri::AppHandle,
+    request: &DeterminismRequest,
+) -> Result<DeterminismReport, String> {
+    let start = std::time::Instant::now();
+
+    let mut engines = vec![(BUNDLED_ENGINE.to_string(), get_cli_command(app)?)];
+    if let Some(path) = &request.alternate_engine {
+        engines.push((path.clone(), engine_command(path)));
+    }
+    let runs_per_engine = runs_per_engine(request.runs, engines.len())?;
+
+    let mut versions = BTreeMap::new();
+    let mut outputs = Vec::new();
+    for (engine, (cmd, args)) in &engines {
+        versions.insert(engine.clone(), cli_version(cmd, args.clone()));
+
+        for run in 1..=runs_per_engine {
+            let run_start = std::time::Instant::now();
+            let files = generate_once(app, (cmd.clone(), args.clone()), request)
+                .map_err(|e| format!("Run {} with {} engine failed: {}", run, engine, e))?;
+            outputs.push(RunOutput {
+                engine: engine.clone(),
+                run,
+                files,
+                duration_ms: run_start.elapsed().as_millis() as u64,
+            });
+        }
+    }
+
+    let labels: Vec<String> = engines.into_iter().map(|(engine, _)| engine).collect();
+    let comparison = compare_runs(&labels, outputs);
+    Ok(DeterminismReport {
+        seed: request.seed,
+        mode: request.mode,
+        engines: versions,
+        runs: comparison.runs,
+        deterministic: comparison.deterministic,
+        engines_agree: comparison.engines_agree,
+        files_checked: comparison.files_checked,
+        nondeterministic: comparison.nondeterministic,
+        duration_ms: start.elapsed().as_millis() as u64,
+    })
+}
+
+/// Fingerprint every run and report files whose content differs between runs
+fn compare_runs(engines: &[String], outputs: Vec<RunOutput>) -> RunComparison {
+    let mut runs = Vec::new();
+    // (label, engine, path → hash) per run, plus contents for diffing
+    let mut hashes: Vec<(String, String, BTreeMap<String, String>)> = Vec::new();
+    let mut contents: BTreeMap<(String, String), Vec<u8>> = BTreeMap::new();
+
+    for output in outputs {
+        let file_hashes: BTreeMap<String, String> = output
+            .files
+            .iter()
+            .map(|(path, content)| (path.clone(), sha256_hex(content)))
+            .collect();
+
+        for (path, content) in output.files {
+            let key = (path.clone(), file_hashes[&path].clone());
+            contents.entry(key).or_insert(content);
+        }
+
+        runs.push(DeterminismRun {
+            engine: output.engine.clone(),
+            run: output.run,
+            files: file_hashes.len(),
+            fingerprint: fingerprint(&file_hashes),
+            duration_ms: output.duration_ms,
+        });
+        hashes.push((
+            format!("{}#{}", output.engine, output.run),
+            output.engine,
+            file_hashes,
+        ));
+    }
+
+    let all_paths: BTreeSet<&String> = hashes.iter().flat_map(|(_, _, h)| h.keys()).collect();
+    let mut nondeterministic = Vec::new();
+
+    for path in &all_paths {
+        // Distinct versions in first-seen order
+        let mut variants: Vec<FileVariant> = Vec::new();
+        for (label, _, file_hashes) in &hashes {
+            let hash = file_hashes.get(*path).cloned();
+            match variants.iter_mut().find(|v| v.sha256 == hash) {
+                Some(v) => v.runs.push(label.clone()),
+                None => variants.push(FileVariant {
+                    sha256: hash,
+                    runs: vec![label.clone()],
+                }),
+            }
+        }
+        if variants.len() < 2 {
+            continue;
+        }
+
+        let varies_within_engine = engines.iter().any(|engine| {
+            let per_engine: BTreeSet<Option<&String>> = hashes
+                .iter()
+                .filter(|(_, e, _)| e == engine)
+                .map(|(_, _, h)| h.get(*path))
+                .collect();
+            per_engine.len() > 1
+        });
+
+        let text = |variant: &FileVariant| -> Option<String> {
+            match &variant.sha256 {
+                None => Some(String::new()),
+                Some(hash) => {
+                    let bytes = contents.get(&((*path).clone(), hash.clone()))?;
+                    String::from_utf8(bytes.clone()).ok()
+                }
+            }
+        };
+        let diff = match (text(&variants[0]), text(&variants[1])) {
+            (Some(old), Some(new)) => Some(unified_diff(path, &old, &new).0),
+            _ => None,
+        };
+
+        nondeterministic.push(NondeterministicFile {
+            path: (*path).clone(),
+            scope: if varies_within_engine {
+                VariationScope::Runs
+            } else {
+                VariationScope::Engines
+            },
+            variants,
+            diff,
+        });
+    }
+
+    let deterministic = nondeterministic
+        .iter()
+        .all(|f| f.scope != VariationScope::Runs);
+    let first_runs: BTreeSet<&String> = runs
+        .iter()
+        .filter(|r| r.run == 1)
+        .map(|r| &r.fingerprint)
+        .collect();
+    let engines_agree = (engines.len() > 1).then_some(first_runs.len() == 1);
+
+    RunComparison {
+        files_checked: all_paths.len(),
+        runs,
+        deterministic,
+        engines_agree,
+        nondeterministic,
+    }
+}
+
+/// Check that a seed produces byte-identical output on every run
+///
+/// Runs the seed `runs` times per engine (the bundled engine plus, optionally, an
+/// alternate engine binary), hashes every file and reports files that differ, with a
+/// diff between the first two versions seen. More than 20 runs are rejected.
+#[tauri::command]
+pub async fn verify_seed_determinism(
+    app: tauri::AppHandle,
+    request: DeterminismRequest,
+) -> Result<DeterminismReport, String> {
+    tokio::task::spawn_blocking(move || verify(&app, &request))
+        .await
+        .map_err(|e| format!("Determinism check failed: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn output(engine: &str, run: usize, files: &[(&str, &str)]) -> RunOutput {
+        RunOutput {
+            engine: engine.to_string(),
+            run,
+            files: files
+                .iter()
+                .map(|(path, content)| (path.to_string(), content.as_bytes().to_vec()))
+                .collect(),
+            duration_ms: 0,
+        }
+    }
+
+    fn engines(labels: &[&str]) -> Vec<String> {
+        labels.iter().map(|l| l.to_string()).collect()
+    }
+
+    #[test]
+    fn limits_runs_per_engine() {
+        assert_eq!(runs_per_engine(0, 1), Ok(2));
+        assert_eq!(runs_per_engine(0, 2), Ok(1));
+        assert_eq!(runs_per_engine(20, 2), Ok(20));
+        assert!(runs_per_engine(21, 1)
+            .unwrap_err()
+            .contains("At most 20 runs"));
+        assert!(runs_per_engine(usize::MAX, 2).is_err());
+    }
+
+    #[test]
+    fn fingerprints_paths_and_contents() {
+        let hashes = |files: &[(&str, &str)]| -> BTreeMap<String, String> {
+            files
+                .iter()
+                .map(|(path, content)| (path.to_string(), sha256_hex(content.as_bytes())))
+                .collect()
+        };
+        let base = fingerprint(&hashes(&[("a.txt", "a"), ("b.txt", "b")]));
+
+        assert_eq!(
+            base,
+            fingerprint(&hashes(&[("b.txt", "b"), ("a.txt", "a")]))
+        );
+        assert_ne!(
+            base,
+            fingerprint(&hashes(&[("a.txt", "a"), ("b.txt", "c")]))
+        );
+        assert_ne!(
+            base,
+            fingerprint(&hashes(&[("a.txt", "a"), ("c.txt", "b")]))
+        );
+        assert_ne!(base, fingerprint(&hashes(&[("a.txt", "a")])));
+    }
+
+    #[test]
+    fn identical_runs_are_deterministic() {
+        let files = [("README.md", "# App\n"), ("src/main.rs", "fn main() {}\n")];
+        let comparison = compare_runs(
+            &engines(&[BUNDLED_ENGINE]),
+            vec![
+                output(BUNDLED_ENGINE, 1, &files),
+                output(BUNDLED_ENGINE, 2, &files),
+            ],
+        );
+
+        assert!(comparison.deterministic);
+        assert!(comparison.nondeterministic.is_empty());
+        assert_eq!(comparison.engines_agree, None);
+        assert_eq!(comparison.files_checked, 2);
+        assert_eq!(
+            comparison.runs[0].fingerprint,
+            comparison.runs[1].fingerprint
+        );
+        assert_eq!(comparison.runs[1].files, 2);
+    }
+
+    #[test]
+    fn reports_files_that_vary_between_runs() {
+        let comparison = compare_runs(
+            &engines(&[BUNDLED_ENGINE]),
+            vec![
+                output(BUNDLED_ENGINE, 1, &[("id.txt", "one\n"), ("same.txt", "x")]),
+                output(BUNDLED_ENGINE, 2, &[("id.txt", "two\n"), ("same.txt", "x")]),
+                output(BUNDLED_ENGINE, 3, &[("id.txt", "one\n"), ("same.txt", "x")]),
+                output(
+                    BUNDLED_ENGINE,
+                    4,
+                    &[("id.txt", "one\n"), ("same.txt", "x"), ("extra.txt", "")],
+                ),
+            ],
+        );
+
+        assert!(!comparison.deterministic);
+        let paths: Vec<&str> = comparison
+            .nondeterministic
+            .iter()
+            .map(|f| f.path.as_str())
+            .collect();
+        assert_eq!(paths, ["extra.txt", "id.txt"]);
+
+        let id = &comparison.nondeterministic[1];
+        assert_eq!(id.scope, VariationScope::Runs);
+        let runs: Vec<&[String]> = id.variants.iter().map(|v| v.runs.as_slice()).collect();
+        assert_eq!(
+            runs,
+            [
+                &["bundled#1", "bundled#3", "bundled#4"][..],
+                &["bundled#2"][..]
+            ]
+        );
+        let diff = id.diff.as_deref().unwrap();
+        assert!(diff.contains("-one") && diff.contains("+two"));
+
+        // A file missing from some runs is a variant without a hash
+        let extra = &comparison.nondeterministic[0];
+        assert_eq!(extra.variants[0].sha256, None);
+        assert_eq!(extra.variants[1].runs, ["bundled#4"]);
+    }
+
+    #[test]
+    fn separates_engine_differences_from_run_differences() {
+        let alternate = "/opt/upg-next";
+        let comparison = compare_runs(
+            &engines(&[BUNDLED_ENGINE, alternate]),
+            vec![
+                output(BUNDLED_ENGINE, 1, &[("version.txt", "1\n")]),
+                output(BUNDLED_ENGINE, 2, &[("version.txt", "1\n")]),
+                output(alternate, 1, &[("version.txt", "2\n")]),
+                output(alternate, 2, &[("version.txt", "2\n")]),
+            ],
+        );
+
+        assert!(comparison.deterministic);
+        assert_eq!(comparison.engines_agree, Some(false));
+        assert_eq!(
+            comparison.nondeterministic[0].scope,
+            VariationScope::Engines
+        );
+
+        // Binary files are reported without a diff
+        let comparison = compare_runs(
+            &engines(&[BUNDLED_ENGINE]),
+            vec![
+                RunOutput {
+                    files: BTreeMap::from([("logo.png".to_string(), vec![0xff, 0x00])]),
+                    ..output(BUNDLED_ENGINE, 1, &[])
+                },
+                RunOutput {
+                    files: BTreeMap::from([("logo.png".to_string(), vec![0xfe, 0x00])]),
+                    ..output(BUNDLED_ENGINE, 2, &[])
+                },
+            ],
+        );
+        assert!(comparison.nondeterministic[0].diff.is_none());
+    }
+}
@@ -0,0 +1,536 @@
+//! Structural diffs between generated outputs
+//!
+//! Compares two previews (for example seed 42 vs seed 43 under the same constraints):
+//! a field-level stack comparison, added/removed/changed file lists, and a unified
+//! diff per changed file. Per-file diffs are streamed as `diff://file` events so large
+//! projects never block the UI on a single huge payload.
+//!
+//! Also compares a preview with an existing directory before regenerating into it.
+
+use crate::files::{walk_files, IGNORED_DIRS};
+use crate::provenance::PROVENANCE_FILE;
+use crate::{resolve_output_path, run_preview, GenerationRequest, PreviewResult};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use similar::{ChangeTag, TextDiff};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::Path;
+use tauri::Emitter;
+
+/// Event carrying one file's diff
+pub const DIFF_FILE_EVENT: &str = "diff://file";
+/// Event emitted once all file diffs have been sent
+pub const DIFF_COMPLETE_EVENT: &str = "diff://complete";
+
+/// Lines of context around each change in unified diffs
+const CONTEXT_LINES: usize = 3;
+
+/// Change to a single stack dimension
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StackFieldChange {
+    pub field: String,
+    pub left: Value,
+    pub right: Value,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FileChangeKind {
+    Added,
+    Removed,
+    Changed,
+}
+
+/// Diff of one file between the two sides
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileDiff {
+    pub path: String,
+    pub kind: FileChangeKind,
+    /// Unified diff text
+    pub diff: String,
+    pub additions: usize,
+    pub deletions: usize,
+}
+
+/// Payload of `diff://file` events
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileDiffEvent {
+    pub diff_id: String,
+    /// 1-based position of this file among all differing files
+    pub index: usize,
+    pub total: usize,
+    pub file: FileDiff,
+}
+
+/// Summary returned by `diff_previews` (diff text arrives via events)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PreviewDiffSummary {
+    pub diff_id: String,
+    pub left_seed: Option<u64>,
+    pub right_seed: Option<u64>,
+    /// Whether both previews resolved to the same stack
+    pub same_stack: bool,
+    pub stack_changes: Vec<StackFieldChange>,
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub changed: Vec<String>,
+    pub unchanged_count: usize,
+}
+
+/// Build a unified diff between two texts, returning (diff, additions, deletions)
+pub fn unified_diff(path: &str, old: &str, new: &str) -> (String, usize, usize) {
+    let diff = TextDiff::from_lines(old, new);
+
+    let mut additions = 0;
+    let mut deletions = 0;
+    for change in diff.iter_all_changes() {
+        match change.tag() {
+            ChangeTag::Insert => additions += 1,
+            ChangeTag::Delete => deletions += 1,
+            ChangeTag::Equal => {}
+        }
+    }
+
+    let text = diff
+        .unified_diff()
+        .context_radius(CONTEXT_LINES)
+        .header(&format!("a/{}", path), &format!("b/{}", path))
+        .to_string();
+
+    (text, additions, deletions)
+}
+
+/// Diff a file that exists on one or both sides
+pub fn file_diff(path: &str, old: Option<&str>, new: Option<&str>) -> FileDiff {
+    let kind = match (old, new) {
+        (None, _) => FileChangeKind::Added,
+        (_, None) => FileChangeKind::Removed,
+        _ => FileChangeKind::Changed,
+    };
+    let (diff, additions, deletions) =
+        unified_diff(path, old.unwrap_or_default(), new.unwrap_or_default());
+
+    FileDiff {
+        path: path.to_string(),
+        kind,
+        diff,
+        additions,
+        deletions,
+    }
+}
+
+/// Compare two resolved stacks field by field
+fn compare_stacks(left: &Option<Value>, right: &Option<Value>) -> Vec<StackFieldChange> {
+    let empty = serde_json::Map::new();
+    let left = left.as_ref().and_then(|v| v.as_object()).unwrap_or(&empty);
+    let right = right.as_ref().and_then(|v| v.as_object()).unwrap_or(&empty);
+
+    let fields: BTreeSet<&String> = left.keys().chain(right.keys()).collect();
+    fields
+        .into_iter()
+        .filter_map(|field| {
+            let l = left.get(field).cloned().unwrap_or(Value::Null);
+            let r = right.get(field).cloned().unwrap_or(Value::Null);
+            (l != r).then(|| StackFieldChange {
+                field: field.clone(),
+                left: l,
+                right: r,
+            })
+        })
+        .collect()
+}
+
+/// Compare two previews and stream per-file diffs to the frontend
+fn diff_and_stream(
+    app: &tauri::AppHandle,
+    diff_id: &str,
+    left: &PreviewResult,
+    right: &PreviewResult,
+) -> PreviewDiffSummary {
+    let summary = compare_previews(diff_id, left, right, |event| {
+        if let Err(e) = app.emit(DIFF_FILE_EVENT, event) {
+            eprintln!("Failed to emit {}: {}", DIFF_FILE_EVENT, e);
+        }
+    });
+
+    if let Err(e) = app.emit(DIFF_COMPLETE_EVENT, &summary) {
+        eprintln!("Failed to emit {}: {}", DIFF_COMPLETE_EVENT, e);
+    }
+
+    summary
+}
+
+/// Compare two previews, passing each differing file's diff to `emit` in path order
+fn compare_previews(
+    diff_id: &str,
+    left: &PreviewResult,
+    right: &PreviewResult,
+    emit: impl Fn(FileDiffEvent),
+) -> PreviewDiffSummary {
+    let left_files: BTreeMap<&String, &String> = left.files.iter().collect();
+    let right_files: BTreeMap<&String, &String> = right.files.iter().collect();
+
+    let mut added = Vec::new();
+    let mut removed = Vec::new();
+    let mut changed = Vec::new();
+    let mut unchanged_count = 0;
+
+    let paths: BTreeSet<&String> = left_files
+        .keys()
+        .chain(right_files.keys())
+        .copied()
+        .collect();
+    for path in &paths {
+        match (left_files.get(path), right_files.get(path)) {
+            (None, Some(_)) => added.push((*path).clone()),
+            (Some(_), None) => removed.push((*path).clone()),
+            (Some(l), Some(r)) if l != r => changed.push((*path).clone()),
+            _ => unchanged_count += 1,
+        }
+    }
+
+    let differing: Vec<&String> = paths
+        .into_iter()
+        .filter(|p| left_files.get(p) != right_files.get(p))
+        .collect();
+    let total = differing.len();
+
+    for (i, path) in differing.into_iter().enumerate() {
+        let file = file_diff(
+            path,
+            left_files.get(path).map(|s| s.as_str()),
+            right_files.get(path).map(|s| s.as_str()),
+        );
+        emit(FileDiffEvent {
+            diff_id: diff_id.to_string(),
+            index: i + 1,
+            total,
+            file,
+        });
+    }
+
+    let stack_changes = compare_stacks(&left.stack, &right.stack);
+    PreviewDiffSummary {
+        diff_id: diff_id.to_string(),
+        left_seed: left.seed,
+        right_seed: right.seed,
+        same_stack: stack_changes.is_empty(),
+        stack_changes,
+        added,
+        removed,
+        changed,
+        unchanged_count,
+    }
+}
+
+/// Diff the generated output of two preview requests (e.g. seed 42 vs seed 43)
+///
+/// Both previews run concurrently. Unified diffs for each differing file are emitted
+/// as `diff://file` events tagged with `diff_id`; the returned summary lists the stack
+/// changes and the added/removed/changed paths. The caller chooses `diff_id` so it can
+/// filter events before the first one arrives.
+#[tauri::command]
+pub async fn diff_previews(
+    app: tauri::AppHandle,
+    left: GenerationRequest,
+    right: GenerationRequest,
+    diff_id: String,
+) -> Result<PreviewDiffSummary, String> {
+    let left_app = app.clone();
+    let right_app = app.clone();
+    let left_task = tokio::task::spawn_blocking(move || run_preview(&left_app, &left));
+    let right_task = tokio::task::spawn_blocking(move || run_preview(&right_app, &right));
+
+    let left_preview = left_task.await.map_err(|e| e.to_string())??;
+    let right_preview = right_task.await.map_err(|e| e.to_string())??;
+
+    tokio::task::spawn_blocking(move || {
+        diff_and_stream(&app, &diff_id, &left_preview, &right_preview)
+    })
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// Status of a file when comparing a preview with an existing directory
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DirFileStatus {
+    /// Would be created
+    New,
+    /// Already on disk with the same content
+    Identical,
+    /// On disk with different content; would be overwritten
+    Modified,
+    /// On disk but not part of the preview; would be left behind
+    Orphaned,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DirFileEntry {
+    pub path: String,
+    pub status: DirFileStatus,
+    /// Unified diff from the current file to the preview (new and modified text files)
+    pub diff: Option<String>,
+    pub additions: usize,
+    pub deletions: usize,
+}
+
+/// Result of `diff_preview_against_dir`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DirDiffResult {
+    pub dest_path: String,
+    pub dest_exists: bool,
+    pub files: Vec<DirFileEntry>,
+    pub new_count: usize,
+    pub identical_count: usize,
+    pub modified_count: usize,
+    pub orphaned_count: usize,
+}
+
+/// Compare preview files with the current contents of a directory
+///
+/// Every preview path is looked up on disk directly; `IGNORED_DIRS` only limits the
+/// search for orphaned files, so generated files under `dist/` or `target/` are still
+/// compared.
+fn diff_against_dir(dest: &Path, preview: &PreviewResult) -> DirDiffResult {
+    let dest_exists = dest.is_dir();
+    let orphan_candidates: BTreeSet<String> = if dest_exists {
+        walk_files(dest, IGNORED_DIRS)
+            .into_iter()
+            .filter(|p| p != PROVENANCE_FILE)
+            .collect()
+    } else {
+        BTreeSet::new()
+    };
+
+    let mut files = Vec::new();
+    let preview_files: BTreeMap<&String, &String> = preview.files.iter().collect();
+
+    for (path, content) in &preview_files {
+        let target = dest.join(path.as_str());
+        let existing = target.is_file().then(|| fs::read(&target).ok()).flatten();
+
+        let entry = match existing {
+            None => {
+                let (diff, additions, deletions) = unified_diff(path, "", content);
+                DirFileEntry {
+                    path: (*path).clone(),
+                    status: DirFileStatus::New,
+                    diff: Some(diff),
+                    additions,
+                    deletions,
+                }
+            }
+            Some(bytes) if bytes == content.as_bytes() => DirFileEntry {
+                path: (*path).clone(),
+                status: DirFileStatus::Identical,
+                diff: None,
+                additions: 0,
+                deletions: 0,
+            },
+            Some(bytes) => match String::from_utf8(bytes) {
+                Ok(current) => {
+                    let (diff, additions, deletions) = unified_diff(path, &current, content);
+                    DirFileEntry {
+                        path: (*path).clone(),
+                        status: DirFileStatus::Modified,
+                        diff: Some(diff),
+                        additions,
+                        deletions,
+                    }
+                }
+                // Binary on disk: report the overwrite without a text diff
+                Err(_) => DirFileEntry {
+                    path: (*path).clone(),
+                    status: DirFileStatus::Modified,
+                    diff: None,
+                    additions: 0,
+                    deletions: 0,
+                },
+            },
+        };
+        files.push(entry);
+    }
+
+    for path in orphan_candidates
+        .iter()
+        .filter(|p| !preview_files.contains_key(p))
+    {
+        files.push(DirFileEntry {
+            path: path.clone(),
+            status: DirFileStatus::Orphaned,
+            diff: None,
+            additions: 0,
+            deletions: 0,
+        });
+    }
+
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    let count = |status: DirFileStatus| files.iter().filter(|f| f.status == status).count();
+
+    DirDiffResult {
+        dest_path: dest.to_string_lossy().to_string(),
+        dest_exists,
+        new_count: count(DirFileStatus::New),
+        identical_count: count(DirFileStatus::Identical),
+        modified_count: count(DirFileStatus::Modified),
+        orphaned_count: count(DirFileStatus::Orphaned),
+        files,
+    }
+}
+
+/// Show what regenerating into an existing directory would change
+///
+/// Previews `request` and compares the result with the current contents of
+/// `request.output_path`, ignoring VCS, dependency and build directories.
+#[tauri::command]
+pub async fn diff_preview_against_dir(
+    app: tauri::AppHandle,
+    request: GenerationRequest,
+) -> Result<DirDiffResult, String> {
+    let dest = resolve_output_path(&request.output_path, &app)?;
+
+    tokio::task::spawn_blocking(move || {
+        let preview = run_preview(&app, &request)?;
+        Ok(diff_against_dir(&dest, &preview))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn preview(files: &[(&str, &str)]) -> PreviewResult {
+        PreviewResult {
+            files: files
+                .iter()
+                .map(|(path, content)| (path.to_string(), content.to_string()))
+                .collect(),
+            stack: None,
+            seed: Some(42),
+        }
+    }
+
+    fn status(result: &DirDiffResult, path: &str) -> DirFileStatus {
+        result.files.iter().find(|f| f.path == path).unwrap().status
+    }
+
+    #[test]
+    fn compares_stacks_field_by_field() {
+        let left = Some(json!({"language": "rust", "framework": "axum", "database": null}));
+        let right = Some(json!({"language": "rust", "framework": "actix", "cache": "redis"}));
+
+        let changes: Vec<(String, Value, Value)> = compare_stacks(&left, &right)
+            .into_iter()
+            .map(|c| (c.field, c.left, c.right))
+            .collect();
+        assert_eq!(
+            changes,
+            [
+                ("cache".to_string(), Value::Null, json!("redis")),
+                ("framework".to_string(), json!("axum"), json!("actix")),
+            ]
+        );
+
+        assert!(compare_stacks(&left, &left).is_empty());
+        assert!(compare_stacks(&None, &Some(json!("not an object"))).is_empty());
+        assert_eq!(compare_stacks(&None, &left).len(), 2);
+    }
+
+    #[test]
+    fn classifies_files_between_previews() {
+        let mut left = preview(&[
+            ("README.md", "# App\n"),
+            ("src/main.rs", "fn main() {}\n"),
+            ("Cargo.toml", "[package]\nname = \"app\"\n"),
+        ]);
+        left.stack = Some(json!({"framework": "axum"}));
+        let mut right = preview(&[
+            ("README.md", "# App\n"),
+            ("src/main.rs", "fn main() {\n    run();\n}\n"),
+            ("src/lib.rs", "pub fn run() {}\n"),
+        ]);
+        right.seed = Some(43);
+        right.stack = Some(json!({"framework": "actix"}));
+
+        let events = std::sync::Mutex::new(Vec::new());
+        let summary = compare_previews("diff-1", &left, &right, |event| {
+            events.lock().unwrap().push(event)
+        });
+
+        assert_eq!(summary.added, ["src/lib.rs"]);
+        assert_eq!(summary.removed, ["Cargo.toml"]);
+        assert_eq!(summary.changed, ["src/main.rs"]);
+        assert_eq!(summary.unchanged_count, 1);
+        assert_eq!(
+            (summary.left_seed, summary.right_seed),
+            (Some(42), Some(43))
+        );
+        assert!(!summary.same_stack);
+
+        let events = events.into_inner().unwrap();
+        let files: Vec<(&str, FileChangeKind, usize, usize)> = events
+            .iter()
+            .map(|e| {
+                (
+                    e.file.path.as_str(),
+                    e.file.kind,
+                    e.file.additions,
+                    e.file.deletions,
+                )
+            })
+            .collect();
+        assert_eq!(
+            files,
+            [
+                ("Cargo.toml", FileChangeKind::Removed, 0, 2),
+                ("src/lib.rs", FileChangeKind::Added, 1, 0),
+                ("src/main.rs", FileChangeKind::Changed, 3, 1),
+            ]
+        );
+        assert!(events.iter().all(|e| e.diff_id == "diff-1" && e.total == 3));
+        assert_eq!(events[2].index, 3);
+        assert!(events[2].file.diff.contains("+    run();"));
+    }
+
+    #[test]
+    fn compares_preview_files_inside_ignored_dirs() {
+        let dest = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dest.path().join("dist")).unwrap();
+        fs::write(dest.path().join("dist/app.js"), "same\n").unwrap();
+        fs::write(dest.path().join("dist/stale.js"), "old\n").unwrap();
+        fs::write(dest.path().join("README.md"), "old\n").unwrap();
+        fs::write(dest.path().join("notes.txt"), "mine\n").unwrap();
+
+        let result = diff_against_dir(
+            dest.path(),
+            &preview(&[
+                ("dist/app.js", "same\n"),
+                ("README.md", "new\n"),
+                ("src/main.rs", "fn main() {}\n"),
+            ]),
+        );
+
+        assert_eq!(status(&result, "dist/app.js"), DirFileStatus::Identical);
+        assert_eq!(status(&result, "README.md"), DirFileStatus::Modified);
+        assert_eq!(status(&result, "src/main.rs"), DirFileStatus::New);
+        assert_eq!(status(&result, "notes.txt"), DirFileStatus::Orphaned);
+        // Ignored directories are not searched for orphans
+        assert!(!result.files.iter().any(|f| f.path == "dist/stale.js"));
+        assert_eq!(result.orphaned_count, 1);
+    }
+
+    #[test]
+    fn reports_everything_new_for_a_missing_dir() {
+        let dest = tempfile::tempdir().unwrap();
+        let result = diff_against_dir(&dest.path().join("missing"), &preview(&[("a.txt", "a\n")]));
+
+        assert!(!result.dest_exists);
+        assert_eq!(result.new_count, 1);
+        assert_eq!(result.files[0].additions, 1);
+    }
+}
@@ -0,0 +1,25 @@
+//! UPG error codes surfaced by the desktop backend
+//!
+//! Mirrors the constants in packages/shared/src/constants/error-codes.ts so the
+//! frontend can map codes to messages the same way it does for CLI errors.
+
+/// Missing required field
+pub const MISSING_REQUIRED_FIELD: &str = "UPG-100-004";
+/// Invalid field type
+pub const INVALID_FIELD_TYPE: &str = "UPG-100-005";
+/// Invalid field value
+pub const INVALID_FIELD_VALUE: &str = "UPG-100-006";
+
+/// Hook execution failed
+pub const HOOK_FAILED: &str = "UPG-200-006";
+/// Command execution failed
+pub const COMMAND_FAILED: &str = "UPG-200-007";
+/// Generation timeout
+pub const TIMEOUT: &str = "UPG-200-008";
+
+/// No updates available
+pub const NO_UPDATES: &str = "UPG-400-003";
+/// Merge conflict during update
+pub const MERGE_CONFLICT: &str = "UPG-400-004";
+/// Template version has breaking changes for the project
+pub const INCOMPATIBLE_VERSION: &str = "UPG-400-006";
@@ -0,0 +1,381 @@
+//! Directory scanning helpers shared by generation, update, diff and identification commands
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::time::SystemTime;
+
+/// Tool, VCS and dependency directories skipped when scanning existing projects
+pub const IGNORED_DIRS: &[&str] = &[
+    ".git",
+    ".hg",
+    ".svn",
+    "node_modules",
+    "target",
+    "dist",
+    "build",
+    ".next",
+    ".turbo",
+    ".gradle",
+    ".venv",
+    "venv",
+    "__pycache__",
+    ".pytest_cache",
+    ".mypy_cache",
+    "coverage",
+];
+
+/// Directories skipped when listing generated output (created by commands run after
+/// generation or by builds, never by the engine itself)
+pub const GENERATED_IGNORED_DIRS: &[&str] = &[
+    ".git",
+    ".hg",
+    ".svn",
+    "node_modules",
+    ".venv",
+    "__pycache__",
+    "target",
+    "dist",
+    "build",
+    ".next",
+];
+
+/// Bytes inspected when deciding whether a file is binary (same heuristic as git)
+const BINARY_SNIFF_BYTES: usize = 8000;
+
+/// List files under `root` as sorted, '/'-separated relative paths
+///
+/// Directories named in `ignored_dirs` are skipped. Symlinked directories are not
+/// followed, so link cycles cannot recurse forever.
+pub fn walk_files(root: &Path, ignored_dirs: &[&str]) -> Vec<String> {
+    let mut files = Vec::new();
+    walk_into(root, "", ignored_dirs, &mut files);
+    files.sort();
+    files
+}
+
+fn walk_into(dir: &Path, prefix: &str, ignored_dirs: &[&str], files: &mut Vec<String>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        let relative = if prefix.is_empty() {
+            name.clone()
+        } else {
+            format!("{}/{}", prefix, name)
+        };
+
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+
+        if file_type.is_dir() {
+            if !ignored_dirs.contains(&name.as_str()) {
+                walk_into(&entry.path(), &relative, ignored_dirs, files);
+            }
+        } else if file_type.is_file() || entry.path().is_file() {
+            files.push(relative);
+        }
+    }
+}
+
+/// Read every file under `root` into memory, keyed by relative path
+pub fn read_tree(root: &Path, ignored_dirs: &[&str]) -> Result<BTreeMap<String, Vec<u8>>, String> {
+    walk_files(root, ignored_dirs)
+        .into_iter()
+        .map(|relative| {
+            let content = fs::read(root.join(&relative))
+                .map_err(|e| format!("Failed to read {}: {}", relative, e))?;
+            Ok((relative, content))
+        })
+        .collect()
+}
+
+/// Join a project-relative path onto `root`, rejecting paths that escape it
+pub fn project_file(root: &Path, relative: &str) -> Result<PathBuf, String> {
+    let path = Path::new(relative);
+    let escapes = path
+        .components()
+        .any(|c| !matches!(c, Component::Normal(_) | Component::CurDir));
+    if relative.is_empty() || escapes {
+        return Err(format!("Invalid project-relative path: {}", relative));
+    }
+    Ok(root.join(path))
+}
+
+/// What generation did to a file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FileOperation {
+    /// File did not exist before generation
+    Created,
+    /// File existed and was rewritten
+    Overwritten,
+    /// File existed and was left untouched
+    Skipped,
+}
+
+/// Metadata for one generated file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileMetadata {
+    /// '/'-separated path relative to the output directory
+    pub path: String,
+    pub size: u64,
+    pub sha256: String,
+    /// Language detected from the file name, if known
+    pub language: Option<String>,
+    pub binary: bool,
+    pub executable: bool,
+    pub operation: FileOperation,
+}
+
+/// Totals over a set of generated files
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FileSummary {
+    pub total_files: usize,
+    pub total_bytes: u64,
+    pub created: usize,
+    pub overwritten: usize,
+    pub skipped: usize,
+    pub binary_files: usize,
+    pub executable_files: usize,
+    /// File count per detected language
+    pub languages: BTreeMap<String, usize>,
+}
+
+/// State of a file before generation
+#[derive(Debug, Clone, PartialEq)]
+struct SnapshotEntry {
+    modified: Option<SystemTime>,
+    size: u64,
+}
+
+impl SnapshotEntry {
+    fn of(metadata: &fs::Metadata) -> Self {
+        SnapshotEntry {
+            modified: metadata.modified().ok(),
+            size: metadata.len(),
+        }
+    }
+}
+
+/// Files present in an output directory before generation, used to classify operations
+///
+/// Only sizes and modification times are recorded, so capturing a large existing
+/// directory reads no file contents; a file whose size and time are unchanged counts
+/// as skipped.
+#[derive(Debug, Clone, Default)]
+pub struct FileSnapshot(BTreeMap<String, SnapshotEntry>);
+
+impl FileSnapshot {
+    /// Record the files currently under `root` (empty if it does not exist)
+    pub fn capture(root: &Path) -> Self {
+        let entries = walk_files(root, GENERATED_IGNORED_DIRS)
+            .into_iter()
+            .filter_map(|relative| {
+                let metadata = fs::metadata(root.join(&relative)).ok()?;
+                Some((relative, SnapshotEntry::of(&metadata)))
+            })
+            .collect();
+        FileSnapshot(entries)
+    }
+}
+
+/// Hex-encoded SHA-256 of `content`
+pub fn sha256_hex(content: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(content))
+}
+
+/// Whether content looks binary (contains a NUL byte near the start)
+pub fn is_binary(content: &[u8]) -> bool {
+    content[..content.len().min(BINARY_SNIFF_BYTES)].contains(&0)
+}
+
+/// Detect a file's language from its name
+pub fn detect_language(path: &str) -> Option<&'static str> {
+    let name = path.rsplit('/').next().unwrap_or(path);
+    match name {
+        "Dockerfile" | "Containerfile" => return Some("Dockerfile"),
+        "Makefile" | "GNUmakefile" => return Some("Makefile"),
+        "Jenkinsfile" => return Some("Groovy"),
+        _ => {}
+    }
+
+    let ext = name.rsplit_once('.')?.1.to_ascii_lowercase();
+    let language = match ext.as_str() {
+        "rs" => "Rust",
+        "ts" | "tsx" | "mts" | "cts" => "TypeScript",
+        "js" | "jsx" | "mjs" | "cjs" => "JavaScript",
+        "py" | "pyi" => "Python",
+        "go" => "Go",
+        "java" => "Java",
+        "kt" | "kts" => "Kotlin",
+        "swift" => "Swift",
+        "cs" => "C#",
+        "c" | "h" => "C",
+        "cpp" | "cc" | "cxx" | "hpp" | "hh" | "hxx" => "C++",
+        "rb" => "Ruby",
+        "php" => "PHP",
+        "dart" => "Dart",
+        "lua" => "Lua",
+        "gradle" | "groovy" => "Groovy",
+        "vue" => "Vue",
+        "svelte" => "Svelte",
+        "html" | "htm" => "HTML",
+        "css" => "CSS",
+        "scss" | "sass" => "SCSS",
+        "json" => "JSON",
+        "yaml" | "yml" => "YAML",
+        "toml" => "TOML",
+        "xml" => "XML",
+        "md" | "markdown" => "Markdown",
+        "sql" => "SQL",
+        "sh" | "bash" | "zsh" => "Shell",
+        "ps1" => "PowerShell",
+        "proto" => "Protocol Buffers",
+        "graphql" | "gql" => "GraphQL",
+        "njk" => "Nunjucks",
+        _ => return None,
+    };
+    Some(language)
+}
+
+#[cfg(unix)]
+fn is_executable(metadata: &fs::Metadata) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    metadata.permissions().mode() & 0o111 != 0
+}
+
+#[cfg(not(unix))]
+fn is_executable(_metadata: &fs::Metadata) -> bool {
+    false
+}
+
+/// Describe generated files under `root`, sorted by path, with summary totals
+///
+/// `paths` are the files the engine reported (relative to `root`); `None` lists the files
+/// in `root` created or changed since the `before` snapshot, leaving out unrelated files
+/// that were already there. Operations are classified against the `before` snapshot.
+pub fn describe_files(
+    root: &Path,
+    paths: Option<&[String]>,
+    before: &FileSnapshot,
+) -> (Vec<FileMetadata>, FileSummary) {
+    let reported = paths.is_some();
+    let mut paths: Vec<String> = match paths {
+        Some(paths) => paths.iter().map(|p| p.replace('\\', "/")).collect(),
+        None => walk_files(root, GENERATED_IGNORED_DIRS),
+    };
+    paths.sort();
+    paths.dedup();
+
+    let mut summary = FileSummary::default();
+    let files: Vec<FileMetadata> = paths
+        .into_iter()
+        .filter_map(|relative| {
+            let path = root.join(&relative);
+            let metadata = fs::metadata(&path).ok().filter(|m| m.is_file())?;
+            let operation = match before.0.get(&relative) {
+                None => FileOperation::Created,
+                Some(old) if *old == SnapshotEntry::of(&metadata) => FileOperation::Skipped,
+                Some(_) => FileOperation::Overwritten,
+            };
+            // Unchanged files that were not reported are not read at all
+            if !reported && operation == FileOperation::Skipped {
+                return None;
+            }
+            let content = fs::read(&path).ok()?;
+            let sha256 = sha256_hex(&content);
+
+            Some(FileMetadata {
+                language: detect_language(&relative).map(String::from),
+                binary: is_binary(&content),
+                executable: is_executable(&metadata),
+                size: metadata.len(),
+                sha256,
+                operation,
+                path: relative,
+            })
+        })
+        .collect();
+
+    for file in &files {
+        summary.total_files += 1;
+        summary.total_bytes += file.size;
+        match file.operation {
+            FileOperation::Created => summary.created += 1,
+            FileOperation::Overwritten => summary.overwritten += 1,
+            FileOperation::Skipped => summary.skipped += 1,
+        }
+        summary.binary_files += usize::from(file.binary);
+        summary.executable_files += usize::from(file.executable);
+        if let Some(language) = &file.language {
+            *summary.languages.entry(language.clone()).or_default() += 1;
+        }
+    }
+
+    (files, summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unreported_files_are_limited_to_changes() {
+        let root = tempfile::tempdir().unwrap();
+        fs::write(root.path().join("unrelated.txt"), "mine").unwrap();
+        fs::write(root.path().join("README.md"), "old").unwrap();
+        let before = FileSnapshot::capture(root.path());
+
+        fs::write(root.path().join("README.md"), "new").unwrap();
+        fs::write(root.path().join("main.rs"), "fn main() {}").unwrap();
+        let (files, summary) = describe_files(root.path(), None, &before);
+
+        let described: Vec<_> = files
+            .iter()
+            .map(|f| (f.path.as_str(), f.operation))
+            .collect();
+        assert_eq!(
+            described,
+            [
+                ("README.md", FileOperation::Overwritten),
+                ("main.rs", FileOperation::Created),
+            ]
+        );
+        assert_eq!(summary.skipped, 0);
+        assert_eq!(summary.languages["Rust"], 1);
+    }
+
+    #[test]
+    fn reported_files_keep_skipped_entries() {
+        let root = tempfile::tempdir().unwrap();
+        fs::write(root.path().join("kept.txt"), "same").unwrap();
+        let before = FileSnapshot::capture(root.path());
+
+        let reported = vec!["kept.txt".to_string()];
+        let (files, summary) = describe_files(root.path(), Some(&reported), &before);
+
+        assert_eq!(files[0].operation, FileOperation::Skipped);
+        assert_eq!(summary.skipped, 1);
+    }
+
+    #[test]
+    fn build_output_is_not_listed() {
+        let root = tempfile::tempdir().unwrap();
+        let before = FileSnapshot::capture(root.path());
+
+        for dir in ["target/debug", "dist", "build", ".next", "src"] {
+            fs::create_dir_all(root.path().join(dir)).unwrap();
+            fs::write(root.path().join(dir).join("out.js"), "").unwrap();
+        }
+        let (files, _) = describe_files(root.path(), None, &before);
+
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].path, "src/out.js");
+    }
+}
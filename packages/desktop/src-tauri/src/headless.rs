@@ -0,0 +1,414 @@
+//! Headless mode
+//!
+//! `upg-desktop --headless <command> [options]` runs the backend without creating an
+//! app or window (build agents have no display), prints the same JSON the UI receives
+//! and exits:
+//!
+//! - `generate --seed <n> --output <dir> [--archetype ..] [--language ..] [--enrich ..]`
+//! - `preview --seed <n> [--archetype ..] [--language ..] [--enrich ..]`
+//! - `validate <manifest>`
+//! - `templates`
+//!
+//! Stack options are those of `upg://seed` links. Generation writes provenance as usual
+//! but is not added to the history, which lives in the app's store.
+//!
+//! Release builds on Windows are GUI-subsystem executables with no console of their
+//! own; headless mode attaches to the console of the shell that started it before
+//! printing. `cmd.exe` does not wait for GUI executables, so scripts there should use
+//! `start /wait` (or redirect the output) to read the exit code.
+
+use crate::deeplink::seed_request;
+use crate::lifecycle::{lifecycle_policy, TEMPLATE_LIFECYCLE_SETTING};
+use crate::provenance::{write_provenance, ProjectProvenance};
+use crate::{
+    cli_command, cli_version, discover_templates_with, generate_procedural, read_setting_file,
+    run_preview_with, template_entries, validate_manifest, AppPaths,
+};
+use serde::Serialize;
+use std::path::Path;
+
+/// Flag that selects headless mode
+pub const HEADLESS_FLAG: &str = "--headless";
+
+/// The command succeeded
+const EXIT_OK: i32 = 0;
+/// The command ran but reported failure (generation failed, manifest invalid)
+const EXIT_FAILED: i32 = 1;
+/// Bad command line
+const EXIT_USAGE: i32 = 2;
+/// The command could not run (CLI missing, unreadable file, ...)
+const EXIT_ERROR: i32 = 3;
+
+const USAGE: &str = "Usage: upg-desktop --headless <command> [options]
+
+Commands:
+  generate --seed <n> --output <dir> [stack options]
+  preview --seed <n> [stack options]
+  validate <manifest>
+  templates
+
+Stack options: --archetype, --language, --framework, --database, --packaging, --cicd,
+               --enrich <off|minimal|standard|full>
+
+Exit codes: 0 success, 1 failed, 2 usage error, 3 error";
+
+/// Outcome of a headless command
+enum Outcome {
+    Ok(serde_json::Value),
+    Failed(serde_json::Value),
+    Usage(String),
+    Error(String),
+}
+
+fn to_json<T: Serialize>(value: &T) -> serde_json::Value {
+    serde_json::to_value(value).unwrap_or_default()
+}
+
+/// `--name value` pairs in command-line order
+type Options = Vec<(String, String)>;
+
+/// Split `--name value` / `--name=value` options from positional arguments
+fn parse_options(args: &[String]) -> Result<(Options, Vec<String>), String> {
+    let mut options = Vec::new();
+    let mut positional = Vec::new();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let Some(flag) = arg.strip_prefix("--") else {
+            positional.push(arg.clone());
+            continue;
+        };
+        let (name, value) = match flag.split_once('=') {
+            Some((name, value)) => (name.to_string(), value.to_string()),
+            None => {
+                let value = iter
+                    .next()
+                    .ok_or_else(|| format!("--{} needs a value", flag))?;
+                (flag.to_string(), value.clone())
+            }
+        };
+        options.push((name, value));
+    }
+    Ok((options, positional))
+}
+
+/// Remove a required option, returning its value
+fn take_option(options: &mut Options, name: &str) -> Result<String, String> {
+    let index = options
+        .iter()
+        .position(|(n, _)| n == name)
+        .ok_or_else(|| format!("--{} is required", name))?;
+    Ok(options.remove(index).1)
+}
+
+fn generate(paths: &AppPaths, args: &[String]) -> Outcome {
+    let request = parse_options(args).and_then(|(mut options, positional)| {
+        if let Some(extra) = positional.first() {
+            return Err(format!("Unexpected argument '{}'", extra));
+        }
+        let seed = take_option(&mut options, "seed")?;
+        let output = take_option(&mut options, "output")?;
+        let mut request = seed_request(&seed, &options)?;
+        request.output_path = output;
+        Ok(request)
+    });
+    let request = match request {
+        Ok(request) => request,
+        Err(e) => return Outcome::Usage(e),
+    };
+
+    let result = match generate_procedural(paths, &request) {
+        Ok(result) => result,
+        Err(e) => return Outcome::Error(e),
+    };
+    if !result.success {
+        return Outcome::Failed(to_json(&result));
+    }
+
+    let engine_version = cli_command(paths)
+        .map(|(cmd, args)| cli_version(&cmd, args))
+        .unwrap_or_else(|_| "unknown".to_string());
+    let provenance = ProjectProvenance::procedural(&request, engine_version);
+    if let Err(e) = write_provenance(Path::new(&result.output_path), &provenance) {
+        eprintln!(
+            "Failed to record provenance in {}: {}",
+            result.output_path, e
+        );
+    }
+    Outcome::Ok(to_json(&result))
+}
+
+fn preview(paths: &AppPaths, args: &[String]) -> Outcome {
+    let request = parse_options(args).and_then(|(mut options, positional)| {
+        if let Some(extra) = positional.first() {
+            return Err(format!("Unexpected argument '{}'", extra));
+        }
+        let seed = take_option(&mut options, "seed")?;
+        seed_request(&seed, &options)
+    });
+    let request = match request {
+        Ok(request) => request,
+        Err(e) => return Outcome::Usage(e),
+    };
+
+    match cli_command(paths).and_then(|cli| run_preview_with(paths, cli, &request)) {
+        Ok(preview) => Outcome::Ok(to_json(&preview)),
+        Err(e) => Outcome::Error(e),
+    }
+}
+
+fn validate(args: &[String]) -> Outcome {
+    let [path] = args else {
+        return Outcome::Usage("validate takes exactly one manifest path".to_string());
+    };
+
+    match tauri::async_runtime::block_on(validate_manifest(path.clone())) {
+        Ok(result) if result.valid => Outcome::Ok(to_json(&result)),
+        Ok(result) => Outcome::Failed(to_json(&result)),
+        Err(e) => Outcome::Error(e),
+    }
+}
+
+fn templates(paths: &AppPaths, args: &[String]) -> Outcome {
+    if let Some(extra) = args.first() {
+        return Outcome::Usage(format!("Unexpected argument '{}'", extra));
+    }
+
+    let (templates, _) = discover_templates_with(paths, |key| read_setting_file(paths, key));
+    let policy = lifecycle_policy(read_setting_file(paths, TEMPLATE_LIFECYCLE_SETTING));
+    Outcome::Ok(to_json(&template_entries(templates, &policy)))
+}
+
+/// Send stdout/stderr to the parent's console on Windows
+///
+/// `windows_subsystem = "windows"` leaves the process without standard handles, so
+/// output would be lost. Redirected handles are kept; without a parent console (e.g.
+/// launched from Explorer) this does nothing.
+#[cfg(windows)]
+fn attach_console() {
+    use windows_sys::Win32::System::Console::{AttachConsole, ATTACH_PARENT_PROCESS};
+
+    // SAFETY: no pointers involved; failure only means there is no console to attach to
+    unsafe {
+        AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+}
+
+#[cfg(not(windows))]
+fn attach_console() {}
+
+fn run(paths: &AppPaths, command: Option<&str>, args: &[String]) -> Outcome {
+    match command {
+        Some("generate") => generate(paths, args),
+        Some("preview") => preview(paths, args),
+        Some("validate") => validate(args),
+        Some("templates") => templates(paths, args),
+        Some(other) => Outcome::Usage(format!("Unknown command '{}'", other)),
+        None => Outcome::Usage("Missing command".to_string()),
+    }
+}
+
+/// JSON to print and the exit code for an outcome
+fn report(outcome: Outcome) -> (serde_json::Value, i32) {
+    match outcome {
+        Outcome::Ok(value) => (value, EXIT_OK),
+        Outcome::Failed(value) => (value, EXIT_FAILED),
+        Outcome::Usage(error) => {
+            eprintln!("{}", USAGE);
+            (serde_json::json!({ "error": error }), EXIT_USAGE)
+        }
+        Outcome::Error(error) => (serde_json::json!({ "error": error }), EXIT_ERROR),
+    }
+}
+
+/// Run a headless command if `--headless` is on the command line
+///
+/// Returns the process exit code, or `None` to start the app normally.
+pub fn run_if_requested(package_info: &tauri::PackageInfo, identifier: &str) -> Option<i32> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let index = args.iter().position(|a| a == HEADLESS_FLAG)?;
+    attach_console();
+    let command = args.get(index + 1).map(String::as_str);
+    let rest = args.get(index + 2..).unwrap_or_default();
+
+    let paths = AppPaths {
+        resource_dir: tauri::utils::platform::resource_dir(package_info, &tauri::Env::default())
+            .ok(),
+        home_dir: dirs::home_dir(),
+        // Same location the app resolves for its data directory
+        app_data_dir: dirs::data_dir().map(|dir| dir.join(identifier)),
+    };
+
+    let (value, code) = report(run(&paths, command, rest));
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&value).unwrap_or_default()
+    );
+    Some(code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|a| a.to_string()).collect()
+    }
+
+    fn paths(app_data: &Path) -> AppPaths {
+        AppPaths {
+            resource_dir: None,
+            home_dir: None,
+            app_data_dir: Some(app_data.to_path_buf()),
+        }
+    }
+
+    /// Exit code and printed JSON of a command
+    fn exit(paths: &AppPaths, command: &str, list: &[&str]) -> (i32, serde_json::Value) {
+        let (value, code) = report(run(paths, Some(command), &args(list)));
+        (code, value)
+    }
+
+    fn error(value: &serde_json::Value) -> &str {
+        value["error"].as_str().unwrap()
+    }
+
+    #[test]
+    fn parses_options_and_positional_arguments() {
+        let (options, positional) =
+            parse_options(&args(&["--seed", "42", "out", "--language=rust", "--x="])).unwrap();
+        assert_eq!(
+            options,
+            [
+                ("seed".to_string(), "42".to_string()),
+                ("language".to_string(), "rust".to_string()),
+                ("x".to_string(), String::new()),
+            ]
+        );
+        assert_eq!(positional, ["out"]);
+
+        assert_eq!(
+            parse_options(&args(&["--seed"])).unwrap_err(),
+            "--seed needs a value"
+        );
+
+        let mut options = options;
+        assert_eq!(take_option(&mut options, "language").unwrap(), "rust");
+        assert_eq!(options.len(), 2);
+        assert_eq!(
+            take_option(&mut options, "language").unwrap_err(),
+            "--language is required"
+        );
+    }
+
+    #[test]
+    fn maps_outcomes_to_exit_codes() {
+        let value = serde_json::json!({"success": true});
+        assert_eq!(report(Outcome::Ok(value.clone())), (value.clone(), 0));
+        assert_eq!(report(Outcome::Failed(value.clone())), (value, 1));
+        assert_eq!(
+            report(Outcome::Usage("bad".to_string())),
+            (serde_json::json!({"error": "bad"}), 2)
+        );
+        assert_eq!(
+            report(Outcome::Error("broken".to_string())),
+            (serde_json::json!({"error": "broken"}), 3)
+        );
+    }
+
+    #[test]
+    fn rejects_bad_command_lines() {
+        let app_data = tempfile::tempdir().unwrap();
+        let paths = paths(app_data.path());
+
+        let (value, code) = report(run(&paths, None, &[]));
+        assert_eq!((code, error(&value)), (2, "Missing command"));
+        let (code, value) = exit(&paths, "deploy", &[]);
+        assert_eq!((code, error(&value)), (2, "Unknown command 'deploy'"));
+
+        for (command, list, message) in [
+            ("generate", &["--seed", "1"][..], "--output is required"),
+            ("generate", &["--output", "out"], "--seed is required"),
+            (
+                "generate",
+                &["--seed", "x", "--output", "out"],
+                "Invalid seed",
+            ),
+            ("generate", &["out"], "Unexpected argument 'out'"),
+            ("preview", &[], "--seed is required"),
+            ("preview", &["--seed", "1", "--enrich", "lots"], "enrich"),
+            ("preview", &["--seed"], "--seed needs a value"),
+            ("validate", &[], "exactly one manifest path"),
+            (
+                "validate",
+                &["a.yaml", "b.yaml"],
+                "exactly one manifest path",
+            ),
+            ("templates", &["--all"], "Unexpected argument '--all'"),
+        ] {
+            let (code, value) = exit(&paths, command, list);
+            assert_eq!(code, 2, "{} {:?}", command, list);
+            assert!(
+                error(&value).contains(message),
+                "{} {:?}: {}",
+                command,
+                list,
+                value
+            );
+        }
+    }
+
+    #[test]
+    fn validate_exits_by_result() {
+        let dir = tempfile::tempdir().unwrap();
+        let paths = paths(dir.path());
+        let manifest = |name: &str, content: &str| {
+            let path = dir.path().join(name);
+            fs::write(&path, content).unwrap();
+            path.to_string_lossy().to_string()
+        };
+
+        let valid = manifest(
+            "valid.yaml",
+            "apiVersion: upg/v1\nmetadata: {name: api, version: 1.0.0}\nprompts: []\n",
+        );
+        let (code, value) = exit(&paths, "validate", &[&valid]);
+        assert_eq!(code, 0);
+        assert_eq!(value["valid"], true);
+
+        let invalid = manifest("invalid.yaml", "metadata: {version: 1.0.0}\n");
+        let (code, value) = exit(&paths, "validate", &[&invalid]);
+        assert_eq!(code, 1);
+        assert_eq!(value["valid"], false);
+
+        // A directory cannot be read as a manifest
+        let unreadable = dir.path().join("dir.yaml");
+        fs::create_dir(&unreadable).unwrap();
+        let (code, value) = exit(&paths, "validate", &[&unreadable.to_string_lossy()]);
+        assert_eq!(code, 3);
+        assert!(error(&value).contains("Failed to read manifest"));
+    }
+
+    #[test]
+    fn templates_lists_discovered_templates() {
+        let app_data = tempfile::tempdir().unwrap();
+        let paths = paths(app_data.path());
+        let template = app_data.path().join("templates/api");
+        fs::create_dir_all(&template).unwrap();
+        fs::write(
+            template.join("upg.yaml"),
+            "metadata: {name: api, version: 1.0.0}\n",
+        )
+        .unwrap();
+
+        let (code, value) = exit(&paths, "templates", &[]);
+        assert_eq!(code, 0);
+        let names: Vec<&str> = value
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|t| t["name"].as_str().unwrap())
+            .collect();
+        assert!(names.contains(&"api"), "{:?}", names);
+    }
+}
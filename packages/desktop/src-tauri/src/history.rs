@@ -0,0 +1,115 @@
+//! Generation history
+//!
+//! Every successful generation is appended to the `history.json` store alongside its
+//! provenance. Later checks (such as `validate_generated_project`) attach their verdict
+//! to the most recent entry for the project.
+
+use crate::provenance::{ProjectProvenance, ProvenanceMode, TemplateProvenance};
+use crate::TechStackConfig;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use tauri_plugin_store::StoreExt;
+
+/// Store file holding the history
+pub const HISTORY_STORE: &str = "history.json";
+
+const HISTORY_KEY: &str = "entries";
+
+/// Oldest entries are dropped beyond this
+const MAX_HISTORY_ENTRIES: usize = 500;
+
+/// Build/test verdict for a generated project
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValidationVerdict {
+    pub passed: bool,
+    /// Detected project types (e.g. `node`, `rust`)
+    pub ecosystems: Vec<String>,
+    /// Command of the first required step that failed
+    pub failed_step: Option<String>,
+    pub validated_at: String,
+    pub duration_ms: u64,
+}
+
+/// One generation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub project_path: String,
+    pub mode: ProvenanceMode,
+    #[serde(default)]
+    pub seed: Option<u64>,
+    #[serde(default)]
+    pub stack: Option<TechStackConfig>,
+    #[serde(default)]
+    pub template: Option<TemplateProvenance>,
+    pub engine_version: String,
+    pub generated_at: String,
+    /// Latest validation verdict, if the project has been validated
+    #[serde(default)]
+    pub validation: Option<ValidationVerdict>,
+}
+
+fn load_entries(app: &tauri::AppHandle) -> Result<Vec<HistoryEntry>, String> {
+    let store = app.store(HISTORY_STORE).map_err(|e| e.to_string())?;
+    match store.get(HISTORY_KEY) {
+        Some(value) => serde_json::from_value(value)
+            .map_err(|e| format!("Failed to read generation history: {}", e)),
+        None => Ok(vec![]),
+    }
+}
+
+fn save_entries(app: &tauri::AppHandle, entries: &[HistoryEntry]) -> Result<(), String> {
+    let store = app.store(HISTORY_STORE).map_err(|e| e.to_string())?;
+    let value = serde_json::to_value(entries).map_err(|e| e.to_string())?;
+    store.set(HISTORY_KEY, value);
+    store
+        .save()
+        .map_err(|e| format!("Failed to save generation history: {}", e))
+}
+
+/// Append a generation to the history (newest first)
+pub fn record_generation(
+    app: &tauri::AppHandle,
+    project_dir: &Path,
+    provenance: &ProjectProvenance,
+) -> Result<(), String> {
+    let mut entries = load_entries(app)?;
+    entries.insert(
+        0,
+        HistoryEntry {
+            project_path: project_dir.to_string_lossy().to_string(),
+            mode: provenance.mode,
+            seed: provenance.seed,
+            stack: provenance.stack.clone(),
+            template: provenance.template.clone(),
+            engine_version: provenance.engine_version.clone(),
+            generated_at: provenance.generated_at.clone(),
+            validation: None,
+        },
+    );
+    entries.truncate(MAX_HISTORY_ENTRIES);
+    save_entries(app, &entries)
+}
+
+/// Attach a verdict to the most recent history entry for `project_dir`
+///
+/// Returns false when the project is not in the history.
+pub fn record_validation(
+    app: &tauri::AppHandle,
+    project_dir: &Path,
+    verdict: ValidationVerdict,
+) -> Result<bool, String> {
+    let mut entries = load_entries(app)?;
+    let project_path = project_dir.to_string_lossy();
+    let Some(entry) = entries.iter_mut().find(|e| e.project_path == project_path) else {
+        return Ok(false);
+    };
+    entry.validation = Some(verdict);
+    save_entries(app, &entries)?;
+    Ok(true)
+}
+
+/// Get the generation history, newest first
+#[tauri::command]
+pub async fn get_generation_history(app: tauri::AppHandle) -> Result<Vec<HistoryEntry>, String> {
+    load_entries(&app)
+}
@@ -0,0 +1,539 @@
+//! Manifest hook execution (`hooks.post_generation`, `hooks.pre_migration`)
+//!
+//! Hook scripts live in the template directory (a script path leaving it fails the
+//! hook) and run with the generated project as their working directory. Answers are passed as JSON on stdin, output is streamed as
+//! `hooks://output` events, and failures are handled according to the hook's `on_error`.
+//!
+//! Like command actions, hooks only run with the user's consent: `get_template_hooks`
+//! lists them for approval, and an unapproved hook is reported as `not_approved`.
+
+use crate::conditions::evaluate_when;
+use crate::error_codes;
+use crate::files::project_file;
+use crate::jinja::with_defaults;
+use crate::manifest::{load_manifest, resolve_manifest_path, HookConfig, ManifestFile, OnError};
+use crate::process::{run_streaming, OutputStream};
+use crate::{parse_answers, resolve_template_path};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tauri::Emitter;
+use tokio::process::Command;
+
+/// Event carrying one line of hook output
+pub const HOOK_OUTPUT_EVENT: &str = "hooks://output";
+
+/// Default time a hook may run before it is killed
+pub const DEFAULT_HOOK_TIMEOUT_SECS: u64 = 300;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HookKind {
+    PostGeneration,
+    PreMigration,
+}
+
+impl HookKind {
+    /// Manifest key of the hook, also passed to scripts as `UPG_HOOK`
+    pub fn as_str(self) -> &'static str {
+        match self {
+            HookKind::PostGeneration => "post_generation",
+            HookKind::PreMigration => "pre_migration",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HookStatus {
+    Succeeded,
+    Failed,
+    TimedOut,
+    /// `when` evaluated to false
+    Skipped,
+    /// The user did not approve the hook
+    NotApproved,
+}
+
+/// A declared hook as it would run for a given set of answers
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlannedHook {
+    pub hook: HookKind,
+    /// Script path relative to the template directory
+    pub script: String,
+    pub description: Option<String>,
+    pub when: Option<String>,
+    pub on_error: OnError,
+    /// False when `when` evaluates to false for the answers
+    pub will_run: bool,
+}
+
+/// Result of running one hook
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HookResult {
+    pub hook: HookKind,
+    pub script: String,
+    pub status: HookStatus,
+    pub on_error: OnError,
+    /// False only when the hook failed and `on_error` is `fail`
+    pub success: bool,
+    /// HOOK_FAILED or TIMEOUT when the hook did not succeed
+    pub code: Option<String>,
+    pub message: String,
+    pub exit_code: Option<i32>,
+    pub stdout: String,
+    pub stderr: String,
+    pub duration_ms: u64,
+}
+
+/// Payload of `hooks://output` events
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HookOutputEvent {
+    pub hook: HookKind,
+    pub stream: OutputStream,
+    pub line: String,
+}
+
+/// Pick an interpreter from the script extension; anything else is executed directly
+fn hook_command(script: &Path) -> Command {
+    let ext = script.extension().and_then(|e| e.to_str()).unwrap_or("");
+    let interpreter: Option<(&str, &[&str])> = match ext {
+        "py" if cfg!(windows) => Some(("python", &[])),
+        "py" => Some(("python3", &[])),
+        "js" | "mjs" | "cjs" => Some(("node", &[])),
+        "sh" => Some(("sh", &[])),
+        "ps1" => Some((
+            "powershell",
+            &["-NoProfile", "-ExecutionPolicy", "Bypass", "-File"],
+        )),
+        _ => None,
+    };
+
+    match interpreter {
+        Some((program, args)) => {
+            let mut command = Command::new(program);
+            command.args(args).arg(script);
+            command
+        }
+        None => Command::new(script),
+    }
+}
+
+/// Directory containing a template's manifest
+pub fn template_dir(template_path: &Path) -> Result<PathBuf, String> {
+    resolve_manifest_path(template_path)
+        .and_then(|m| m.parent().map(Path::to_path_buf))
+        .ok_or_else(|| format!("No manifest found at {:?}", template_path))
+}
+
+fn hook_config(manifest: &ManifestFile, kind: HookKind) -> Option<&HookConfig> {
+    let hooks = manifest.hooks.as_ref()?;
+    match kind {
+        HookKind::PostGeneration => hooks.post_generation.as_ref(),
+        HookKind::PreMigration => hooks.pre_migration.as_ref(),
+    }
+}
+
+/// Answers as a hook's `when` clause sees them, with defaults for unanswered prompts
+fn hook_context(
+    kind: HookKind,
+    manifest: &ManifestFile,
+    answers: &Map<String, Value>,
+) -> Map<String, Value> {
+    let mut context = with_defaults(manifest, answers);
+    context
+        .entry("update")
+        .or_insert(Value::Bool(kind == HookKind::PreMigration));
+    context
+}
+
+/// List the manifest's hooks with their `when` evaluated against `answers`
+pub fn plan_hooks(manifest: &ManifestFile, answers: &Map<String, Value>) -> Vec<PlannedHook> {
+    [HookKind::PostGeneration, HookKind::PreMigration]
+        .into_iter()
+        .filter_map(|kind| {
+            let hook = hook_config(manifest, kind)?;
+            let context = hook_context(kind, manifest, answers);
+            Some(PlannedHook {
+                hook: kind,
+                script: hook.script.clone(),
+                description: hook.description.clone(),
+                when: hook.when.clone(),
+                on_error: hook.on_error,
+                will_run: hook
+                    .when
+                    .as_deref()
+                    .map_or(true, |w| evaluate_when(w, &context)),
+            })
+        })
+        .collect()
+}
+
+/// Run a manifest hook if the template declares it
+///
+/// Returns `None` when the template has no such hook. `update` is exposed to the
+/// hook's `when` clause so `pre_migration` hooks can use `when: 'update'`. Unless
+/// `approved`, a hook that would run is reported as `not_approved` instead.
+#[allow(clippy::too_many_arguments)]
+pub async fn run_hook(
+    app: &tauri::AppHandle,
+    kind: HookKind,
+    template_dir: &Path,
+    manifest: &ManifestFile,
+    project_dir: &Path,
+    answers: &Map<String, Value>,
+    approved: bool,
+    timeout: Duration,
+) -> Option<HookResult> {
+    let emit = |event: HookOutputEvent| {
+        if let Err(e) = app.emit(HOOK_OUTPUT_EVENT, event) {
+            eprintln!("Failed to emit {}: {}", HOOK_OUTPUT_EVENT, e);
+        }
+    };
+    run_hook_with(
+        kind,
+        template_dir,
+        manifest,
+        project_dir,
+        answers,
+        approved,
+        timeout,
+        emit,
+    )
+    .await
+}
+
+/// `run_hook`, passing output lines to `emit`
+#[allow(clippy::too_many_arguments)]
+async fn run_hook_with(
+    kind: HookKind,
+    template_dir: &Path,
+    manifest: &ManifestFile,
+    project_dir: &Path,
+    answers: &Map<String, Value>,
+    approved: bool,
+    timeout: Duration,
+    emit: impl Fn(HookOutputEvent),
+) -> Option<HookResult> {
+    let hook = hook_config(manifest, kind)?;
+    let context = hook_context(kind, manifest, answers);
+
+    let result = |status: HookStatus, message: String| HookResult {
+        hook: kind,
+        script: hook.script.clone(),
+        status,
+        on_error: hook.on_error,
+        success: status != HookStatus::Failed && status != HookStatus::TimedOut
+            || hook.on_error != OnError::Fail,
+        code: None,
+        message,
+        exit_code: None,
+        stdout: String::new(),
+        stderr: String::new(),
+        duration_ms: 0,
+    };
+
+    if let Some(when) = hook.when.as_deref() {
+        if !evaluate_when(when, &context) {
+            return Some(result(
+                HookStatus::Skipped,
+                format!("Skipped: condition '{}' is false", when),
+            ));
+        }
+    }
+    if !approved {
+        return Some(result(
+            HookStatus::NotApproved,
+            "Not run: hook was not approved".to_string(),
+        ));
+    }
+
+    // Scripts must stay inside the template directory
+    let script = match project_file(template_dir, &hook.script) {
+        Ok(script) => script,
+        Err(_) => {
+            return Some(HookResult {
+                code: Some(error_codes::HOOK_FAILED.to_string()),
+                ..result(
+                    HookStatus::Failed,
+                    format!("Hook script is outside the template: {}", hook.script),
+                )
+            })
+        }
+    };
+    if !script.is_file() {
+        return Some(HookResult {
+            code: Some(error_codes::HOOK_FAILED.to_string()),
+            ..result(
+                HookStatus::Failed,
+                format!("Hook script not found: {:?}", script),
+            )
+        });
+    }
+
+    let mut command = hook_command(&script);
+    command
+        .current_dir(project_dir)
+        .env("UPG_HOOK", kind.as_str())
+        .env("UPG_PROJECT_DIR", project_dir)
+        .env("UPG_TEMPLATE_DIR", template_dir);
+
+    let stdin = serde_json::to_vec(answers).unwrap_or_default();
+    let outcome = run_streaming(command, Some(stdin), timeout, |stream, line| {
+        emit(HookOutputEvent {
+            hook: kind,
+            stream,
+            line: line.to_string(),
+        })
+    })
+    .await;
+
+    let outcome = match outcome {
+        Ok(o) => o,
+        Err(e) => {
+            return Some(HookResult {
+                code: Some(error_codes::HOOK_FAILED.to_string()),
+                ..result(HookStatus::Failed, e)
+            })
+        }
+    };
+
+    let (status, code, message) = if outcome.timed_out {
+        (
+            HookStatus::TimedOut,
+            Some(error_codes::TIMEOUT.to_string()),
+            format!(
+                "Hook {} timed out after {}s",
+                hook.script,
+                timeout.as_secs()
+            ),
+        )
+    } else if outcome.success {
+        (
+            HookStatus::Succeeded,
+            None,
+            format!("Hook {} succeeded", hook.script),
+        )
+    } else {
+        (
+            HookStatus::Failed,
+            Some(error_codes::HOOK_FAILED.to_string()),
+            format!(
+                "Hook {} failed with exit code {:?}",
+                hook.script, outcome.exit_code
+            ),
+        )
+    };
+
+    Some(HookResult {
+        code,
+        exit_code: outcome.exit_code,
+        stdout: outcome.stdout,
+        stderr: outcome.stderr,
+        duration_ms: outcome.duration_ms,
+        ..result(status, message)
+    })
+}
+
+/// List the hooks a template declares, evaluated for the given answers
+///
+/// The frontend shows these for approval and passes `run_hooks: true` to
+/// `generate_from_template` or `update_project` once the user agrees.
+#[tauri::command]
+pub async fn get_template_hooks(
+    app: tauri::AppHandle,
+    template_path: String,
+    data: Option<String>,
+) -> Result<Vec<PlannedHook>, String> {
+    let template_path = resolve_template_path(&template_path, &app)?;
+    let manifest = load_manifest(&template_path)?;
+    Ok(plan_hooks(&manifest, &parse_answers(data.as_deref())))
+}
+
+/// Run a template's hook against a project directory
+///
+/// Invoking this is the approval: the hook runs unless its `when` is false. Returns
+/// `None` when the template does not declare the hook.
+#[tauri::command]
+pub async fn run_template_hook(
+    app: tauri::AppHandle,
+    template_path: String,
+    project_path: String,
+    hook: HookKind,
+    answers: Option<Map<String, Value>>,
+    timeout_secs: Option<u64>,
+) -> Result<Option<HookResult>, String> {
+    let template_path = resolve_template_path(&template_path, &app)?;
+    let manifest = load_manifest(&template_path)?;
+    let dir = template_dir(&template_path)?;
+    let timeout = Duration::from_secs(timeout_secs.unwrap_or(DEFAULT_HOOK_TIMEOUT_SECS));
+
+    Ok(run_hook(
+        &app,
+        hook,
+        &dir,
+        &manifest,
+        Path::new(&project_path),
+        &answers.unwrap_or_default(),
+        true,
+        timeout,
+    )
+    .await)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::sync::Mutex;
+
+    fn manifest(hooks: &str) -> ManifestFile {
+        serde_yaml::from_str(&format!(
+            "metadata: {{name: api, version: 1.0.0}}\n\
+             prompts:\n  - {{id: docker, type: confirm, message: Docker?, default: false}}\n\
+             hooks:\n{}",
+            hooks
+        ))
+        .unwrap()
+    }
+
+    fn answers(value: Value) -> Map<String, Value> {
+        value.as_object().unwrap().clone()
+    }
+
+    async fn run(
+        template_dir: &Path,
+        manifest: &ManifestFile,
+        answers: &Map<String, Value>,
+        approved: bool,
+    ) -> HookResult {
+        let project = tempfile::tempdir().unwrap();
+        run_hook_with(
+            HookKind::PostGeneration,
+            template_dir,
+            manifest,
+            project.path(),
+            answers,
+            approved,
+            Duration::from_secs(10),
+            |_| {},
+        )
+        .await
+        .unwrap()
+    }
+
+    #[test]
+    fn plans_hooks_against_the_answers() {
+        let manifest = manifest(
+            "  post_generation: {script: setup.sh, when: docker}\n\
+             \x20 pre_migration: {script: migrate.sh, when: update, on_error: warn}\n",
+        );
+
+        let planned = plan_hooks(&manifest, &Map::new());
+        assert_eq!(planned.len(), 2);
+        assert_eq!(planned[0].hook, HookKind::PostGeneration);
+        // `docker` defaults to false
+        assert!(!planned[0].will_run);
+        // `update` is true for pre_migration hooks
+        assert!(planned[1].will_run);
+        assert_eq!(planned[1].on_error, OnError::Warn);
+
+        let planned = plan_hooks(&manifest, &answers(serde_json::json!({"docker": true})));
+        assert!(planned[0].will_run);
+
+        assert!(plan_hooks(&self::manifest("  {}\n"), &Map::new()).is_empty());
+    }
+
+    #[tokio::test]
+    async fn skips_unapproved_and_false_hooks() {
+        let template = tempfile::tempdir().unwrap();
+        let manifest = manifest("  post_generation: {script: setup.sh, when: docker}\n");
+
+        let result = run(template.path(), &manifest, &Map::new(), true).await;
+        assert_eq!(result.status, HookStatus::Skipped);
+        assert!(result.success);
+
+        let docker = answers(serde_json::json!({"docker": true}));
+        let result = run(template.path(), &manifest, &docker, false).await;
+        assert_eq!(result.status, HookStatus::NotApproved);
+        assert!(result.success);
+
+        let result = run(template.path(), &manifest, &docker, true).await;
+        assert_eq!(result.status, HookStatus::Failed);
+        assert_eq!(result.code.as_deref(), Some(error_codes::HOOK_FAILED));
+        assert!(!result.success);
+    }
+
+    #[tokio::test]
+    async fn rejects_scripts_outside_the_template() {
+        let parent = tempfile::tempdir().unwrap();
+        let template = parent.path().join("template");
+        fs::create_dir(&template).unwrap();
+        fs::write(parent.path().join("escape.sh"), "echo escaped\n").unwrap();
+
+        for script in ["../escape.sh", "/bin/sh"] {
+            let manifest = manifest(&format!("  post_generation: {{script: '{}'}}\n", script));
+            let result = run(&template, &manifest, &Map::new(), true).await;
+            assert_eq!(result.status, HookStatus::Failed);
+            assert_eq!(result.code.as_deref(), Some(error_codes::HOOK_FAILED));
+            assert!(result.stdout.is_empty());
+        }
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn reports_script_outcomes_per_on_error() {
+        let template = tempfile::tempdir().unwrap();
+        fs::write(
+            template.path().join("ok.sh"),
+            "read answers; echo \"$UPG_HOOK $answers\"\n",
+        )
+        .unwrap();
+        fs::write(template.path().join("fail.sh"), "echo broken >&2; exit 3\n").unwrap();
+        let docker = answers(serde_json::json!({"docker": true}));
+
+        let lines = Mutex::new(Vec::new());
+        let project = tempfile::tempdir().unwrap();
+        let result = run_hook_with(
+            HookKind::PostGeneration,
+            template.path(),
+            &manifest("  post_generation: {script: ok.sh}\n"),
+            project.path(),
+            &docker,
+            true,
+            Duration::from_secs(10),
+            |event| lines.lock().unwrap().push(event.line),
+        )
+        .await
+        .unwrap();
+        assert_eq!(result.status, HookStatus::Succeeded);
+        assert_eq!(result.stdout, "post_generation {\"docker\":true}\n");
+        assert_eq!(
+            *lines.lock().unwrap(),
+            ["post_generation {\"docker\":true}"]
+        );
+
+        let result = run(
+            template.path(),
+            &manifest("  post_generation: {script: fail.sh}\n"),
+            &docker,
+            true,
+        )
+        .await;
+        assert_eq!(result.status, HookStatus::Failed);
+        assert_eq!(result.exit_code, Some(3));
+        assert_eq!(result.stderr, "broken\n");
+        assert!(!result.success);
+
+        for on_error in ["warn", "ignore"] {
+            let manifest = manifest(&format!(
+                "  post_generation: {{script: fail.sh, on_error: {}}}\n",
+                on_error
+            ));
+            let result = run(template.path(), &manifest, &docker, true).await;
+            assert_eq!(result.status, HookStatus::Failed);
+            assert!(result.success);
+        }
+    }
+}
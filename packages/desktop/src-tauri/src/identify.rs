@@ -0,0 +1,341 @@
+//! Project origin detection
+//!
+//! Works out which template or seed produced an arbitrary directory. A provenance file
+//! (`.upg-answers.yaml`) is conclusive; otherwise every known template's
+//! `template.markers` are checked against the directory and scored by how many match.
+//! Markers are relative to the project root, which is the directory holding the
+//! provenance file when one is found above the given path.
+
+use crate::discover_templates;
+use crate::files::{walk_files, IGNORED_DIRS};
+use crate::provenance::{find_provenance, load_provenance, ProvenanceMode, PROVENANCE_FILE};
+use crate::templates::{find_template, DiscoveredTemplate};
+use globset::Glob;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Highest confidence a marker-only match can reach (provenance is always 1.0)
+const MARKER_CONFIDENCE_CEILING: f64 = 0.9;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OriginKind {
+    Template,
+    Seed,
+}
+
+/// A possible origin for a project
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OriginCandidate {
+    pub kind: OriginKind,
+    pub template_name: Option<String>,
+    /// Path of the matching installed template; the recorded version when it is installed
+    pub template_path: Option<String>,
+    /// Template version that generated the project, when recorded
+    pub version: Option<String>,
+    /// Default version of the matching template currently installed
+    pub installed_version: Option<String>,
+    pub seed: Option<u64>,
+    /// 0.0–1.0
+    pub confidence: f64,
+    pub matched_markers: Vec<String>,
+    pub missing_markers: Vec<String>,
+    /// Human-readable reasons for this candidate
+    pub evidence: Vec<String>,
+}
+
+/// Result of `identify_project`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectIdentification {
+    pub path: String,
+    /// Provenance file found at or above `path`
+    pub provenance_path: Option<String>,
+    /// Most likely origin (first candidate)
+    pub best: Option<OriginCandidate>,
+    /// All candidates, most confident first
+    pub candidates: Vec<OriginCandidate>,
+}
+
+/// Check a marker against the directory; glob markers are matched against its files
+fn marker_present(dir: &Path, marker: &str, files: &mut Option<Vec<String>>) -> bool {
+    let is_glob = marker.contains(['*', '?', '[', '{']);
+    if !is_glob {
+        return dir.join(marker).exists();
+    }
+
+    let Ok(glob) = Glob::new(marker) else {
+        return false;
+    };
+    let matcher = glob.compile_matcher();
+    files
+        .get_or_insert_with(|| walk_files(dir, IGNORED_DIRS))
+        .iter()
+        .any(|f| matcher.is_match(f))
+}
+
+/// Identify which template or seed produced a directory
+///
+/// Returns candidates ranked by confidence so the desktop can offer update or
+/// re-generate actions for folders it did not create in this session.
+#[tauri::command]
+pub async fn identify_project(
+    app: tauri::AppHandle,
+    path: String,
+) -> Result<ProjectIdentification, String> {
+    if !Path::new(&path).is_dir() {
+        return Err(format!("Not a directory: {}", path));
+    }
+    Ok(identify(path, &discover_templates(&app)))
+}
+
+/// Rank `templates` (and a recorded seed) as origins of the directory at `path`
+fn identify(path: String, templates: &[DiscoveredTemplate]) -> ProjectIdentification {
+    let provenance_file = find_provenance(Path::new(&path));
+    let dir = provenance_file
+        .as_deref()
+        .and_then(Path::parent)
+        .unwrap_or(Path::new(&path));
+    let provenance = match &provenance_file {
+        Some(file) => match load_provenance(file) {
+            Ok(p) => Some(p),
+            Err(e) => {
+                eprintln!("Ignoring unreadable provenance: {}", e);
+                None
+            }
+        },
+        None => None,
+    };
+
+    let mut candidates = Vec::new();
+    let mut files = None;
+
+    if let Some(p) = provenance
+        .as_ref()
+        .filter(|p| p.mode == ProvenanceMode::Procedural)
+    {
+        candidates.push(OriginCandidate {
+            kind: OriginKind::Seed,
+            template_name: None,
+            template_path: None,
+            version: Some(p.engine_version.clone()),
+            installed_version: None,
+            seed: p.seed,
+            confidence: 1.0,
+            matched_markers: vec![PROVENANCE_FILE.to_string()],
+            missing_markers: vec![],
+            evidence: vec![format!("{} records seed {:?}", PROVENANCE_FILE, p.seed)],
+        });
+    }
+
+    let recorded_template = provenance.as_ref().and_then(|p| p.template.clone());
+
+    // One candidate per template name, for its default version
+    let defaults = templates
+        .iter()
+        .enumerate()
+        .filter(|(i, t)| find_template(templates, &t.manifest.metadata.name, None) == Some(*i));
+    for (_, template) in defaults {
+        let meta = &template.manifest.metadata;
+        let markers = template
+            .manifest
+            .template
+            .as_ref()
+            .map(|t| t.markers.clone())
+            .unwrap_or_default();
+
+        let (matched, missing): (Vec<String>, Vec<String>) = markers
+            .into_iter()
+            .partition(|m| marker_present(dir, m, &mut files));
+
+        let recorded = recorded_template.as_ref().filter(|t| t.name == meta.name);
+        let mut evidence = Vec::new();
+
+        // Point at the version that generated the project while it is installed
+        let mut path = &template.path;
+        let confidence = if let Some(t) = recorded {
+            evidence.push(format!(
+                "{} records template {} {}",
+                PROVENANCE_FILE, t.name, t.version
+            ));
+            if let Some(index) = find_template(templates, &t.name, Some(&t.version)) {
+                path = &templates[index].path;
+            }
+            1.0
+        } else if !matched.is_empty() {
+            // The provenance marker alone is shared by every UPG project
+            let specific = matched.iter().filter(|m| *m != PROVENANCE_FILE).count();
+            if specific == 0 {
+                continue;
+            }
+            evidence.push(format!(
+                "{} of {} markers present",
+                matched.len(),
+                matched.len() + missing.len()
+            ));
+            MARKER_CONFIDENCE_CEILING * matched.len() as f64
+                / (matched.len() + missing.len()) as f64
+        } else {
+            continue;
+        };
+
+        candidates.push(OriginCandidate {
+            kind: OriginKind::Template,
+            template_name: Some(meta.name.clone()),
+            template_path: Some(path.to_string_lossy().to_string()),
+            version: recorded.map(|t| t.version.clone()),
+            installed_version: Some(meta.version.clone()),
+            seed: None,
+            confidence,
+            matched_markers: matched,
+            missing_markers: missing,
+            evidence,
+        });
+    }
+
+    // Recorded template that is not installed any more
+    if let Some(t) = recorded_template.as_ref() {
+        let installed = candidates
+            .iter()
+            .any(|c| c.template_name.as_deref() == Some(t.name.as_str()));
+        if !installed {
+            candidates.push(OriginCandidate {
+                kind: OriginKind::Template,
+                template_name: Some(t.name.clone()),
+                template_path: None,
+                version: Some(t.version.clone()),
+                installed_version: None,
+                seed: None,
+                confidence: 1.0,
+                matched_markers: vec![PROVENANCE_FILE.to_string()],
+                missing_markers: vec![],
+                evidence: vec![format!(
+                    "{} records template {} {} (not installed; recorded path {})",
+                    PROVENANCE_FILE, t.name, t.version, t.path
+                )],
+            });
+        }
+    }
+
+    candidates.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+
+    ProjectIdentification {
+        path: path.clone(),
+        provenance_path: provenance_file
+            .as_ref()
+            .map(|f| f.to_string_lossy().to_string()),
+        best: candidates.first().cloned(),
+        candidates,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::templates::{TemplateRoot, TemplateRootKind};
+    use std::fs;
+
+    fn template(name: &str, version: &str, markers: &[&str]) -> DiscoveredTemplate {
+        let manifest = serde_yaml::from_str(&format!(
+            "metadata: {{name: {}, version: {}}}\ntemplate: {{markers: {:?}}}\n",
+            name, version, markers
+        ))
+        .unwrap();
+        DiscoveredTemplate {
+            path: format!("/templates/{}/{}", name, version).into(),
+            manifest,
+            root: TemplateRoot {
+                kind: TemplateRootKind::User,
+                path: "/templates".to_string(),
+                priority: 0,
+                max_depth: 2,
+            },
+            shadows: vec![],
+            catalog: None,
+        }
+    }
+
+    fn project(files: &[&str]) -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        for file in files {
+            let path = dir.path().join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, "").unwrap();
+        }
+        dir
+    }
+
+    fn names(identification: &ProjectIdentification) -> Vec<(&str, f64)> {
+        identification
+            .candidates
+            .iter()
+            .map(|c| (c.template_name.as_deref().unwrap_or(""), c.confidence))
+            .collect()
+    }
+
+    #[test]
+    fn scores_templates_by_matched_markers() {
+        let dir = project(&["Cargo.toml", "src/main.rs"]);
+        let templates = [
+            template("cli", "1.0.0", &["Cargo.toml", "src/main.rs"]),
+            template("lib", "1.0.0", &["Cargo.toml", "src/lib.rs", "benches"]),
+            template("web", "1.0.0", &["package.json"]),
+            template("bare", "1.0.0", &[]),
+        ];
+
+        let found = identify(dir.path().to_string_lossy().to_string(), &templates);
+        assert_eq!(names(&found), [("cli", 0.9), ("lib", 0.3)]);
+        assert_eq!(found.best.unwrap().template_name.as_deref(), Some("cli"));
+        assert_eq!(
+            found.candidates[1].missing_markers,
+            ["src/lib.rs", "benches"]
+        );
+        assert!(found.provenance_path.is_none());
+    }
+
+    #[test]
+    fn matches_glob_markers_against_the_files() {
+        let dir = project(&["app/views/home.erb", "node_modules/pkg/index.erb"]);
+        let templates = [
+            template("rails", "1.0.0", &["**/*.erb", "Gemfile"]),
+            template("ignored", "1.0.0", &["node_modules/**/*.erb"]),
+            template("invalid", "1.0.0", &["src/[.rs"]),
+        ];
+
+        let found = identify(dir.path().to_string_lossy().to_string(), &templates);
+        assert_eq!(names(&found), [("rails", 0.45)]);
+        assert_eq!(found.candidates[0].matched_markers, ["**/*.erb"]);
+    }
+
+    #[test]
+    fn provenance_is_conclusive_and_sets_the_project_root() {
+        let dir = project(&["Cargo.toml", "src/main.rs"]);
+        fs::write(
+            dir.path().join(PROVENANCE_FILE),
+            "_version: '1'\n\
+             _template: {name: lib, version: 1.0.0, source: /templates/lib}\n\
+             _generated: {timestamp: '2026-01-01T00:00:00Z', upgVersion: 0.9.0}\n",
+        )
+        .unwrap();
+        let templates = [
+            template("cli", "1.0.0", &["Cargo.toml", "src/main.rs"]),
+            template("lib", "2.0.0", &["src/lib.rs"]),
+            template("lib", "1.0.0", &["src/lib.rs"]),
+        ];
+
+        // Markers are checked from the project root, not the subdirectory
+        let sub = dir.path().join("src");
+        let found = identify(sub.to_string_lossy().to_string(), &templates);
+        assert_eq!(names(&found), [("lib", 1.0), ("cli", 0.9)]);
+
+        let best = found.best.unwrap();
+        assert_eq!(best.version.as_deref(), Some("1.0.0"));
+        assert_eq!(best.installed_version.as_deref(), Some("2.0.0"));
+        assert_eq!(best.template_path.as_deref(), Some("/templates/lib/1.0.0"));
+        assert!(found.provenance_path.is_some());
+
+        // A recorded template that is not installed is still the best match
+        let found = identify(sub.to_string_lossy().to_string(), &templates[..1]);
+        assert_eq!(names(&found), [("lib", 1.0), ("cli", 0.9)]);
+        assert!(found.best.unwrap().template_path.is_none());
+    }
+}
@@ -0,0 +1,228 @@
+//! Single-instance argument forwarding
+//!
+//! Only one instance of the app runs at a time. A second launch hands its command line
+//! to the running instance and exits; recognised arguments become the same requests
+//! deep links produce and are forwarded to the window, which is focused either way:
+//!
+//! - `--seed 42 [--archetype cli] [--language rust] [--enrich full] ...`
+//! - a path to a template directory or its `upg.yaml`/`upg.yml`
+//!
+//! The first instance's own arguments are handled the same way at startup. `upg://`
+//! arguments are left to the deep-link plugin. Other flags are ignored, and so is the
+//! argument after one unless it is written `--name=value`, since it may be its value.
+
+use crate::deeplink::{
+    dispatch, focus_main_window, seed_request, DeepLinkPayload, DEEP_LINK_SCHEME,
+};
+use crate::manifest::{resolve_manifest_path, MANIFEST_FILENAMES};
+use std::path::Path;
+
+/// Options forwarded as a seed request (same names as deep-link parameters)
+const SEED_OPTIONS: &[&str] = &[
+    "seed",
+    "archetype",
+    "language",
+    "framework",
+    "database",
+    "packaging",
+    "cicd",
+    "enrich",
+];
+
+/// Parse a launch command line (`args[0]` is the executable); relative paths resolve
+/// against `cwd`
+///
+/// Returns `None` when the arguments hold no request.
+pub fn parse_args(args: &[String], cwd: &Path) -> Option<DeepLinkPayload> {
+    let args: Vec<&String> = args.iter().skip(1).collect();
+    let source = args
+        .iter()
+        .map(|a| a.as_str())
+        .collect::<Vec<_>>()
+        .join(" ");
+    let invalid = |error: String| {
+        Some(DeepLinkPayload::Invalid {
+            source: source.clone(),
+            error,
+        })
+    };
+
+    let mut options: Vec<(String, String)> = Vec::new();
+    let mut positional = Vec::new();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if let Some(flag) = arg.strip_prefix("--") {
+            let (name, value) = match flag.split_once('=') {
+                Some((name, value)) => (name, Some(value.to_string())),
+                None => (flag, None),
+            };
+            // Flags meant for something else (e.g. added by the OS) are ignored, with
+            // what may be their value
+            if !SEED_OPTIONS.contains(&name) {
+                let next_is_value = iter.as_slice().first().is_some_and(|v| !v.starts_with('-'));
+                if value.is_none() && next_is_value {
+                    iter.next();
+                }
+                continue;
+            }
+            let Some(value) = value.or_else(|| iter.next().map(|v| v.to_string())) else {
+                return invalid(format!("--{} needs a value", name));
+            };
+            options.push((name.to_string(), value));
+        } else if !arg.starts_with('-') && !arg.starts_with(&format!("{}:", DEEP_LINK_SCHEME)) {
+            positional.push(*arg);
+        }
+    }
+
+    let seeds: Vec<usize> = options
+        .iter()
+        .enumerate()
+        .filter(|(_, (name, _))| name == "seed")
+        .map(|(i, _)| i)
+        .collect();
+    match seeds.as_slice() {
+        [] if !options.is_empty() => {
+            return invalid("--seed is required with stack options".to_string())
+        }
+        [] => {}
+        [i] => {
+            let (_, seed) = options.remove(*i);
+            return Some(match seed_request(&seed, &options) {
+                Ok(request) => DeepLinkPayload::Seed { source, request },
+                Err(error) => DeepLinkPayload::Invalid { source, error },
+            });
+        }
+        _ => return invalid("--seed given more than once".to_string()),
+    }
+
+    let arg = positional.first()?;
+    let path = cwd.join(arg);
+    let is_manifest = path.is_file()
+        && path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .is_some_and(|n| MANIFEST_FILENAMES.contains(&n));
+    let template_dir = if is_manifest {
+        path.parent().map(Path::to_path_buf)
+    } else if path.is_dir() && resolve_manifest_path(&path).is_some() {
+        Some(path)
+    } else {
+        None
+    };
+
+    match template_dir {
+        Some(dir) => Some(DeepLinkPayload::Template {
+            source,
+            template_path: dir.to_string_lossy().to_string(),
+        }),
+        None => invalid(format!("Not a UPG template: no upg.yaml found at {}", arg)),
+    }
+}
+
+/// Forward a launch command line to the window and focus it
+pub fn handle_args(app: &tauri::AppHandle, args: &[String], cwd: &Path) {
+    if let Some(payload) = parse_args(args, cwd) {
+        dispatch(app, payload);
+    }
+    focus_main_window(app);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        std::iter::once("upg-desktop")
+            .chain(list.iter().copied())
+            .map(String::from)
+            .collect()
+    }
+
+    fn error(payload: Option<DeepLinkPayload>) -> String {
+        match payload {
+            Some(DeepLinkPayload::Invalid { error, .. }) => error,
+            other => panic!("expected an invalid payload, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn no_request_without_arguments() {
+        let cwd = Path::new("/");
+        assert!(parse_args(&args(&[]), cwd).is_none());
+        assert!(parse_args(&args(&["--verbose", "-x", "upg://seed/1"]), cwd).is_none());
+    }
+
+    #[test]
+    fn parses_seed_options() {
+        let payload = parse_args(
+            &args(&["--seed", "42", "--language=rust", "--enrich", "minimal"]),
+            Path::new("/"),
+        );
+        let Some(DeepLinkPayload::Seed { source, request }) = payload else {
+            panic!("expected a seed payload, got {:?}", payload);
+        };
+
+        assert_eq!(source, "--seed 42 --language=rust --enrich minimal");
+        assert_eq!(request.seed, Some(42));
+        assert_eq!(request.stack.unwrap().language.as_deref(), Some("rust"));
+        assert_eq!(request.enrichment.unwrap().depth, "minimal");
+    }
+
+    #[test]
+    fn rejects_invalid_seed_options() {
+        let cwd = Path::new("/");
+        assert!(
+            error(parse_args(&args(&["--language", "rust"]), cwd)).contains("--seed is required")
+        );
+        assert!(error(parse_args(&args(&["--seed"]), cwd)).contains("needs a value"));
+        assert!(error(parse_args(&args(&["--seed=1", "--seed=2"]), cwd)).contains("more than once"));
+        assert!(error(parse_args(&args(&["--seed", "x"]), cwd)).contains("Invalid seed"));
+    }
+
+    #[test]
+    fn parses_template_paths() {
+        let dir = tempfile::tempdir().unwrap();
+        let template = dir.path().join("api");
+        fs::create_dir(&template).unwrap();
+        fs::write(template.join("upg.yaml"), "metadata: {name: api}\n").unwrap();
+        let expected = template.to_string_lossy().to_string();
+
+        for arg in ["api", "api/upg.yaml"] {
+            match parse_args(&args(&[arg]), dir.path()) {
+                Some(DeepLinkPayload::Template { template_path, .. }) => {
+                    assert_eq!(template_path, expected)
+                }
+                other => panic!("expected a template payload for {}, got {:?}", arg, other),
+            }
+        }
+
+        assert!(error(parse_args(&args(&["missing"]), dir.path())).contains("Not a UPG template"));
+        assert!(error(parse_args(&args(&["."]), dir.path())).contains("Not a UPG template"));
+    }
+
+    #[test]
+    fn unknown_flags_take_their_value_with_them() {
+        let dir = tempfile::tempdir().unwrap();
+        let template = dir.path().join("api");
+        fs::create_dir(&template).unwrap();
+        fs::write(template.join("upg.yaml"), "metadata: {name: api}\n").unwrap();
+
+        assert!(parse_args(&args(&["--foo", "bar"]), dir.path()).is_none());
+        assert!(parse_args(&args(&["--foo", "api"]), dir.path()).is_none());
+        for list in [
+            &["--foo=bar", "api"][..],
+            &["--foo", "-v", "api"],
+            &["api", "--foo", "bar"],
+        ] {
+            assert!(
+                matches!(
+                    parse_args(&args(list), dir.path()),
+                    Some(DeepLinkPayload::Template { .. })
+                ),
+                "{:?}",
+                list
+            );
+        }
+    }
+}
@@ -0,0 +1,882 @@
+//! Jinja2 dynamic defaults
+//!
+//! Evaluates prompt defaults such as `"{{ project_name | lower | replace('-', '_') }}"`
+//! or `"{{ env.USER }}"` with the filters of packages/core/src/transpiler/jinja2-filters.ts
+//! and the same JavaScript conversions (`String()`, `Number()`, `parseInt()`...), so the
+//! form can show computed values while the user types.
+//!
+//! An expression is a variable (`name`, `a.b.c` or `env.NAME`) followed by filters with
+//! optional arguments (quoted strings, `true`/`false`, numbers or variable names).
+//! A default that is exactly one `{{ }}` keeps the expression's type; text around or
+//! between expressions is rendered as a string. Unknown or malformed filters are skipped
+//! with a warning, as in TypeScript.
+
+use crate::manifest::{load_manifest, ManifestFile};
+use regex::{Captures, Regex};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::sync::OnceLock;
+
+// OnceLock rather than LazyLock: the crate's MSRV is 1.70
+fn regex(cell: &'static OnceLock<Regex>, pattern: &str) -> &'static Regex {
+    cell.get_or_init(|| Regex::new(pattern).expect("valid jinja pattern"))
+}
+
+/// `String(value)` in JavaScript; `None` is `undefined`
+fn js_string(value: Option<&Value>) -> String {
+    match value {
+        None => "undefined".to_string(),
+        Some(Value::Null) => "null".to_string(),
+        Some(Value::Bool(b)) => b.to_string(),
+        Some(Value::Number(n)) => n
+            .as_f64()
+            .map(format_number)
+            .unwrap_or_else(|| n.to_string()),
+        Some(Value::String(s)) => s.clone(),
+        Some(Value::Array(items)) => items
+            .iter()
+            .map(|item| match item {
+                Value::Null => String::new(),
+                other => js_string(Some(other)),
+            })
+            .collect::<Vec<_>>()
+            .join(","),
+        Some(Value::Object(_)) => "[object Object]".to_string(),
+    }
+}
+
+fn format_number(f: f64) -> String {
+    if f.is_nan() {
+        "NaN".to_string()
+    } else if f.is_infinite() {
+        if f > 0.0 { "Infinity" } else { "-Infinity" }.to_string()
+    } else if f.fract() == 0.0 && f.abs() < 1e21 {
+        format!("{}", f as i128)
+    } else {
+        f.to_string()
+    }
+}
+
+/// A JavaScript number as JSON; integral values stay integers, NaN becomes null
+fn number(f: f64) -> Value {
+    if f.fract() == 0.0 && f.abs() < 9_007_199_254_740_992.0 {
+        Value::from(f as i64)
+    } else {
+        serde_json::Number::from_f64(f).map_or(Value::Null, Value::Number)
+    }
+}
+
+/// `Number(value)` in JavaScript
+fn js_number(value: Option<&Value>) -> f64 {
+    match value {
+        None => f64::NAN,
+        Some(Value::Null) => 0.0,
+        Some(Value::Bool(b)) => f64::from(u8::from(*b)),
+        Some(Value::Number(n)) => n.as_f64().unwrap_or(f64::NAN),
+        Some(Value::String(s)) => string_to_number(s),
+        Some(Value::Array(items)) => match items.as_slice() {
+            [] => 0.0,
+            [item] => string_to_number(&js_string(Some(item))),
+            _ => f64::NAN,
+        },
+        Some(Value::Object(_)) => f64::NAN,
+    }
+}
+
+fn string_to_number(s: &str) -> f64 {
+    let s = s.trim();
+    if s.is_empty() {
+        return 0.0;
+    }
+    let (sign, unsigned) = match s.strip_prefix('-') {
+        Some(rest) => (-1.0, rest),
+        None => (1.0, s.strip_prefix('+').unwrap_or(s)),
+    };
+    if unsigned == "Infinity" {
+        return sign * f64::INFINITY;
+    }
+    for (prefix, radix) in [
+        ("0x", 16),
+        ("0X", 16),
+        ("0o", 8),
+        ("0O", 8),
+        ("0b", 2),
+        ("0B", 2),
+    ] {
+        if let Some(digits) = s.strip_prefix(prefix) {
+            return u64::from_str_radix(digits, radix).map_or(f64::NAN, |n| n as f64);
+        }
+    }
+    // Rust also accepts "inf" and "nan", which JavaScript does not
+    if unsigned
+        .chars()
+        .any(|c| c.is_ascii_alphabetic() && c != 'e' && c != 'E')
+    {
+        return f64::NAN;
+    }
+    s.parse().unwrap_or(f64::NAN)
+}
+
+/// `parseInt(String(value), 10)`
+fn parse_int(s: &str) -> Option<f64> {
+    let s = s.trim_start();
+    let (negative, rest) = match s.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, s.strip_prefix('+').unwrap_or(s)),
+    };
+    let digits: String = rest.chars().take_while(|c| c.is_ascii_digit()).collect();
+    let value: f64 = digits.parse().ok()?;
+    Some(if negative { -value } else { value })
+}
+
+/// `parseFloat(String(value))`
+fn parse_float(s: &str) -> Option<f64> {
+    static PREFIX: OnceLock<Regex> = OnceLock::new();
+    let prefix = regex(
+        &PREFIX,
+        r"^[+-]?(Infinity|\d+\.?\d*(?:[eE][+-]?\d+)?|\.\d+(?:[eE][+-]?\d+)?)",
+    );
+    let s = s.trim_start();
+    let matched = prefix.find(s)?.as_str();
+    Some(match matched.trim_start_matches(['+', '-']) {
+        "Infinity" if matched.starts_with('-') => f64::NEG_INFINITY,
+        "Infinity" => f64::INFINITY,
+        _ => matched.parse().ok()?,
+    })
+}
+
+/// JavaScript truthiness
+fn js_truthy(value: Option<&Value>) -> bool {
+    match value {
+        None | Some(Value::Null) => false,
+        Some(Value::Bool(b)) => *b,
+        Some(Value::Number(n)) => n.as_f64().is_some_and(|f| f != 0.0),
+        Some(Value::String(s)) => !s.is_empty(),
+        Some(Value::Array(_)) | Some(Value::Object(_)) => true,
+    }
+}
+
+fn upper_first(s: &str) -> String {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+fn lower_first(s: &str) -> String {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(first) => first.to_lowercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+fn capitalize(s: &str) -> String {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(first) => first
+            .to_uppercase()
+            .chain(chars.as_str().to_lowercase().chars())
+            .collect(),
+        None => String::new(),
+    }
+}
+
+/// Collapse runs of characters outside `[a-z0-9]` into `sep` and drop one leading and
+/// one trailing separator
+fn separate(s: &str, sep: char) -> String {
+    let mut out = String::new();
+    let mut in_run = false;
+    for c in s.chars() {
+        if c.is_ascii_lowercase() || c.is_ascii_digit() {
+            out.push(c);
+            in_run = false;
+        } else if !in_run {
+            out.push(sep);
+            in_run = true;
+        }
+    }
+    let out = out.strip_prefix(sep).unwrap_or(&out);
+    out.strip_suffix(sep).unwrap_or(out).to_string()
+}
+
+/// `[-_\s]+(.)?` replaced by the upper-cased following character
+fn join_words(s: &str) -> String {
+    static SEPARATORS: OnceLock<Regex> = OnceLock::new();
+    let separators = regex(&SEPARATORS, r"[-_\s]+(.)?");
+    separators
+        .replace_all(s, |caps: &Captures| {
+            caps.get(1)
+                .map(|c| c.as_str().to_uppercase())
+                .unwrap_or_default()
+        })
+        .to_string()
+}
+
+/// `str.slice(0, end)` with JavaScript's handling of negative and NaN ends
+fn slice_to(s: &str, end: f64) -> String {
+    let len = s.chars().count() as f64;
+    let end = if end.is_nan() {
+        0.0
+    } else if end < 0.0 {
+        (len + end.trunc()).max(0.0)
+    } else {
+        end.trunc().min(len)
+    };
+    s.chars().take(end as usize).collect()
+}
+
+fn wordwrap(s: &str, width: f64) -> String {
+    let mut lines = Vec::new();
+    let mut current = String::new();
+    for word in s.split(' ') {
+        let candidate = format!("{} {}", current, word);
+        if candidate.trim().encode_utf16().count() as f64 > width {
+            if !current.is_empty() {
+                lines.push(current.trim().to_string());
+            }
+            current = word.to_string();
+        } else if current.is_empty() {
+            current = word.to_string();
+        } else {
+            current = candidate;
+        }
+    }
+    if !current.is_empty() {
+        lines.push(current.trim().to_string());
+    }
+    lines.join("\n")
+}
+
+/// Apply a filter; `None` when it is unknown
+fn apply_filter(name: &str, value: Option<Value>, args: &[Option<Value>]) -> Option<Option<Value>> {
+    let arg = |i: usize| args.get(i).cloned().flatten();
+    let text = || js_string(value.as_ref());
+    let string = |s: String| Some(Value::String(s));
+
+    let result = match name {
+        "upper" => string(text().to_uppercase()),
+        "lower" => string(text().to_lowercase()),
+        "capitalize" => string(capitalize(&text())),
+        "title" => string(
+            text()
+                .split(' ')
+                .map(capitalize)
+                .collect::<Vec<_>>()
+                .join(" "),
+        ),
+        "replace" => {
+            let old = js_string(arg(0).as_ref());
+            let new = js_string(arg(1).as_ref());
+            string(text().replace(&old, &new))
+        }
+        "slug" => string(separate(&text().to_lowercase(), '-')),
+        "snake" => {
+            static BOUNDARIES: OnceLock<Regex> = OnceLock::new();
+            let boundaries = regex(&BOUNDARIES, "([a-z])([A-Z])");
+            let spaced = boundaries.replace_all(&text(), "${1}_${2}").to_lowercase();
+            string(separate(&spaced, '_'))
+        }
+        "camel" => string(lower_first(&join_words(&text()))),
+        "pascal" => string(upper_first(&join_words(&text()))),
+        "trim" => string(text().trim().to_string()),
+        "length" => Some(Value::from(match &value {
+            Some(Value::Array(items)) => items.len(),
+            _ => text().encode_utf16().count(),
+        })),
+        "default" => match &value {
+            None | Some(Value::Null) => arg(0),
+            Some(Value::String(s)) if s.is_empty() => arg(0),
+            _ => value,
+        },
+        "first" => match &value {
+            Some(Value::Array(items)) => items.first().cloned(),
+            _ => string(text().chars().take(1).collect()),
+        },
+        "last" => match &value {
+            Some(Value::Array(items)) => items.last().cloned(),
+            _ => string(text().chars().last().map(String::from).unwrap_or_default()),
+        },
+        "join" => {
+            let separator = arg(0).map_or(", ".to_string(), |s| js_string(Some(&s)));
+            match &value {
+                Some(Value::Array(items)) => string(
+                    items
+                        .iter()
+                        .map(|item| match item {
+                            Value::Null => String::new(),
+                            other => js_string(Some(other)),
+                        })
+                        .collect::<Vec<_>>()
+                        .join(&separator),
+                ),
+                _ => string(text()),
+            }
+        }
+        "split" => {
+            let separator = arg(0).map_or(",".to_string(), |s| js_string(Some(&s)));
+            let text = text();
+            let parts: Vec<Value> = if separator.is_empty() {
+                text.chars().map(|c| Value::String(c.to_string())).collect()
+            } else {
+                text.split(separator.as_str())
+                    .map(|p| Value::String(p.to_string()))
+                    .collect()
+            };
+            Some(Value::Array(parts))
+        }
+        "truncate" => {
+            let text = text();
+            let length = arg(0).map_or(50.0, |l| js_number(Some(&l)));
+            let end = arg(1).map_or("...".to_string(), |e| js_string(Some(&e)));
+            if text.chars().count() as f64 <= length {
+                string(text)
+            } else {
+                string(slice_to(&text, length - end.chars().count() as f64) + &end)
+            }
+        }
+        "wordwrap" => string(wordwrap(
+            &text(),
+            arg(0).map_or(79.0, |w| js_number(Some(&w))),
+        )),
+        "int" => Some(match parse_int(&text()) {
+            Some(n) => number(n),
+            None => number(arg(0).map_or(0.0, |d| js_number(Some(&d)))),
+        }),
+        "float" => Some(match parse_float(&text()) {
+            Some(n) => number(n),
+            None => number(arg(0).map_or(0.0, |d| js_number(Some(&d)))),
+        }),
+        "string" => string(text()),
+        "bool" => Some(Value::Bool(match &value {
+            Some(Value::Bool(b)) => *b,
+            Some(Value::String(s)) => {
+                ["true", "yes", "1", "on"].contains(&s.to_lowercase().as_str())
+            }
+            other => js_truthy(other.as_ref()),
+        })),
+        "abs" => Some(number(js_number(value.as_ref()).abs())),
+        "round" => {
+            let precision = arg(0).map_or(0.0, |p| js_number(Some(&p)));
+            let factor = 10f64.powf(precision);
+            // Math.round rounds halves up
+            Some(number(
+                (js_number(value.as_ref()) * factor + 0.5).floor() / factor,
+            ))
+        }
+        _ => return None,
+    };
+    Some(result)
+}
+
+/// Split on `sep` outside quoted strings
+fn split_unquoted(s: &str, sep: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut quote = None;
+    let mut start = 0;
+    for (i, c) in s.char_indices() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None if c == '"' || c == '\'' => quote = Some(c),
+            None if c == sep => {
+                parts.push(&s[start..i]);
+                start = i + c.len_utf8();
+            }
+            None => {}
+        }
+    }
+    parts.push(&s[start..]);
+    parts
+}
+
+/// Look up `name`, `a.b.c` or `env.NAME`
+fn lookup(path: &str, context: &Map<String, Value>) -> Option<Value> {
+    if let Some(var) = path.strip_prefix("env.") {
+        return Some(Value::String(std::env::var(var).unwrap_or_default()));
+    }
+
+    let mut parts = path.split('.');
+    let mut value = context.get(parts.next()?)?;
+    for part in parts {
+        value = value.as_object()?.get(part)?;
+    }
+    Some(value.clone())
+}
+
+fn parse_arg(raw: &str, context: &Map<String, Value>) -> Option<Value> {
+    let raw = raw.trim();
+    if raw.starts_with('"') || raw.starts_with('\'') {
+        let inner = raw.get(1..raw.len().saturating_sub(1)).unwrap_or_default();
+        return Some(Value::String(inner.to_string()));
+    }
+    match raw {
+        "true" => return Some(Value::Bool(true)),
+        "false" => return Some(Value::Bool(false)),
+        _ => {}
+    }
+    let n = string_to_number(raw);
+    if !n.is_nan() {
+        return Some(number(n));
+    }
+    context.get(raw).cloned()
+}
+
+/// Evaluate the inside of `{{ }}`; `None` is `undefined`
+pub fn evaluate_expression(
+    expression: &str,
+    context: &Map<String, Value>,
+    warnings: &mut Vec<String>,
+) -> Option<Value> {
+    static FILTER_SYNTAX: OnceLock<Regex> = OnceLock::new();
+    let filter_syntax = regex(&FILTER_SYNTAX, r"^(\w+)(?:\((.+)\))?$");
+    let mut parts = split_unquoted(expression, '|').into_iter().map(str::trim);
+    let mut value = lookup(parts.next().unwrap_or_default(), context);
+
+    for filter in parts {
+        let Some(caps) = filter_syntax.captures(filter) else {
+            warnings.push(format!("Malformed filter '{}'", filter));
+            continue;
+        };
+        let name = &caps[1];
+        let args: Vec<Option<Value>> = caps
+            .get(2)
+            .map(|args| {
+                split_unquoted(args.as_str(), ',')
+                    .into_iter()
+                    .map(|arg| parse_arg(arg, context))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        match apply_filter(name, value.clone(), &args) {
+            Some(result) => value = result,
+            None => warnings.push(format!("Unknown filter '{}'", name)),
+        }
+    }
+    value
+}
+
+/// Render a default: a lone `{{ }}` keeps its type, anything else becomes a string
+pub fn render_default(
+    default: &Value,
+    context: &Map<String, Value>,
+    warnings: &mut Vec<String>,
+) -> Option<Value> {
+    let Value::String(template) = default else {
+        return Some(default.clone());
+    };
+    static EXPRESSIONS: OnceLock<Regex> = OnceLock::new();
+    let expressions = regex(&EXPRESSIONS, r"\{\{\s*(.+?)\s*\}\}");
+
+    let trimmed = template.trim();
+    if let Some(caps) = expressions.captures(trimmed) {
+        if caps[0].len() == trimmed.len() {
+            return evaluate_expression(&caps[1], context, warnings);
+        }
+    }
+
+    let rendered = expressions.replace_all(template, |caps: &Captures| {
+        // Jinja renders undefined as an empty string
+        evaluate_expression(&caps[1], context, warnings)
+            .map(|v| js_string(Some(&v)))
+            .unwrap_or_default()
+    });
+    Some(Value::String(rendered.to_string()))
+}
+
+/// A warning raised while rendering a prompt's default
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DefaultWarning {
+    pub prompt_id: String,
+    pub message: String,
+}
+
+/// Prompt defaults rendered against the current answers
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResolvedDefaults {
+    /// Default of every prompt that has one, keyed by prompt id (null when undefined)
+    pub defaults: BTreeMap<String, Value>,
+    pub warnings: Vec<DefaultWarning>,
+}
+
+/// Render each prompt's default against `answers`, returning the defaults and the
+/// answers completed with the defaults of unanswered prompts
+///
+/// Prompts are processed in manifest order; an unanswered prompt contributes its own
+/// resolved default to the prompts after it.
+fn resolve(
+    manifest: &ManifestFile,
+    answers: &Map<String, Value>,
+) -> (ResolvedDefaults, Map<String, Value>) {
+    let mut context = answers.clone();
+    let mut defaults = BTreeMap::new();
+    let mut warnings = Vec::new();
+    for prompt in &manifest.prompts {
+        let Some(default) = &prompt.default else {
+            continue;
+        };
+        let mut messages = Vec::new();
+        let value = render_default(default, &context, &mut messages).unwrap_or(Value::Null);
+        warnings.extend(messages.into_iter().map(|message| DefaultWarning {
+            prompt_id: prompt.id.clone(),
+            message,
+        }));
+
+        if !context.contains_key(&prompt.id) {
+            context.insert(prompt.id.clone(), value.clone());
+        }
+        defaults.insert(prompt.id.clone(), value);
+    }
+
+    (ResolvedDefaults { defaults, warnings }, context)
+}
+
+/// The answers completed with the resolved defaults of unanswered prompts, as the CLI
+/// sees them when it evaluates `when` conditions
+pub fn with_defaults(manifest: &ManifestFile, answers: &Map<String, Value>) -> Map<String, Value> {
+    resolve(manifest, answers).1
+}
+
+/// Render each prompt's default against the current answers
+///
+/// Prompts are processed in manifest order; an unanswered prompt contributes its own
+/// resolved default to the prompts after it.
+#[tauri::command]
+pub async fn resolve_prompt_defaults(
+    app: tauri::AppHandle,
+    template_path: String,
+    answers: Map<String, Value>,
+) -> Result<ResolvedDefaults, String> {
+    let template_path = crate::resolve_template_path(&template_path, &app)?;
+    let manifest = load_manifest(&template_path)?;
+    Ok(resolve(&manifest, &answers).0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// Apply a known filter to a defined value
+    fn filter(name: &str, value: Value, args: &[Value]) -> Option<Value> {
+        let args: Vec<Option<Value>> = args.iter().cloned().map(Some).collect();
+        apply_filter(name, Some(value), &args).expect("known filter")
+    }
+
+    fn text(name: &str, value: Value, args: &[Value]) -> String {
+        match filter(name, value, args) {
+            Some(Value::String(s)) => s,
+            other => panic!("expected a string from {}, got {:?}", name, other),
+        }
+    }
+
+    fn eval(expression: &str, context: Value) -> (Option<Value>, Vec<String>) {
+        let Value::Object(context) = context else {
+            unreachable!()
+        };
+        let mut warnings = Vec::new();
+        let value = render_default(&json!(expression), &context, &mut warnings);
+        (value, warnings)
+    }
+
+    // Cases from tests/unit/transpiler/jinja2-filters.test.ts
+
+    #[test]
+    fn case_filters() {
+        assert_eq!(text("upper", json!("HeLLo WoRLD"), &[]), "HELLO WORLD");
+        assert_eq!(text("upper", json!(123), &[]), "123");
+        assert_eq!(text("lower", json!("HeLLo WoRLD"), &[]), "hello world");
+        assert_eq!(text("lower", json!(123), &[]), "123");
+        assert_eq!(text("capitalize", json!("hELLO"), &[]), "Hello");
+        assert_eq!(text("capitalize", json!(""), &[]), "");
+        assert_eq!(text("title", json!("hELLO wORLD"), &[]), "Hello World");
+        assert_eq!(text("title", json!("hello"), &[]), "Hello");
+    }
+
+    #[test]
+    fn transformation_filters() {
+        let replace = [json!("world"), json!("there")];
+        assert_eq!(
+            text("replace", json!("hello world world"), &replace),
+            "hello there there"
+        );
+        assert_eq!(
+            text("replace", json!("hello"), &[json!("x"), json!("y")]),
+            "hello"
+        );
+
+        assert_eq!(text("slug", json!("Hello World"), &[]), "hello-world");
+        assert_eq!(text("slug", json!("Hello! World?"), &[]), "hello-world");
+        assert_eq!(text("slug", json!("hello   world"), &[]), "hello-world");
+        assert_eq!(text("slug", json!("  hello world  "), &[]), "hello-world");
+
+        for (input, snake, camel, pascal) in [
+            ("HelloWorld", "hello_world", "helloWorld", "HelloWorld"),
+            ("hello world", "hello_world", "helloWorld", "HelloWorld"),
+            ("hello-world", "hello_world", "helloWorld", "HelloWorld"),
+            ("hello_world", "hello_world", "helloWorld", "HelloWorld"),
+            ("Hello_world", "hello_world", "helloWorld", "HelloWorld"),
+        ] {
+            assert_eq!(text("snake", json!(input), &[]), snake, "snake {}", input);
+            assert_eq!(text("camel", json!(input), &[]), camel, "camel {}", input);
+            assert_eq!(
+                text("pascal", json!(input), &[]),
+                pascal,
+                "pascal {}",
+                input
+            );
+        }
+
+        assert_eq!(text("trim", json!("\t\nhello\n\t"), &[]), "hello");
+    }
+
+    #[test]
+    fn truncate_and_wordwrap() {
+        assert_eq!(
+            text("truncate", json!("hello world"), &[json!(8)]),
+            "hello..."
+        );
+        assert_eq!(text("truncate", json!("hi"), &[json!(10)]), "hi");
+        assert_eq!(
+            text("truncate", json!("hello world"), &[json!(8), json!("!")]),
+            "hello w!"
+        );
+        assert_eq!(text("truncate", json!("a".repeat(60)), &[]).len(), 50);
+
+        assert_eq!(
+            text("wordwrap", json!("hello world how are you"), &[json!(10)]),
+            "hello\nworld how\nare you"
+        );
+        assert_eq!(
+            text("wordwrap", json!("supercalifragilistic"), &[json!(10)]),
+            "supercalifragilistic"
+        );
+        assert!(text("wordwrap", json!("a ".repeat(50)), &[]).contains('\n'));
+    }
+
+    #[test]
+    fn array_and_string_filters() {
+        let items = json!(["a", "b", "c"]);
+        assert_eq!(filter("first", items.clone(), &[]), Some(json!("a")));
+        assert_eq!(filter("first", json!("hello"), &[]), Some(json!("h")));
+        assert_eq!(filter("first", json!([]), &[]), None);
+        assert_eq!(filter("last", items.clone(), &[]), Some(json!("c")));
+        assert_eq!(filter("last", json!("hello"), &[]), Some(json!("o")));
+        assert_eq!(filter("last", json!([]), &[]), None);
+
+        assert_eq!(text("join", items.clone(), &[]), "a, b, c");
+        assert_eq!(text("join", items.clone(), &[json!("-")]), "a-b-c");
+        assert_eq!(text("join", json!("hello"), &[]), "hello");
+
+        assert_eq!(filter("split", json!("a,b,c"), &[]), Some(items.clone()));
+        assert_eq!(
+            filter("split", json!("a-b-c"), &[json!("-")]),
+            Some(items.clone())
+        );
+
+        assert_eq!(filter("length", items, &[]), Some(json!(3)));
+        assert_eq!(filter("length", json!("hello"), &[]), Some(json!(5)));
+        assert_eq!(filter("length", json!([]), &[]), Some(json!(0)));
+        assert_eq!(filter("length", json!(""), &[]), Some(json!(0)));
+    }
+
+    #[test]
+    fn conversion_filters() {
+        assert_eq!(filter("int", json!("42"), &[]), Some(json!(42)));
+        assert_eq!(filter("int", json!("3.99"), &[]), Some(json!(3)));
+        assert_eq!(
+            filter("int", json!("not a number"), &[json!(10)]),
+            Some(json!(10))
+        );
+        assert_eq!(filter("int", json!("abc"), &[]), Some(json!(0)));
+
+        assert_eq!(filter("float", json!("2.75"), &[]), Some(json!(2.75)));
+        assert_eq!(filter("float", json!("42"), &[]), Some(json!(42)));
+        assert_eq!(
+            filter("float", json!("not a number"), &[json!(1.5)]),
+            Some(json!(1.5))
+        );
+        assert_eq!(filter("float", json!("abc"), &[]), Some(json!(0)));
+
+        assert_eq!(text("string", json!(42), &[]), "42");
+        assert_eq!(text("string", json!(true), &[]), "true");
+        assert_eq!(text("string", Value::Null, &[]), "null");
+
+        for (input, expected) in [
+            (json!(true), true),
+            (json!(false), false),
+            (json!("true"), true),
+            (json!("yes"), true),
+            (json!("1"), true),
+            (json!("on"), true),
+            (json!("TRUE"), true),
+            (json!("YES"), true),
+            (json!("false"), false),
+            (json!("no"), false),
+            (json!(""), false),
+            (json!(1), true),
+            (json!(0), false),
+        ] {
+            assert_eq!(
+                filter("bool", input.clone(), &[]),
+                Some(json!(expected)),
+                "bool {}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn numeric_filters() {
+        assert_eq!(filter("abs", json!(5), &[]), Some(json!(5)));
+        assert_eq!(filter("abs", json!(-5), &[]), Some(json!(5)));
+        assert_eq!(filter("abs", json!("-42"), &[]), Some(json!(42)));
+
+        assert_eq!(filter("round", json!(3.5), &[]), Some(json!(4)));
+        assert_eq!(filter("round", json!(3.4), &[]), Some(json!(3)));
+        assert_eq!(
+            filter("round", json!(1.23456), &[json!(2)]),
+            Some(json!(1.23))
+        );
+        assert_eq!(
+            filter("round", json!(1.23456), &[json!(3)]),
+            Some(json!(1.235))
+        );
+        assert_eq!(filter("round", json!("3.5"), &[]), Some(json!(4)));
+    }
+
+    #[test]
+    fn default_filter() {
+        let fallback = [json!("default")];
+        assert_eq!(
+            filter("default", json!("hello"), &fallback),
+            Some(json!("hello"))
+        );
+        assert_eq!(
+            filter("default", Value::Null, &fallback),
+            Some(json!("default"))
+        );
+        assert_eq!(
+            apply_filter("default", None, &[Some(json!("default"))]),
+            Some(Some(json!("default")))
+        );
+        assert_eq!(
+            filter("default", json!(""), &fallback),
+            Some(json!("default"))
+        );
+        assert_eq!(filter("default", json!(0), &fallback), Some(json!(0)));
+        assert_eq!(
+            filter("default", json!(false), &fallback),
+            Some(json!(false))
+        );
+    }
+
+    #[test]
+    fn substitutes_variables() {
+        assert_eq!(
+            eval("{{ name }}", json!({ "name": "John" })).0,
+            Some(json!("John"))
+        );
+        assert_eq!(
+            eval("not a template", json!({ "name": "John" })).0,
+            Some(json!("not a template"))
+        );
+        assert_eq!(eval("{{ missing }}", json!({})).0, None);
+
+        let user = json!({ "user": { "name": "John" } });
+        assert_eq!(eval("{{ user.name }}", user.clone()).0, Some(json!("John")));
+        assert_eq!(eval("{{ user.missing }}", user).0, None);
+        assert_eq!(
+            eval("{{ a.b.c }}", json!({ "a": { "b": { "c": "deep" } } })).0,
+            Some(json!("deep"))
+        );
+    }
+
+    #[test]
+    fn reads_environment_variables() {
+        std::env::set_var("UPG_JINJA_TEST_VAR", "test_value");
+        assert_eq!(
+            eval("{{ env.UPG_JINJA_TEST_VAR }}", json!({})).0,
+            Some(json!("test_value"))
+        );
+        assert_eq!(
+            eval("{{ env.UPG_JINJA_TEST_MISSING }}", json!({})).0,
+            Some(json!(""))
+        );
+    }
+
+    #[test]
+    fn applies_filter_chains() {
+        let name = json!({ "name": "JOHN DOE" });
+        assert_eq!(
+            eval("{{ name | upper }}", json!({ "name": "john" })).0,
+            Some(json!("JOHN"))
+        );
+        assert_eq!(
+            eval("{{ name | lower | capitalize }}", name).0,
+            Some(json!("John doe"))
+        );
+        assert_eq!(
+            eval(
+                "{{ items | join(\"-\") }}",
+                json!({ "items": ["a", "b", "c"] })
+            )
+            .0,
+            Some(json!("a-b-c"))
+        );
+        let hello = json!({ "text": "hello world" });
+        assert_eq!(
+            eval("{{ text | truncate(5) }}", hello.clone()).0,
+            Some(json!("he..."))
+        );
+        assert_eq!(
+            eval("{{ text | replace(\"world\", \"there\") }}", hello).0,
+            Some(json!("hello there"))
+        );
+        assert_eq!(
+            eval("{{ val | default(true) }}", json!({ "val": null })).0,
+            Some(json!(true))
+        );
+        assert_eq!(
+            eval("{{ val | default(false) }}", json!({})).0,
+            Some(json!(false))
+        );
+        assert_eq!(
+            eval("{{   name   |   upper   }}", json!({ "name": "john" })).0,
+            Some(json!("JOHN"))
+        );
+        assert_eq!(
+            eval(
+                "{{ name | slug | upper }}",
+                json!({ "name": "Hello World" })
+            )
+            .0,
+            Some(json!("HELLO-WORLD"))
+        );
+    }
+
+    #[test]
+    fn skips_unknown_filters_with_a_warning() {
+        let (value, warnings) = eval("{{ name | unknownFilter }}", json!({ "name": "john" }));
+        assert_eq!(value, Some(json!("john")));
+        assert_eq!(warnings, vec!["Unknown filter 'unknownFilter'"]);
+    }
+
+    #[test]
+    fn renders_text_around_expressions() {
+        let context = json!({ "name": "api", "port": 8080 });
+        assert_eq!(
+            eval(
+                "{{ name }}-service:{{ port }}{{ missing }}",
+                context.clone()
+            )
+            .0,
+            Some(json!("api-service:8080"))
+        );
+        // A lone expression keeps its type
+        assert_eq!(eval(" {{ port }} ", context).0, Some(json!(8080)));
+    }
+}
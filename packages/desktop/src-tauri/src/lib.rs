@@ -4,10 +4,43 @@
 //! v1 uses the CLI (upg) as the single generation engine — the CLI binary is bundled as a
 //! resource (not sidecar), executed via std::process::Command from a single Rust function.
 
+mod actions;
+mod answers;
+mod bookmarks;
+mod catalog;
+mod conditions;
+mod deeplink;
+mod determinism;
+mod diff;
+mod error_codes;
+mod files;
+mod headless;
+mod history;
+mod hooks;
+mod identify;
+mod instance;
+mod jinja;
+mod library;
+mod lifecycle;
+mod manifest;
+mod merge;
+mod process;
+mod provenance;
+mod recipe;
+mod seeds;
+mod templates;
+mod update;
+mod validation;
+mod watcher;
+
+use files::{describe_files, FileMetadata, FileSnapshot, FileSummary};
+use provenance::ProjectProvenance;
+use catalog::{CatalogMetadata, CatalogReport};
+use templates::{DiscoveredTemplate, TemplateRoot};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 use tauri::Manager;
 use tauri_plugin_store::StoreExt;
@@ -87,11 +120,15 @@ pub struct GenerationResult {
     pub success: bool,
     pub message: String,
     pub files_generated: Vec<String>,
+    /// Per-file metadata, sorted by path
+    pub files: Vec<FileMetadata>,
+    pub summary: FileSummary,
     pub output_path: String,
     pub duration_ms: u64,
 }
 
 /// Get the target triple for the current platform (compile-time)
+#[cfg_attr(debug_assertions, allow(dead_code))]
 fn get_target_triple() -> &'static str {
     #[cfg(all(target_os = "linux", target_arch = "x86_64"))]
     { "x86_64-unknown-linux-gnu" }
@@ -107,18 +144,54 @@ fn get_target_triple() -> &'static str {
     { "aarch64-pc-windows-msvc" }
 }
 
+/// Directories the backend resolves paths against
+///
+/// Normally taken from the running app; headless mode builds them from the executable
+/// location because it never creates one.
+#[derive(Debug, Clone, Default)]
+struct AppPaths {
+    /// Bundled resources (CLI binary, templates)
+    #[cfg_attr(debug_assertions, allow(dead_code))]
+    resource_dir: Option<PathBuf>,
+    /// Working directory of the CLI and base for relative paths
+    home_dir: Option<PathBuf>,
+    /// Holds the stores and the user's templates
+    app_data_dir: Option<PathBuf>,
+}
+
+impl AppPaths {
+    fn from_app(app: &tauri::AppHandle) -> Self {
+        Self {
+            resource_dir: app.path().resource_dir().ok(),
+            home_dir: app.path().home_dir().ok(),
+            app_data_dir: app.path().app_data_dir().ok(),
+        }
+    }
+
+    fn home_dir(&self) -> Result<PathBuf, String> {
+        self.home_dir
+            .clone()
+            .ok_or_else(|| "Failed to resolve home directory".to_string())
+    }
+}
+
 /// Get the path to the UPG CLI executable
 /// In development: uses the built CLI from the monorepo via node
 /// In production: uses the CLI binary bundled as a resource
 fn get_cli_command(app: &tauri::AppHandle) -> Result<(String, Vec<String>), String> {
+    cli_command(&AppPaths::from_app(app))
+}
+
+/// CLI command and base arguments for the given directories
+#[cfg_attr(debug_assertions, allow(unused_variables))]
+fn cli_command(paths: &AppPaths) -> Result<(String, Vec<String>), String> {
     #[cfg(debug_assertions)]
     {
         // In development, use the built CLI from packages/cli/dist/bin/upg.js via node
         // This is acceptable in dev because we control the environment
-        let manifest_dir = env!("CARGO_MANIFEST_DIR");
-        let project_root = PathBuf::from(manifest_dir)
-            .parent() // src-tauri
-            .and_then(|p| p.parent()) // desktop
+        let manifest_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        let project_root = manifest_dir
+            .parent() // desktop
             .and_then(|p| p.parent()) // packages
             .and_then(|p| p.parent()) // project root
             .ok_or("Failed to find project root")?;
@@ -137,7 +210,10 @@ fn get_cli_command(app: &tauri::AppHandle) -> Result<(String, Vec<String>), Stri
     #[cfg(not(debug_assertions))]
     {
         // In production, use the CLI binary bundled as a resource
-        let resource_dir = app.path().resource_dir().map_err(|e| e.to_string())?;
+        let resource_dir = paths
+            .resource_dir
+            .clone()
+            .ok_or("Failed to resolve resource directory")?;
         let target = get_target_triple();
 
         #[cfg(windows)]
@@ -266,35 +342,55 @@ fn execute_cli_internal(
     Ok((success, stdout, stderr, exit_code))
 }
 
-/// List files in a directory recursively
-fn list_files_recursive(dir: &PathBuf) -> Vec<String> {
-    let mut files = Vec::new();
+/// Get the version reported by the upg CLI, or "unknown" if it cannot be determined
+fn get_engine_version(app: &tauri::AppHandle) -> String {
+    match get_cli_command(app) {
+        Ok((cmd, args)) => cli_version(&cmd, args),
+        Err(_) => "unknown".to_string(),
+    }
+}
+
+/// Get the version reported by a CLI command, or "unknown" if it cannot be determined
+fn cli_version(cmd: &str, mut args: Vec<String>) -> String {
+    args.push("--version".to_string());
 
-    if let Ok(entries) = fs::read_dir(dir) {
-        for entry in entries.flatten() {
-            let path = entry.path();
-            if path.is_file() {
-                if let Ok(relative) = path.strip_prefix(dir) {
-                    files.push(relative.to_string_lossy().to_string());
-                }
-            } else if path.is_dir() {
-                // Recurse into subdirectories
-                let subfiles = list_files_recursive(&path);
-                for subfile in subfiles {
-                    if let Ok(relative) = path.strip_prefix(dir) {
-                        files.push(format!("{}/{}", relative.to_string_lossy(), subfile));
-                    }
-                }
-            }
-        }
+    let cwd = std::env::temp_dir();
+    match execute_cli_internal(cmd, args, &cwd) {
+        Ok((true, stdout, _, _)) if !stdout.trim().is_empty() => stdout.trim().to_string(),
+        _ => "unknown".to_string(),
     }
+}
 
-    files
+/// Write the provenance file into a freshly generated project
+///
+/// Failure to record provenance never fails the generation itself.
+fn record_provenance(
+    app: &tauri::AppHandle,
+    project_dir: &Path,
+    provenance: Result<ProjectProvenance, String>,
+) {
+    let provenance = match provenance {
+        Ok(p) => p,
+        Err(e) => {
+            eprintln!("Failed to record provenance in {:?}: {}", project_dir, e);
+            return;
+        }
+    };
+    if let Err(e) = provenance::write_provenance(project_dir, &provenance) {
+        eprintln!("Failed to record provenance in {:?}: {}", project_dir, e);
+    }
+    if let Err(e) = history::record_generation(app, project_dir, &provenance) {
+        eprintln!("Failed to record generation history: {}", e);
+    }
 }
 
 /// Resolve output path to an absolute path
 /// If relative, resolves against the user's home directory or current directory
 fn resolve_output_path(output_path: &str, app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    resolve_output_path_in(output_path, app.path().home_dir().ok().as_deref())
+}
+
+fn resolve_output_path_in(output_path: &str, home_dir: Option<&Path>) -> Result<PathBuf, String> {
     let cleaned = output_path.strip_prefix("./").unwrap_or(output_path);
     let path = PathBuf::from(cleaned);
 
@@ -304,7 +400,7 @@ fn resolve_output_path(output_path: &str, app: &tauri::AppHandle) -> Result<Path
 
     // For relative paths, resolve against user's home directory or document directory
     // This gives users a predictable location for their generated projects
-    if let Ok(home_dir) = app.path().home_dir() {
+    if let Some(home_dir) = home_dir {
         return Ok(home_dir.join(cleaned));
     }
 
@@ -314,6 +410,63 @@ fn resolve_output_path(output_path: &str, app: &tauri::AppHandle) -> Result<Path
         .map_err(|e| format!("Failed to resolve output path: {}", e))
 }
 
+/// Resolve a template path to an absolute path
+/// Relative paths resolve against the home directory, the CLI's working directory;
+/// `name@version` selects that version of a discovered template unless such a
+/// directory exists.
+fn resolve_template_path(template_path: &str, app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    let path = PathBuf::from(template_path);
+    if path.is_absolute() {
+        return Ok(path);
+    }
+    let path = app
+        .path()
+        .home_dir()
+        .map(|home| home.join(path))
+        .map_err(|e| format!("Failed to resolve template path: {}", e))?;
+    match templates::parse_template_ref(template_path) {
+        Some((name, version)) if !path.exists() => resolve_template_ref(app, name, version),
+        _ => Ok(path),
+    }
+}
+
+/// Directory of version `version` of template `name`
+fn resolve_template_ref(
+    app: &tauri::AppHandle,
+    name: &str,
+    version: &str,
+) -> Result<PathBuf, String> {
+    let discovered = discover_templates(app);
+    if let Some(index) = templates::find_template(&discovered, name, Some(version)) {
+        return Ok(discovered[index].path.clone());
+    }
+
+    let mut available: Vec<&str> = discovered
+        .iter()
+        .map(|t| &t.manifest.metadata)
+        .filter(|meta| meta.name == name)
+        .map(|meta| meta.version.as_str())
+        .collect();
+    available.sort_by(|a, b| templates::newest_first(a, b));
+    available.dedup();
+    Err(if available.is_empty() {
+        format!("Template {} is not installed", name)
+    } else {
+        format!(
+            "Template {}@{} is not installed (available: {})",
+            name,
+            version,
+            available.join(", ")
+        )
+    })
+}
+
+/// Parse generation answers passed as a JSON object string (invalid input yields no answers)
+fn parse_answers(data: Option<&str>) -> serde_json::Map<String, Value> {
+    data.and_then(|d| serde_json::from_str(d).ok())
+        .unwrap_or_default()
+}
+
 /// Generate a project using the CLI (upg seed command)
 ///
 /// Primary invariant: upg seed <SEED> --output <DIR> [constraints...]
@@ -323,6 +476,22 @@ fn resolve_output_path(output_path: &str, app: &tauri::AppHandle) -> Result<Path
 async fn generate_project(
     app: tauri::AppHandle,
     request: GenerationRequest,
+) -> Result<GenerationResult, String> {
+    let result = generate_procedural(&AppPaths::from_app(&app), &request)?;
+    if result.success {
+        record_provenance(
+            &app,
+            Path::new(&result.output_path),
+            Ok(ProjectProvenance::procedural(&request, get_engine_version(&app))),
+        );
+    }
+    Ok(result)
+}
+
+/// Run `upg seed` for a procedural request; provenance is left to the caller
+fn generate_procedural(
+    paths: &AppPaths,
+    request: &GenerationRequest,
 ) -> Result<GenerationResult, String> {
     let start = std::time::Instant::now();
 
@@ -334,11 +503,12 @@ async fn generate_project(
                 .ok_or("Seed is required for procedural generation")?;
 
             // Resolve the output path to an absolute path
-            let resolved_output = resolve_output_path(&request.output_path, &app)?;
+            let resolved_output =
+                resolve_output_path_in(&request.output_path, paths.home_dir.as_deref())?;
             let resolved_output_str = resolved_output.to_string_lossy().to_string();
 
             // Get CLI command and base args
-            let (cmd, base_args) = get_cli_command(&app)?;
+            let (cmd, base_args) = cli_command(paths)?;
 
             // Build CLI arguments for seed command
             let cli_args = build_cli_args(seed, &resolved_output_str, &request.stack, &request.enrichment);
@@ -348,7 +518,10 @@ async fn generate_project(
             all_args.extend(cli_args);
 
             // Get working directory (home dir in both dev and prod)
-            let working_dir = app.path().home_dir().map_err(|e| e.to_string())?;
+            let working_dir = paths.home_dir()?;
+
+            // Snapshot existing files so each output file can be classified afterwards
+            let before = FileSnapshot::capture(&resolved_output);
 
             // Execute the CLI
             let (success, stdout, stderr, exit_code) = execute_cli_internal(&cmd, all_args, &working_dir)?;
@@ -361,7 +534,7 @@ async fn generate_project(
                     .and_then(|value| value.as_bool())
                     .unwrap_or(false);
 
-                let files_generated = response
+                let reported_files = response
                     .get("files_generated")
                     .and_then(|value| value.as_array())
                     .map(|files| {
@@ -369,10 +542,13 @@ async fn generate_project(
                             .iter()
                             .filter_map(|file| file.as_str().map(String::from))
                             .collect::<Vec<String>>()
-                    })
-                    .unwrap_or_default();
+                    });
 
                 if cli_success {
+                    let (files, summary) =
+                        describe_files(&resolved_output, reported_files.as_deref(), &before);
+                    let files_generated = files.iter().map(|f| f.path.clone()).collect::<Vec<_>>();
+
                     let message = format!(
                         "Generated {} files for seed {} in {}ms",
                         files_generated.len(),
@@ -384,6 +560,8 @@ async fn generate_project(
                         success: true,
                         message,
                         files_generated,
+                        files,
+                        summary,
                         output_path: resolved_output_str,
                         duration_ms,
                     })
@@ -398,17 +576,16 @@ async fn generate_project(
                         success: false,
                         message: error_msg,
                         files_generated: vec![],
+                        files: vec![],
+                        summary: FileSummary::default(),
                         output_path: resolved_output_str,
                         duration_ms,
                     })
                 }
             } else if success {
                 // List generated files from the output directory
-                let files_generated = if resolved_output.exists() {
-                    list_files_recursive(&resolved_output)
-                } else {
-                    vec![]
-                };
+                let (files, summary) = describe_files(&resolved_output, None, &before);
+                let files_generated = files.iter().map(|f| f.path.clone()).collect::<Vec<_>>();
 
                 let message = format!(
                     "Generated {} files for seed {} in {}ms",
@@ -421,6 +598,8 @@ async fn generate_project(
                     success: true,
                     message,
                     files_generated,
+                    files,
+                    summary,
                     output_path: resolved_output_str,
                     duration_ms,
                 })
@@ -438,6 +617,8 @@ async fn generate_project(
                     success: false,
                     message: error_msg,
                     files_generated: vec![],
+                    files: vec![],
+                    summary: FileSummary::default(),
                     output_path: resolved_output_str,
                     duration_ms,
                 })
@@ -457,51 +638,37 @@ pub struct TemplateEntry {
     pub icon: Option<String>,
     pub author: Option<String>,
     pub lifecycle: String,
+    /// Warning shown for experimental/deprecated templates under the `flag` policy
+    pub lifecycle_notice: Option<String>,
     pub path: String,
+    /// Root the template was discovered under
+    pub source_root: TemplateRoot,
+    /// Paths of lower-priority templates with the same name and version
+    pub shadows: Vec<String>,
+    /// Catalog entry describing the template, if any
+    pub catalog: Option<CatalogMetadata>,
+    /// Every version found, newest first within each root (the entry is the first)
+    pub versions: Vec<TemplateVersion>,
 }
 
-/// YAML manifest metadata (partial parse for template info)
-#[derive(Debug, Deserialize)]
-struct ManifestMetadata {
-    name: String,
-    #[serde(default = "default_version")]
-    version: String,
-    #[serde(default)]
-    title: Option<String>,
-    #[serde(default)]
-    description: Option<String>,
-    #[serde(default)]
-    tags: Vec<String>,
-    #[serde(default)]
-    icon: Option<String>,
-    #[serde(default)]
-    author: Option<String>,
-    #[serde(default = "default_lifecycle")]
-    lifecycle: String,
-}
-
-fn default_version() -> String {
-    "1.0.0".to_string()
-}
-
-fn default_lifecycle() -> String {
-    "production".to_string()
-}
-
-#[derive(Debug, Deserialize)]
-struct ManifestFile {
-    metadata: ManifestMetadata,
+/// One version of a template
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TemplateVersion {
+    pub version: String,
+    pub path: String,
+    pub lifecycle: String,
+    pub source_root: TemplateRoot,
 }
 
-/// Get the templates directory path
-fn get_templates_dir(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+/// Bundled templates directory for the given directories
+#[cfg_attr(debug_assertions, allow(unused_variables))]
+fn templates_dir(paths: &AppPaths) -> Result<PathBuf, String> {
     #[cfg(debug_assertions)]
     {
         // In development, use the source templates directory
-        let manifest_dir = env!("CARGO_MANIFEST_DIR");
-        let project_root = PathBuf::from(manifest_dir)
-            .parent() // src-tauri
-            .and_then(|p| p.parent()) // packages/desktop
+        let manifest_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        let project_root = manifest_dir
+            .parent() // packages/desktop
             .and_then(|p| p.parent()) // packages
             .and_then(|p| p.parent()) // project root
             .ok_or("Failed to find project root")?;
@@ -509,78 +676,151 @@ fn get_templates_dir(app: &tauri::AppHandle) -> Result<PathBuf, String> {
     }
     #[cfg(not(debug_assertions))]
     {
-        let resource_dir = app.path().resource_dir().map_err(|e| e.to_string())?;
+        let resource_dir = paths
+            .resource_dir
+            .clone()
+            .ok_or("Failed to resolve resource directory")?;
         Ok(resource_dir.join("templates"))
     }
 }
 
+/// Discover templates under the configured template roots and apply template catalogs
+fn discover_templates(app: &tauri::AppHandle) -> Vec<DiscoveredTemplate> {
+    discover_templates_with(&AppPaths::from_app(app), |key| read_setting(app, key)).0
+}
+
+/// Discover templates using `setting` to read the root and catalog settings
+fn discover_templates_with(
+    paths: &AppPaths,
+    setting: impl Fn(&str) -> Option<Value>,
+) -> (Vec<DiscoveredTemplate>, Vec<CatalogReport>) {
+    let configs = templates::root_configs(setting(templates::TEMPLATE_ROOTS_SETTING));
+    let mut discovered = templates::discover_in_roots(&templates::resolve_roots(paths, &configs));
+
+    let locations = catalog::catalog_locations(setting(catalog::TEMPLATE_CATALOGS_SETTING));
+    let catalogs = catalog::load_catalogs(paths, &locations);
+    let reports = catalog::merge_catalogs(&mut discovered, catalogs);
+    (discovered, reports)
+}
+
 /// Get available templates from the templates directory
+///
+/// Experimental and deprecated templates are flagged or left out per the
+/// `templateLifecycle` setting.
 #[tauri::command]
 async fn get_templates(app: tauri::AppHandle) -> Result<Vec<TemplateEntry>, String> {
-    let templates_dir = get_templates_dir(&app)?;
-    let mut templates = Vec::new();
-
-    if !templates_dir.exists() {
-        return Ok(templates);
-    }
-
-    // Read all subdirectories in templates/
-    let entries = fs::read_dir(&templates_dir).map_err(|e| {
-        format!(
-            "Failed to read templates directory {:?}: {}",
-            templates_dir, e
-        )
-    })?;
+    let policy =
+        lifecycle::lifecycle_policy(read_setting(&app, lifecycle::TEMPLATE_LIFECYCLE_SETTING));
+    Ok(template_entries(discover_templates(&app), &policy))
+}
 
-    for entry in entries {
-        let entry = match entry {
-            Ok(e) => e,
-            Err(_) => continue,
-        };
+/// Full template definition, as needed to build a template's prompt form
+#[derive(Debug, Clone, Serialize)]
+pub struct TemplateDetail {
+    /// Template directory
+    pub path: String,
+    pub metadata: manifest::ManifestMetadata,
+    pub prompts: Vec<manifest::ManifestPrompt>,
+    pub actions: Vec<manifest::ManifestAction>,
+    pub hooks: Option<manifest::ManifestHooks>,
+    pub documentation: Option<manifest::ManifestDocumentation>,
+    pub smart_update: Option<manifest::SmartUpdateConfig>,
+}
 
-        let path = entry.path();
-        if !path.is_dir() {
-            continue;
-        }
+/// Get a template's prompts, actions, hooks, documentation and smart update settings
+#[tauri::command]
+async fn get_template_detail(path: String) -> Result<TemplateDetail, String> {
+    let manifest_path = manifest::resolve_manifest_path(Path::new(&path))
+        .ok_or_else(|| format!("No manifest found at {}", path))?;
+    let manifest = manifest::load_manifest(&manifest_path)?;
+    let dir = manifest_path.parent().unwrap_or(Path::new(""));
+
+    Ok(TemplateDetail {
+        path: dir.to_string_lossy().to_string(),
+        metadata: manifest.metadata,
+        prompts: manifest.prompts,
+        actions: manifest.actions,
+        hooks: manifest.hooks,
+        documentation: manifest.documentation,
+        smart_update: manifest.template.and_then(|t| t.smart_update),
+    })
+}
 
-        // Look for upg.yaml manifest
-        let manifest_path = path.join("upg.yaml");
-        if !manifest_path.exists() {
-            continue;
+/// One entry per template name for its default version, listing the other versions
+///
+/// The lifecycle policy applies to each version on its own: hidden versions are left
+/// out of `versions`, and the default is the first version still listed.
+fn template_entries(
+    templates: Vec<DiscoveredTemplate>,
+    policy: &lifecycle::LifecyclePolicy,
+) -> Vec<TemplateEntry> {
+    let mut groups: Vec<Vec<DiscoveredTemplate>> = Vec::new();
+    let mut by_name: std::collections::HashMap<String, usize> = Default::default();
+    for template in templates {
+        let name = template.manifest.metadata.name.clone();
+        match by_name.get(&name) {
+            Some(&index) => groups[index].push(template),
+            None => {
+                by_name.insert(name, groups.len());
+                groups.push(vec![template]);
+            }
         }
+    }
 
-        // Parse the manifest
-        let content = match fs::read_to_string(&manifest_path) {
-            Ok(c) => c,
-            Err(e) => {
-                eprintln!("Failed to read manifest {:?}: {}", manifest_path, e);
-                continue;
-            }
-        };
+    groups
+        .into_iter()
+        .filter_map(|mut group| {
+            group.sort_by(templates::default_order);
+            let entries = group.into_iter().map(template_entry).collect();
+            let mut entries = lifecycle::apply_lifecycle_policy(entries, policy).into_iter();
+            let mut entry = entries.next()?;
+            entry.versions.extend(entries.flat_map(|e| e.versions));
+            Some(entry)
+        })
+        .collect()
+}
 
-        let manifest: ManifestFile = match serde_yaml::from_str(&content) {
-            Ok(m) => m,
-            Err(e) => {
-                eprintln!("Failed to parse manifest {:?}: {}", manifest_path, e);
-                continue;
-            }
-        };
+fn template_entry(template: DiscoveredTemplate) -> TemplateEntry {
+    let meta = template.manifest.metadata;
+    let mut entry = TemplateEntry {
+        name: meta.name.clone(),
+        version: meta.version,
+        title: meta.title.unwrap_or_else(|| meta.name.clone()),
+        description: meta.description.unwrap_or_default(),
+        tags: meta.tags,
+        icon: meta.icon,
+        author: meta.author,
+        lifecycle: meta.lifecycle,
+        lifecycle_notice: None,
+        path: template.path.to_string_lossy().to_string(),
+        source_root: template.root,
+        shadows: template.shadows,
+        catalog: None,
+        versions: vec![],
+    };
 
-        let meta = manifest.metadata;
-        templates.push(TemplateEntry {
-            name: meta.name.clone(),
-            version: meta.version,
-            title: meta.title.unwrap_or_else(|| meta.name.clone()),
-            description: meta.description.unwrap_or_default(),
-            tags: meta.tags,
-            icon: meta.icon,
-            author: meta.author,
-            lifecycle: meta.lifecycle,
-            path: path.to_string_lossy().to_string(),
-        });
+    // Catalog metadata overrides the manifest's
+    if let Some(catalog) = template.catalog {
+        if let Some(title) = &catalog.title {
+            entry.title = title.clone();
+        }
+        if let Some(description) = &catalog.description {
+            entry.description = description.clone();
+        }
+        if !catalog.tags.is_empty() {
+            entry.tags = catalog.tags.clone();
+        }
+        entry.lifecycle = catalog.lifecycle.clone();
+        entry.author = Some(catalog.owner.clone());
+        entry.catalog = Some(catalog);
     }
-
-    Ok(templates)
+    entry.versions = vec![TemplateVersion {
+        version: entry.version.clone(),
+        path: entry.path.clone(),
+        lifecycle: entry.lifecycle.clone(),
+        source_root: entry.source_root.clone(),
+    }];
+    entry
 }
 
 /// Validation result
@@ -733,6 +973,20 @@ struct CLIPreviewData {
 async fn preview_generation(
     app: tauri::AppHandle,
     request: GenerationRequest,
+) -> Result<PreviewResult, String> {
+    run_preview(&app, &request)
+}
+
+/// Run the CLI preview command for a request (shared by preview, diff and verification commands)
+fn run_preview(app: &tauri::AppHandle, request: &GenerationRequest) -> Result<PreviewResult, String> {
+    run_preview_with(&AppPaths::from_app(app), get_cli_command(app)?, request)
+}
+
+/// Run a procedural preview with a specific CLI command (see `get_cli_command`)
+fn run_preview_with(
+    paths: &AppPaths,
+    cli: (String, Vec<String>),
+    request: &GenerationRequest,
 ) -> Result<PreviewResult, String> {
     // v1 only supports Procedural mode
     match request.mode {
@@ -741,8 +995,7 @@ async fn preview_generation(
                 .seed
                 .ok_or("Seed is required for procedural preview")?;
 
-            // Get CLI command
-            let (cmd, mut base_args) = get_cli_command(&app)?;
+            let (cmd, mut base_args) = cli;
 
             // Build preview args: preview <seed> [constraints...]
             let mut cli_args = vec!["preview".to_string(), seed.to_string()];
@@ -799,7 +1052,7 @@ async fn preview_generation(
             }
 
             // Working directory
-            let working_dir = app.path().home_dir().map_err(|e| e.to_string())?;
+            let working_dir = paths.home_dir()?;
 
             // Execute CLI
             base_args.extend(cli_args);
@@ -915,34 +1168,40 @@ pub struct TemplateGenerationResult {
     pub success: bool,
     pub message: String,
     pub files_generated: Vec<String>,
+    /// Per-file metadata, sorted by path
+    pub files: Vec<FileMetadata>,
+    pub summary: FileSummary,
     pub output_path: String,
     pub duration_ms: u64,
+    /// Outcome of each manifest `command` action
+    pub actions: Vec<actions::ActionResult>,
+    /// Manifest hooks that ran after generation
+    pub hooks: Vec<hooks::HookResult>,
+    /// Invalid answers keyed by prompt id; generation is not attempted when non-empty
+    #[serde(default)]
+    pub answer_errors: answers::AnswerErrors,
+    /// Breaking changes since the template version already in the output directory;
+    /// generation waits for `acknowledge_breaking_changes` when set
+    #[serde(default)]
+    pub breaking_changes: Option<lifecycle::BreakingChangeReport>,
 }
 
-/// Generate a project from a UPG manifest template using the CLI generate command
-///
-/// Uses: upg generate <template_path> --dest <output_dir> [--data <json>] [--use-defaults] [--force]
-#[tauri::command]
-async fn generate_from_template(
-    app: tauri::AppHandle,
-    template_path: String,
-    output_path: String,
-    data: Option<String>,
+/// Build CLI arguments for the generate (template) command
+fn build_template_cli_args(
+    template_path: &str,
+    output_path: &str,
+    data: &Option<String>,
     use_defaults: bool,
     force: bool,
-    enrichment_config: Option<EnrichmentConfig>,
-) -> Result<TemplateGenerationResult, String> {
-    let start = std::time::Instant::now();
-
-    let (cmd, base_args) = get_cli_command(&app)?;
-
-    let mut cli_args = vec!["generate".to_string(), template_path.clone()];
-
-    // Resolve and add output path
-    let resolved_output = resolve_output_path(&output_path, &app)?;
-    let resolved_output_str = resolved_output.to_string_lossy().to_string();
-    cli_args.push("--dest".to_string());
-    cli_args.push(resolved_output_str.clone());
+    skip_commands: bool,
+    enrichment: &Option<EnrichmentConfig>,
+) -> Vec<String> {
+    let mut cli_args = vec![
+        "generate".to_string(),
+        template_path.to_string(),
+        "--dest".to_string(),
+        output_path.to_string(),
+    ];
 
     // Add optional data as JSON
     if let Some(ref json_data) = data {
@@ -958,8 +1217,12 @@ async fn generate_from_template(
         cli_args.push("--force".to_string());
     }
 
+    if skip_commands {
+        cli_args.push("--skip-commands".to_string());
+    }
+
     // Add enrichment flags if enabled
-    if let Some(ref enrich) = enrichment_config {
+    if let Some(ref enrich) = enrichment {
         if enrich.enabled {
             cli_args.push("--enrich".to_string());
             cli_args.push("--enrich-depth".to_string());
@@ -993,21 +1256,191 @@ async fn generate_from_template(
         }
     }
 
+    cli_args
+}
+
+/// Generate a project from a UPG manifest template using the CLI generate command
+///
+/// Uses: upg generate <template_path> --dest <output_dir> [--data <json>] [--use-defaults] [--force]
+/// Command actions run afterwards only if listed verbatim in `approved_commands` (see
+/// `get_template_commands`), followed by the `post_generation` hook only if `run_hooks` is
+/// true (see `get_template_hooks`).
+/// Regenerating over a project made with an earlier version of the template stops with its
+/// breaking changes unless `acknowledge_breaking_changes` is set.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+async fn generate_from_template(
+    app: tauri::AppHandle,
+    template_path: String,
+    output_path: String,
+    data: Option<String>,
+    use_defaults: bool,
+    force: bool,
+    enrichment_config: Option<EnrichmentConfig>,
+    approved_commands: Option<Vec<String>>,
+    run_hooks: Option<bool>,
+    acknowledge_breaking_changes: Option<bool>,
+) -> Result<TemplateGenerationResult, String> {
+    let start = std::time::Instant::now();
+
+    let (cmd, base_args) = get_cli_command(&app)?;
+    let template_path = resolve_template_path(&template_path, &app)?
+        .to_string_lossy()
+        .to_string();
+
+    // Resolve output path
+    let resolved_output = resolve_output_path(&output_path, &app)?;
+    let resolved_output_str = resolved_output.to_string_lossy().to_string();
+
+    // Reject bad answers before spawning anything; manifests that fail to load are
+    // left for the CLI to report
+    let answers: serde_json::Map<String, Value> = match data.as_deref() {
+        Some(data) => serde_json::from_str(data)
+            .map_err(|e| format!("Invalid answer data: expected a JSON object ({})", e))?,
+        None => serde_json::Map::new(),
+    };
+    let answer_errors = answers::check_template_answers(Path::new(&template_path), &answers)
+        .unwrap_or_default();
+    if !answer_errors.is_empty() {
+        return Ok(TemplateGenerationResult {
+            success: false,
+            message: format!(
+                "{} answer(s) are invalid: {}",
+                answer_errors.len(),
+                answer_errors.keys().cloned().collect::<Vec<_>>().join(", ")
+            ),
+            files_generated: vec![],
+            files: vec![],
+            summary: FileSummary::default(),
+            output_path: resolved_output_str,
+            duration_ms: start.elapsed().as_millis() as u64,
+            actions: vec![],
+            hooks: vec![],
+            answer_errors,
+            breaking_changes: None,
+        });
+    }
+
+    if !acknowledge_breaking_changes.unwrap_or(false) {
+        let report = manifest::load_manifest(Path::new(&template_path))
+            .ok()
+            .and_then(|m| lifecycle::breaking_changes_in_dir(&resolved_output, &m));
+        if let Some(report) = report {
+            return Ok(TemplateGenerationResult {
+                success: false,
+                message: format!(
+                    "[{}] {} {} has breaking changes since {}; review them before regenerating",
+                    error_codes::INCOMPATIBLE_VERSION,
+                    report.template_name,
+                    report.to_version,
+                    report.from_version
+                ),
+                files_generated: vec![],
+                files: vec![],
+                summary: FileSummary::default(),
+                output_path: resolved_output_str,
+                duration_ms: start.elapsed().as_millis() as u64,
+                actions: vec![],
+                hooks: vec![],
+                answer_errors: answers::AnswerErrors::new(),
+                breaking_changes: Some(report),
+            });
+        }
+    }
+
+    let cli_args = build_template_cli_args(
+        &template_path,
+        &resolved_output_str,
+        &data,
+        use_defaults,
+        force,
+        true,
+        &enrichment_config,
+    );
+
     // Combine base args with CLI args
     let mut all_args = base_args;
     all_args.extend(cli_args);
 
     let working_dir = app.path().home_dir().map_err(|e| e.to_string())?;
+    let before = FileSnapshot::capture(&resolved_output);
     let (success, stdout, stderr, exit_code) = execute_cli_internal(&cmd, all_args, &working_dir)?;
 
-    let duration_ms = start.elapsed().as_millis() as u64;
-
     if success {
-        let files_generated = if resolved_output.exists() {
-            list_files_recursive(&resolved_output)
-        } else {
-            vec![]
-        };
+        let (files, summary) = describe_files(&resolved_output, None, &before);
+        let files_generated = files.iter().map(|f| f.path.clone()).collect::<Vec<_>>();
+
+        let mut action_results = Vec::new();
+        let mut hook_results = Vec::new();
+        let template = Path::new(&template_path);
+        if let (Ok(manifest), Ok(dir)) =
+            (manifest::load_manifest(template), hooks::template_dir(template))
+        {
+            action_results = actions::run_commands(
+                &app,
+                &manifest,
+                &resolved_output,
+                &answers,
+                approved_commands.as_deref().unwrap_or_default(),
+                std::time::Duration::from_secs(actions::DEFAULT_ACTION_TIMEOUT_SECS),
+            )
+            .await;
+
+            if action_results.iter().all(|a| a.success) {
+                hook_results.extend(
+                    hooks::run_hook(
+                        &app,
+                        hooks::HookKind::PostGeneration,
+                        &dir,
+                        &manifest,
+                        &resolved_output,
+                        &answers,
+                        run_hooks.unwrap_or(false),
+                        std::time::Duration::from_secs(hooks::DEFAULT_HOOK_TIMEOUT_SECS),
+                    )
+                    .await,
+                );
+            }
+        }
+
+        record_provenance(
+            &app,
+            &resolved_output,
+            ProjectProvenance::template(
+                &template_path,
+                data.as_deref(),
+                enrichment_config.clone(),
+                get_engine_version(&app),
+            ),
+        );
+
+        let duration_ms = start.elapsed().as_millis() as u64;
+
+        let failure = action_results
+            .iter()
+            .find(|a| !a.success)
+            .map(|a| (a.code.as_deref(), error_codes::COMMAND_FAILED, &a.message))
+            .or_else(|| {
+                hook_results
+                    .iter()
+                    .find(|h| !h.success)
+                    .map(|h| (h.code.as_deref(), error_codes::HOOK_FAILED, &h.message))
+            });
+        if let Some((code, fallback, message)) = failure {
+            return Ok(TemplateGenerationResult {
+                success: false,
+                message: format!("[{}] {}", code.unwrap_or(fallback), message),
+                files_generated,
+                files,
+                summary,
+                output_path: resolved_output_str,
+                duration_ms,
+                actions: action_results,
+                hooks: hook_results,
+                answer_errors: answers::AnswerErrors::new(),
+                breaking_changes: None,
+            });
+        }
 
         Ok(TemplateGenerationResult {
             success: true,
@@ -1017,10 +1450,17 @@ async fn generate_from_template(
                 duration_ms
             ),
             files_generated,
+            files,
+            summary,
             output_path: resolved_output_str,
             duration_ms,
+            actions: action_results,
+            hooks: hook_results,
+            answer_errors: answers::AnswerErrors::new(),
+            breaking_changes: None,
         })
     } else {
+        let duration_ms = start.elapsed().as_millis() as u64;
         let error_msg = if !stderr.is_empty() {
             stderr
                 .lines()
@@ -1041,12 +1481,31 @@ async fn generate_from_template(
             success: false,
             message: error_msg,
             files_generated: vec![],
+            files: vec![],
+            summary: FileSummary::default(),
             output_path: resolved_output_str,
             duration_ms,
+            actions: vec![],
+            hooks: vec![],
+            answer_errors: answers::AnswerErrors::new(),
+            breaking_changes: None,
         })
     }
 }
 
+/// Read a setting from the persistent store (`None` when unset or unreadable)
+fn read_setting(app: &tauri::AppHandle, key: &str) -> Option<Value> {
+    app.store("settings.json").ok()?.get(key)
+}
+
+/// Read a setting from the store file without an app (used by headless mode)
+fn read_setting_file(paths: &AppPaths, key: &str) -> Option<Value> {
+    let file = paths.app_data_dir.as_ref()?.join("settings.json");
+    let content = fs::read_to_string(file).ok()?;
+    let mut settings: serde_json::Map<String, Value> = serde_json::from_str(&content).ok()?;
+    settings.remove(key)
+}
+
 /// Get a setting from the persistent store
 #[tauri::command]
 async fn get_setting(app: tauri::AppHandle, key: String) -> Result<serde_json::Value, String> {
@@ -1063,6 +1522,12 @@ async fn set_setting(app: tauri::AppHandle, key: String, value: serde_json::Valu
     let store = app.store("settings.json").map_err(|e| e.to_string())?;
     store.set(&key, value);
     store.save().map_err(|e| format!("Failed to save settings: {}", e))?;
+    if key == templates::TEMPLATE_ROOTS_SETTING
+        || key == catalog::TEMPLATE_CATALOGS_SETTING
+        || key == lifecycle::TEMPLATE_LIFECYCLE_SETTING
+    {
+        watcher::restart(&app);
+    }
     Ok(())
 }
 
@@ -1079,20 +1544,43 @@ async fn get_all_settings(app: tauri::AppHandle) -> Result<serde_json::Value, St
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    let context = tauri::generate_context!();
+    let identifier = context.config().identifier.clone();
+    if let Some(code) = headless::run_if_requested(context.package_info(), &identifier) {
+        std::process::exit(code);
+    }
+
     tauri::Builder::default()
+        // Must be registered first so a second launch exits before doing any work
+        .plugin(tauri_plugin_single_instance::init(|app, args, cwd| {
+            instance::handle_args(app, &args, Path::new(&cwd));
+        }))
         .plugin(tauri_plugin_store::Builder::default().build())
-        .setup(|_app| {
+        .plugin(tauri_plugin_deep_link::init())
+        .manage(deeplink::PendingDeepLinks::default())
+        .manage(watcher::TemplateWatcher::default())
+        .setup(|app| {
             #[cfg(debug_assertions)]
             {
-                let window = _app.get_webview_window("main").unwrap();
+                let window = app.get_webview_window("main").unwrap();
                 window.open_devtools();
             }
+            deeplink::register(app.handle());
+            watcher::restart(app.handle());
+            if let Ok(cwd) = std::env::current_dir() {
+                let args: Vec<String> = std::env::args().collect();
+                instance::handle_args(app.handle(), &args, &cwd);
+            }
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
             generate_project,
             generate_from_template,
             get_templates,
+            get_template_detail,
+            answers::validate_answers,
+            conditions::get_visible_prompts,
+            jinja::resolve_prompt_defaults,
             validate_manifest,
             preview_generation,
             read_manifest,
@@ -1100,8 +1588,39 @@ pub fn run() {
             execute_upg_cli,
             get_setting,
             set_setting,
-            get_all_settings
+            get_all_settings,
+            provenance::read_project_provenance,
+            update::update_project,
+            update::resolve_update_conflicts,
+            diff::diff_previews,
+            diff::diff_preview_against_dir,
+            identify::identify_project,
+            hooks::get_template_hooks,
+            hooks::run_template_hook,
+            actions::get_template_commands,
+            validation::validate_generated_project,
+            history::get_generation_history,
+            seeds::get_seeds,
+            determinism::verify_seed_determinism,
+            bookmarks::list_bookmarks,
+            bookmarks::get_bookmark,
+            bookmarks::create_bookmark,
+            bookmarks::update_bookmark,
+            bookmarks::delete_bookmark,
+            bookmarks::search_bookmarks,
+            bookmarks::list_bookmark_collections,
+            bookmarks::export_bookmarks,
+            bookmarks::import_bookmarks,
+            recipe::export_recipe,
+            recipe::import_recipe,
+            deeplink::take_pending_deep_links,
+            templates::get_template_roots,
+            catalog::get_template_catalogs,
+            library::install_template,
+            library::uninstall_template,
+            library::list_installed_templates,
+            lifecycle::check_breaking_changes
         ])
-        .run(tauri::generate_context!())
+        .run(context)
         .expect("error while running tauri application");
 }
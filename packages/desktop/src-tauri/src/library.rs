@@ -0,0 +1,605 @@
+//! Installed template library
+//!
+//! `install_template` copies a template from a directory, a `.zip` or `.tar.gz` archive,
+//! or a local git repository (a `file://` URL or a path ending in `.git`) into the
+//! user's templates directory as `<name>/<version>/`, so it is discovered through the
+//! `user` root without touching the app bundle. Each install records where it came
+//! from, a hash of its files and when it was installed in `.upg-install.json`; only
+//! directories holding that record are listed or removed here.
+//!
+//! Archives and git repositories must not contain symbolic links: copying one would
+//! install whatever file it points at on this machine.
+
+use crate::files::{project_file, sha256_hex, walk_files};
+use crate::manifest::{load_manifest, resolve_manifest_path};
+use crate::templates::{same_version, user_templates_dir};
+use crate::{validate_manifest, AppPaths};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Install record written into each installed template
+pub const INSTALL_RECORD_FILE: &str = ".upg-install.json";
+
+/// Directories never copied from a source
+const EXCLUDED_DIRS: &[&str] = &[".git", ".hg", ".svn", "node_modules"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InstallSourceKind {
+    Directory,
+    Zip,
+    TarGz,
+    Git,
+}
+
+/// Install manifest of a library template
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InstallRecord {
+    pub name: String,
+    pub version: String,
+    /// Source as given to `install_template`
+    pub source: String,
+    pub source_kind: InstallSourceKind,
+    /// Commit checked out, for git sources
+    #[serde(default)]
+    pub revision: Option<String>,
+    /// SHA-256 over the installed files' paths and contents
+    pub sha256: String,
+    pub installed_at: String,
+    /// Installed template directory
+    pub path: String,
+}
+
+fn library_dir(paths: &AppPaths) -> Result<PathBuf, String> {
+    user_templates_dir(paths).ok_or_else(|| "Failed to resolve app data directory".to_string())
+}
+
+/// Classify a source and resolve it to a local path (git URLs are passed through)
+fn classify_source(paths: &AppPaths, source: &str) -> Result<(InstallSourceKind, String), String> {
+    if source.starts_with("file://") {
+        return Ok((InstallSourceKind::Git, source.to_string()));
+    }
+    if source.contains("://") {
+        return Err(format!(
+            "Unsupported source '{}': only local paths and file:// git repositories can be installed",
+            source
+        ));
+    }
+
+    let path = PathBuf::from(source);
+    let path = if path.is_absolute() {
+        path
+    } else {
+        paths.home_dir()?.join(path)
+    };
+    if !path.exists() {
+        return Err(format!("Source not found: {}", path.display()));
+    }
+
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    let kind = if name.ends_with(".git") {
+        InstallSourceKind::Git
+    } else if path.is_dir() {
+        InstallSourceKind::Directory
+    } else if name.ends_with(".zip") {
+        InstallSourceKind::Zip
+    } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+        InstallSourceKind::TarGz
+    } else {
+        return Err(format!(
+            "Unsupported source '{}': expected a directory, .zip, .tar.gz or git repository",
+            source
+        ));
+    };
+    Ok((kind, path.to_string_lossy().to_string()))
+}
+
+fn extract_zip(archive: &Path, dest: &Path) -> Result<(), String> {
+    let file = fs::File::open(archive).map_err(|e| format!("Failed to open archive: {}", e))?;
+    let mut zip =
+        zip::ZipArchive::new(file).map_err(|e| format!("Failed to read zip archive: {}", e))?;
+    zip.extract(dest)
+        .map_err(|e| format!("Failed to extract zip archive: {}", e))
+}
+
+fn extract_tar_gz(archive: &Path, dest: &Path) -> Result<(), String> {
+    let file = fs::File::open(archive).map_err(|e| format!("Failed to open archive: {}", e))?;
+    // `unpack` refuses entries that would land outside `dest`
+    tar::Archive::new(flate2::read::GzDecoder::new(file))
+        .unpack(dest)
+        .map_err(|e| format!("Failed to extract tar.gz archive: {}", e))
+}
+
+fn git(args: &[&str], cwd: Option<&Path>) -> Result<String, String> {
+    let mut command = Command::new("git");
+    command.args(args);
+    if let Some(cwd) = cwd {
+        command.current_dir(cwd);
+    }
+    let output = command
+        .output()
+        .map_err(|e| format!("Failed to run git (is it installed?): {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "git {} failed: {}",
+            args.first().unwrap_or(&""),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Locate the template in a staged source: the directory itself, or its single
+/// subdirectory (archives usually wrap their contents in one)
+fn find_template_root(staged: &Path) -> Result<PathBuf, String> {
+    if resolve_manifest_path(staged).is_some() {
+        return Ok(staged.to_path_buf());
+    }
+
+    let subdirs: Vec<PathBuf> = fs::read_dir(staged)
+        .map_err(|e| format!("Failed to read {}: {}", staged.display(), e))?
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.is_dir())
+        .collect();
+    match subdirs.as_slice() {
+        [single] if resolve_manifest_path(single).is_some() => Ok(single.clone()),
+        _ => Err("No upg.yaml found at the top level of the source".to_string()),
+    }
+}
+
+/// Hash a template's files (paths and contents) in a stable order
+fn tree_hash(dir: &Path) -> Result<String, String> {
+    let mut listing = String::new();
+    for relative in walk_files(dir, EXCLUDED_DIRS) {
+        if relative == INSTALL_RECORD_FILE {
+            continue;
+        }
+        let content = fs::read(dir.join(&relative))
+            .map_err(|e| format!("Failed to read {}: {}", relative, e))?;
+        listing.push_str(&format!("{}\0{}\n", relative, sha256_hex(&content)));
+    }
+    Ok(sha256_hex(listing.as_bytes()))
+}
+
+/// Copy a template's files
+///
+/// `fs::copy` follows symlinks, so with `allow_links` unset (archive and git sources,
+/// whose links could point anywhere on this machine) a link fails the copy.
+fn copy_template(from: &Path, to: &Path, allow_links: bool) -> Result<(), String> {
+    for relative in walk_files(from, EXCLUDED_DIRS) {
+        let source = from.join(&relative);
+        let is_link = fs::symlink_metadata(&source)
+            .map_err(|e| format!("Failed to read {}: {}", relative, e))?
+            .file_type()
+            .is_symlink();
+        if is_link && !allow_links {
+            return Err(format!(
+                "{} is a symbolic link; links are not installed from archives or git repositories",
+                relative
+            ));
+        }
+
+        let target = to.join(&relative);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+        fs::copy(&source, &target).map_err(|e| format!("Failed to copy {}: {}", relative, e))?;
+    }
+    Ok(())
+}
+
+fn install(paths: &AppPaths, source: &str, replace: bool) -> Result<InstallRecord, String> {
+    let (source_kind, location) = classify_source(paths, source)?;
+    let temp = tempfile::tempdir().map_err(|e| format!("Failed to create temp dir: {}", e))?;
+    let staged = temp.path().join("source");
+
+    let mut revision = None;
+    match source_kind {
+        InstallSourceKind::Directory => {}
+        InstallSourceKind::Zip => extract_zip(Path::new(&location), &staged)?,
+        InstallSourceKind::TarGz => extract_tar_gz(Path::new(&location), &staged)?,
+        InstallSourceKind::Git => {
+            let target = staged.to_string_lossy().to_string();
+            git(&["clone", "--quiet", &location, &target], None)?;
+            revision = git(&["rev-parse", "HEAD"], Some(&staged)).ok();
+        }
+    }
+    let staged = match source_kind {
+        InstallSourceKind::Directory => PathBuf::from(&location),
+        _ => staged,
+    };
+    let template_root = find_template_root(&staged)?;
+
+    let manifest_path = resolve_manifest_path(&template_root)
+        .ok_or_else(|| "No upg.yaml found in the source".to_string())?;
+    let validation = tauri::async_runtime::block_on(validate_manifest(
+        manifest_path.to_string_lossy().to_string(),
+    ))?;
+    if !validation.valid {
+        return Err(format!(
+            "Invalid template manifest: {}",
+            validation.errors.join("; ")
+        ));
+    }
+    let manifest = load_manifest(&template_root)?;
+    let name = manifest.metadata.name;
+    let version = manifest.metadata.version;
+
+    // Name and version become directory names
+    let library = library_dir(paths)?;
+    let name_dir = project_file(&library, &name)
+        .map_err(|_| format!("Template name '{}' is not a valid directory name", name))?;
+    let dest = project_file(&name_dir, &version).map_err(|_| {
+        format!(
+            "Template version '{}' is not a valid directory name",
+            version
+        )
+    })?;
+    // Only installs recorded here are replaced, never templates the user put there
+    if dest.exists() && (!replace || !dest.join(INSTALL_RECORD_FILE).is_file()) {
+        return Err(format!(
+            "{}@{} is already installed at {}",
+            name,
+            version,
+            dest.display()
+        ));
+    }
+
+    // Copy next to the destination first so a failed copy leaves any install intact
+    let partial = name_dir.join(format!(".{}.partial", version));
+    let _ = fs::remove_dir_all(&partial);
+    let allow_links = source_kind == InstallSourceKind::Directory;
+    if let Err(e) = copy_template(&template_root, &partial, allow_links) {
+        let _ = fs::remove_dir_all(&partial);
+        return Err(e);
+    }
+
+    let record = InstallRecord {
+        name,
+        version,
+        source: source.to_string(),
+        source_kind,
+        revision,
+        sha256: tree_hash(&partial)?,
+        installed_at: chrono::Utc::now().to_rfc3339(),
+        path: dest.to_string_lossy().to_string(),
+    };
+    let json = serde_json::to_string_pretty(&record)
+        .map_err(|e| format!("Failed to serialize install record: {}", e))?;
+    fs::write(partial.join(INSTALL_RECORD_FILE), json)
+        .map_err(|e| format!("Failed to write install record: {}", e))?;
+
+    if dest.exists() {
+        fs::remove_dir_all(&dest)
+            .map_err(|e| format!("Failed to remove previous install: {}", e))?;
+    }
+    fs::rename(&partial, &dest).map_err(|e| format!("Failed to install template: {}", e))?;
+    Ok(record)
+}
+
+fn read_record(dir: &Path) -> Option<InstallRecord> {
+    let content = fs::read_to_string(dir.join(INSTALL_RECORD_FILE)).ok()?;
+    match serde_json::from_str::<InstallRecord>(&content) {
+        Ok(record) => Some(InstallRecord {
+            // The library may have moved since the install
+            path: dir.to_string_lossy().to_string(),
+            ..record
+        }),
+        Err(e) => {
+            eprintln!("Ignoring invalid install record in {:?}: {}", dir, e);
+            None
+        }
+    }
+}
+
+fn subdirs(dir: &Path) -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|e| e.path())
+                .filter(|p| p.is_dir())
+                .collect()
+        })
+        .unwrap_or_default();
+    dirs.sort();
+    dirs
+}
+
+/// Installed templates, sorted by name and version
+pub fn installed_templates(paths: &AppPaths) -> Result<Vec<InstallRecord>, String> {
+    let library = library_dir(paths)?;
+    Ok(subdirs(&library)
+        .iter()
+        .flat_map(|name_dir| subdirs(name_dir))
+        .filter_map(|dir| read_record(&dir))
+        .collect())
+}
+
+/// Install a template into the user library
+///
+/// `replace` overwrites an existing install of the same name and version.
+#[tauri::command]
+pub async fn install_template(
+    app: tauri::AppHandle,
+    source: String,
+    replace: Option<bool>,
+) -> Result<InstallRecord, String> {
+    let paths = AppPaths::from_app(&app);
+    tokio::task::spawn_blocking(move || install(&paths, &source, replace.unwrap_or(false)))
+        .await
+        .map_err(|e| format!("Install task failed: {}", e))?
+}
+
+/// Remove an installed template; without `version`, every installed version is removed
+#[tauri::command]
+pub async fn uninstall_template(
+    app: tauri::AppHandle,
+    name: String,
+    version: Option<String>,
+) -> Result<Vec<InstallRecord>, String> {
+    let paths = AppPaths::from_app(&app);
+    uninstall(&paths, &name, version.as_deref())
+}
+
+fn uninstall(
+    paths: &AppPaths,
+    name: &str,
+    version: Option<&str>,
+) -> Result<Vec<InstallRecord>, String> {
+    let removed: Vec<InstallRecord> = installed_templates(paths)?
+        .into_iter()
+        .filter(|r| r.name == name && version.map_or(true, |v| same_version(&r.version, v)))
+        .collect();
+    if removed.is_empty() {
+        return Err(match version {
+            Some(version) => format!("{}@{} is not installed", name, version),
+            None => format!("{} is not installed", name),
+        });
+    }
+
+    for record in &removed {
+        fs::remove_dir_all(&record.path)
+            .map_err(|e| format!("Failed to remove {}: {}", record.path, e))?;
+    }
+    // Drop the name directory once no version is left (fails harmlessly otherwise)
+    let _ = fs::remove_dir(library_dir(paths)?.join(name));
+    Ok(removed)
+}
+
+/// List templates installed into the user library
+#[tauri::command]
+pub async fn list_installed_templates(app: tauri::AppHandle) -> Result<Vec<InstallRecord>, String> {
+    installed_templates(&AppPaths::from_app(&app))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn paths(app_data: &Path) -> AppPaths {
+        AppPaths {
+            resource_dir: None,
+            home_dir: None,
+            app_data_dir: Some(app_data.to_path_buf()),
+        }
+    }
+
+    fn write_record(paths: &AppPaths, name: &str, version: &str) -> PathBuf {
+        let dir = library_dir(paths).unwrap().join(name).join(version);
+        fs::create_dir_all(&dir).unwrap();
+        let record = InstallRecord {
+            name: name.to_string(),
+            version: version.to_string(),
+            source: "/src".to_string(),
+            source_kind: InstallSourceKind::Directory,
+            revision: None,
+            sha256: String::new(),
+            installed_at: String::new(),
+            path: dir.to_string_lossy().to_string(),
+        };
+        fs::write(
+            dir.join(INSTALL_RECORD_FILE),
+            serde_json::to_string(&record).unwrap(),
+        )
+        .unwrap();
+        dir
+    }
+
+    /// A template source with a file that must not be installed
+    fn write_source(dir: &Path, name: &str) {
+        fs::create_dir_all(dir.join("src")).unwrap();
+        fs::create_dir_all(dir.join("node_modules/dep")).unwrap();
+        fs::write(
+            dir.join("upg.yaml"),
+            format!(
+                "apiVersion: upg/v1\nmetadata:\n  name: {}\n  version: 1.0.0\nprompts: []\n",
+                name
+            ),
+        )
+        .unwrap();
+        fs::write(dir.join("src/main.rs"), "fn main() {}\n").unwrap();
+        fs::write(dir.join("node_modules/dep/index.js"), "").unwrap();
+    }
+
+    /// Check the `<name>/<version>/` layout and the install record
+    fn assert_installed(paths: &AppPaths, record: &InstallRecord, kind: InstallSourceKind) {
+        let dest = library_dir(paths).unwrap().join("api").join("1.0.0");
+        assert_eq!(record.source_kind, kind);
+        assert_eq!(record.path, dest.to_string_lossy());
+        assert!(dest.join("upg.yaml").is_file());
+        assert!(dest.join("src/main.rs").is_file());
+        assert!(!dest.join("node_modules").exists());
+        assert!(!dest.join(".git").exists());
+
+        let written = read_record(&dest).unwrap();
+        assert_eq!(written.name, "api");
+        assert_eq!(written.version, "1.0.0");
+        assert_eq!(written.source, record.source);
+        assert_eq!(written.sha256, record.sha256);
+        assert_eq!(record.sha256, tree_hash(&dest).unwrap());
+    }
+
+    #[test]
+    fn installs_directories_and_archives() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("api");
+        write_source(&source, "api");
+
+        let app_data = tempfile::tempdir().unwrap();
+        let from_dir = install(&paths(app_data.path()), &source.to_string_lossy(), false).unwrap();
+        assert_installed(
+            &paths(app_data.path()),
+            &from_dir,
+            InstallSourceKind::Directory,
+        );
+
+        // Archives wrap the template in a top-level directory
+        let zip_path = dir.path().join("api.zip");
+        let mut zip = zip::ZipWriter::new(fs::File::create(&zip_path).unwrap());
+        for relative in ["upg.yaml", "src/main.rs"] {
+            zip.start_file(
+                format!("api-1.0.0/{}", relative),
+                zip::write::FileOptions::default(),
+            )
+            .unwrap();
+            zip.write_all(&fs::read(source.join(relative)).unwrap())
+                .unwrap();
+        }
+        zip.finish().unwrap();
+
+        let tar_path = dir.path().join("api.tar.gz");
+        let gz = flate2::write::GzEncoder::new(
+            fs::File::create(&tar_path).unwrap(),
+            flate2::Compression::default(),
+        );
+        let mut tar = tar::Builder::new(gz);
+        tar.append_path_with_name(source.join("upg.yaml"), "api-1.0.0/upg.yaml")
+            .unwrap();
+        tar.append_path_with_name(source.join("src/main.rs"), "api-1.0.0/src/main.rs")
+            .unwrap();
+        tar.into_inner().unwrap().finish().unwrap();
+
+        for (archive, kind) in [
+            (zip_path, InstallSourceKind::Zip),
+            (tar_path, InstallSourceKind::TarGz),
+        ] {
+            let app_data = tempfile::tempdir().unwrap();
+            let paths = paths(app_data.path());
+            let record = install(&paths, &archive.to_string_lossy(), false).unwrap();
+            assert_installed(&paths, &record, kind);
+            // Same files, same hash, whatever the source
+            assert_eq!(record.sha256, from_dir.sha256);
+            assert!(record.revision.is_none());
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn installs_local_git_repositories() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = dir.path().join("api");
+        write_source(&repo, "api");
+        fs::remove_dir_all(repo.join("node_modules")).unwrap();
+        git(&["init", "--quiet"], Some(&repo)).unwrap();
+        git(&["add", "."], Some(&repo)).unwrap();
+        git(
+            &[
+                "-c",
+                "user.name=UPG",
+                "-c",
+                "user.email=upg@example.com",
+                "commit",
+                "--quiet",
+                "-m",
+                "Template",
+            ],
+            Some(&repo),
+        )
+        .unwrap();
+        let head = git(&["rev-parse", "HEAD"], Some(&repo)).unwrap();
+
+        let app_data = tempfile::tempdir().unwrap();
+        let paths = paths(app_data.path());
+        let source = format!("file://{}", repo.display());
+        let record = install(&paths, &source, false).unwrap();
+
+        assert_installed(&paths, &record, InstallSourceKind::Git);
+        assert_eq!(record.source, source);
+        assert_eq!(record.revision, Some(head));
+    }
+
+    #[test]
+    fn replaces_only_recorded_installs() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("api");
+        write_source(&source, "api");
+        let source = source.to_string_lossy();
+        let app_data = tempfile::tempdir().unwrap();
+        let paths = paths(app_data.path());
+
+        let first = install(&paths, &source, false).unwrap();
+        let err = install(&paths, &source, false).unwrap_err();
+        assert!(err.contains("api@1.0.0 is already installed"));
+
+        fs::write(dir.path().join("api/src/lib.rs"), "").unwrap();
+        let second = install(&paths, &source, true).unwrap();
+        assert_ne!(second.sha256, first.sha256);
+        assert!(Path::new(&second.path).join("src/lib.rs").is_file());
+
+        // A template the user put in the library is never replaced
+        let user = dir.path().join("web");
+        write_source(&user, "web");
+        let placed = library_dir(&paths).unwrap().join("web").join("1.0.0");
+        copy_template(&user, &placed, true).unwrap();
+        let err = install(&paths, &user.to_string_lossy(), true).unwrap_err();
+        assert!(err.contains("web@1.0.0 is already installed"));
+        assert!(!placed.join(INSTALL_RECORD_FILE).exists());
+    }
+
+    #[test]
+    fn uninstall_matches_equivalent_versions() {
+        let app_data = tempfile::tempdir().unwrap();
+        let paths = paths(app_data.path());
+        let one = write_record(&paths, "api", "1.0.0");
+        let two = write_record(&paths, "api", "2.0.0");
+
+        let removed = uninstall(&paths, "api", Some("1")).unwrap();
+
+        assert_eq!(removed.len(), 1);
+        assert!(!one.exists());
+        assert!(two.exists());
+        assert!(uninstall(&paths, "api", Some("1.0")).is_err());
+
+        uninstall(&paths, "api", None).unwrap();
+        assert!(!library_dir(&paths).unwrap().join("api").exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn links_are_only_copied_from_directories() {
+        let dir = tempfile::tempdir().unwrap();
+        let from = dir.path().join("from");
+        fs::create_dir(&from).unwrap();
+        fs::write(from.join("upg.yaml"), "metadata: {name: api}\n").unwrap();
+        fs::write(dir.path().join("secret"), "key").unwrap();
+        std::os::unix::fs::symlink(dir.path().join("secret"), from.join("link")).unwrap();
+
+        let err = copy_template(&from, &dir.path().join("archive"), false).unwrap_err();
+        assert!(err.contains("link is a symbolic link"));
+
+        copy_template(&from, &dir.path().join("directory"), true).unwrap();
+        assert_eq!(
+            fs::read_to_string(dir.path().join("directory/link")).unwrap(),
+            "key"
+        );
+    }
+}
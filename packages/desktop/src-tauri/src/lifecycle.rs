@@ -0,0 +1,374 @@
+//! Template lifecycle and breaking changes
+//!
+//! `experimental` and `deprecated` templates are shown, flagged or hidden according to
+//! the `templateLifecycle` setting. Before a project is regenerated or updated to another
+//! version of its template, the template's `template.breaking_changes` entries between
+//! the version recorded in the project's provenance and the new one are reported, with
+//! their migration notes, and the operation waits for the user to acknowledge them.
+
+use crate::manifest::{load_manifest, BreakingChange, ManifestFile};
+use crate::provenance::{
+    find_provenance, load_provenance, ProjectProvenance, ProvenanceMode, PROVENANCE_FILE,
+};
+use crate::templates::parse_version;
+use crate::TemplateEntry;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::Path;
+
+/// Settings key holding the lifecycle policy
+pub const TEMPLATE_LIFECYCLE_SETTING: &str = "templateLifecycle";
+
+/// How templates in a lifecycle stage are listed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LifecycleVisibility {
+    /// Listed like production templates
+    Show,
+    /// Listed with a `lifecycle_notice`
+    #[default]
+    Flag,
+    /// Not listed
+    Hide,
+}
+
+/// Value of the `templateLifecycle` setting
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LifecyclePolicy {
+    #[serde(default)]
+    pub experimental: LifecycleVisibility,
+    #[serde(default)]
+    pub deprecated: LifecycleVisibility,
+}
+
+/// Parse the `templateLifecycle` setting, falling back to flagging both stages
+pub fn lifecycle_policy(value: Option<Value>) -> LifecyclePolicy {
+    match value {
+        Some(value) if !value.is_null() => serde_json::from_value(value).unwrap_or_else(|e| {
+            eprintln!(
+                "Ignoring invalid {} setting: {}",
+                TEMPLATE_LIFECYCLE_SETTING, e
+            );
+            LifecyclePolicy::default()
+        }),
+        _ => LifecyclePolicy::default(),
+    }
+}
+
+/// Drop or flag experimental and deprecated templates
+pub fn apply_lifecycle_policy(
+    entries: Vec<TemplateEntry>,
+    policy: &LifecyclePolicy,
+) -> Vec<TemplateEntry> {
+    entries
+        .into_iter()
+        .filter_map(|mut entry| {
+            let (visibility, notice) = match entry.lifecycle.as_str() {
+                "experimental" => (
+                    policy.experimental,
+                    "Experimental: this template may change without notice",
+                ),
+                "deprecated" => (
+                    policy.deprecated,
+                    "Deprecated: this template is no longer maintained",
+                ),
+                _ => return Some(entry),
+            };
+            match visibility {
+                LifecycleVisibility::Show => Some(entry),
+                LifecycleVisibility::Flag => {
+                    entry.lifecycle_notice = Some(notice.to_string());
+                    Some(entry)
+                }
+                LifecycleVisibility::Hide => None,
+            }
+        })
+        .collect()
+}
+
+/// Breaking changes introduced after `from` up to and including `to`
+///
+/// Entries whose version cannot be ordered are always included, and so is every entry
+/// when `from` or `to` cannot be parsed: better to show a note too many than miss one.
+pub fn breaking_changes_between(
+    changes: &[BreakingChange],
+    from: &str,
+    to: &str,
+) -> Vec<BreakingChange> {
+    let (Some(from), Some(to)) = (parse_version(from), parse_version(to)) else {
+        return changes.to_vec();
+    };
+    changes
+        .iter()
+        .filter(|change| {
+            parse_version(&change.version).map_or(true, |version| from < version && version <= to)
+        })
+        .cloned()
+        .collect()
+}
+
+/// Breaking changes between a project's recorded template and another version of it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BreakingChangeReport {
+    pub template_name: String,
+    /// Version recorded in the project's provenance
+    pub from_version: String,
+    /// Version the project is about to be regenerated or updated with
+    pub to_version: String,
+    /// Entries of `template.breaking_changes`, in manifest order
+    pub changes: Vec<BreakingChange>,
+}
+
+/// Breaking changes a project would go through by moving to `manifest`
+///
+/// `None` for procedural projects, another template, the same version or a version
+/// range without documented breaking changes.
+pub fn breaking_changes_since(
+    provenance: &ProjectProvenance,
+    manifest: &ManifestFile,
+) -> Option<BreakingChangeReport> {
+    if provenance.mode != ProvenanceMode::Template {
+        return None;
+    }
+    let recorded = provenance.template.as_ref()?;
+    let meta = &manifest.metadata;
+    if recorded.name != meta.name || recorded.version == meta.version {
+        return None;
+    }
+
+    let documented = manifest.template.as_ref()?;
+    let changes = breaking_changes_between(
+        &documented.breaking_changes,
+        &recorded.version,
+        &meta.version,
+    );
+    if changes.is_empty() {
+        return None;
+    }
+    Some(BreakingChangeReport {
+        template_name: meta.name.clone(),
+        from_version: recorded.version.clone(),
+        to_version: meta.version.clone(),
+        changes,
+    })
+}
+
+/// Breaking changes for regenerating `manifest` into `project_dir`, if it holds a
+/// project generated from an earlier version
+pub fn breaking_changes_in_dir(
+    project_dir: &Path,
+    manifest: &ManifestFile,
+) -> Option<BreakingChangeReport> {
+    let provenance = load_provenance(&project_dir.join(PROVENANCE_FILE)).ok()?;
+    breaking_changes_since(&provenance, manifest)
+}
+
+/// Breaking changes a project would go through by moving to another template version
+///
+/// `template_path` defaults to the template path recorded in the project's provenance.
+#[tauri::command]
+pub async fn check_breaking_changes(
+    app: tauri::AppHandle,
+    project_path: String,
+    template_path: Option<String>,
+) -> Result<Option<BreakingChangeReport>, String> {
+    let file = find_provenance(Path::new(&project_path)).ok_or_else(|| {
+        format!(
+            "Not a UPG project: no {} found at or above {}",
+            PROVENANCE_FILE, project_path
+        )
+    })?;
+    let provenance = load_provenance(&file)?;
+
+    let template_path = match template_path {
+        Some(path) => path,
+        None => match &provenance.template {
+            Some(template) => template.path.clone(),
+            None => return Ok(None),
+        },
+    };
+    let manifest = load_manifest(&crate::resolve_template_path(&template_path, &app)?)?;
+    Ok(breaking_changes_since(&provenance, &manifest))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::templates::{DiscoveredTemplate, TemplateRoot, TemplateRootKind};
+    use crate::{template_entries, template_entry};
+
+    fn template(version: &str, lifecycle: &str) -> DiscoveredTemplate {
+        let manifest = serde_yaml::from_str(&format!(
+            "metadata: {{name: api, version: {}, lifecycle: {}}}\n",
+            version, lifecycle
+        ))
+        .unwrap();
+        DiscoveredTemplate {
+            path: format!("/templates/api/{}", version).into(),
+            manifest,
+            root: TemplateRoot {
+                kind: TemplateRootKind::User,
+                path: "/templates".to_string(),
+                priority: 0,
+                max_depth: 2,
+            },
+            shadows: vec![],
+            catalog: None,
+        }
+    }
+
+    fn policy_for(visibility: LifecycleVisibility) -> LifecyclePolicy {
+        LifecyclePolicy {
+            experimental: visibility,
+            deprecated: visibility,
+        }
+    }
+
+    fn change(version: &str) -> BreakingChange {
+        BreakingChange {
+            version: version.to_string(),
+            changes: vec![format!("Changed in {}", version)],
+            migration_notes: None,
+            migration_url: None,
+        }
+    }
+
+    fn versions(changes: &[BreakingChange]) -> Vec<&str> {
+        changes.iter().map(|c| c.version.as_str()).collect()
+    }
+
+    #[test]
+    fn shows_flags_or_hides_by_stage() {
+        let entries = || {
+            ["production", "experimental", "deprecated"]
+                .iter()
+                .map(|lifecycle| template_entry(template("1.0.0", lifecycle)))
+                .collect::<Vec<_>>()
+        };
+        let notices = |entries: &[TemplateEntry]| {
+            entries
+                .iter()
+                .map(|e| (e.lifecycle.clone(), e.lifecycle_notice.is_some()))
+                .collect::<Vec<_>>()
+        };
+
+        let shown = apply_lifecycle_policy(entries(), &policy_for(LifecycleVisibility::Show));
+        assert_eq!(
+            notices(&shown),
+            [
+                ("production".to_string(), false),
+                ("experimental".to_string(), false),
+                ("deprecated".to_string(), false),
+            ]
+        );
+
+        let flagged = apply_lifecycle_policy(entries(), &LifecyclePolicy::default());
+        assert_eq!(
+            notices(&flagged),
+            [
+                ("production".to_string(), false),
+                ("experimental".to_string(), true),
+                ("deprecated".to_string(), true),
+            ]
+        );
+
+        let hidden = apply_lifecycle_policy(entries(), &policy_for(LifecycleVisibility::Hide));
+        assert_eq!(notices(&hidden), [("production".to_string(), false)]);
+    }
+
+    #[test]
+    fn applies_to_each_version() {
+        let templates = || {
+            vec![
+                template("1.0.0", "deprecated"),
+                template("2.0.0", "experimental"),
+                template("1.5.0", "production"),
+            ]
+        };
+
+        // The experimental 2.0.0 is hidden, so 1.5.0 becomes the default
+        let policy = LifecyclePolicy {
+            experimental: LifecycleVisibility::Hide,
+            deprecated: LifecycleVisibility::Show,
+        };
+        let entries = template_entries(templates(), &policy);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].version, "1.5.0");
+        assert!(entries[0].lifecycle_notice.is_none());
+        let listed: Vec<&str> = entries[0]
+            .versions
+            .iter()
+            .map(|v| v.version.as_str())
+            .collect();
+        assert_eq!(listed, ["1.5.0", "1.0.0"]);
+
+        // A name whose every version is hidden is not listed
+        let hide = policy_for(LifecycleVisibility::Hide);
+        let entries = template_entries(vec![template("1.0.0", "deprecated")], &hide);
+        assert!(entries.is_empty());
+
+        let entries = template_entries(templates(), &LifecyclePolicy::default());
+        assert_eq!(entries[0].version, "2.0.0");
+        assert!(entries[0].lifecycle_notice.is_some());
+        assert_eq!(entries[0].versions.len(), 3);
+    }
+
+    #[test]
+    fn includes_versions_after_from_up_to_to() {
+        let changes = [
+            change("1.0.0"),
+            change("1.5.0"),
+            change("2.0.0"),
+            change("3.0.0"),
+        ];
+
+        let between = breaking_changes_between(&changes, "1.0.0", "2.0.0");
+        assert_eq!(versions(&between), ["1.5.0", "2.0.0"]);
+
+        // Lenient versions compare like full ones
+        let between = breaking_changes_between(&changes, "v1", "2");
+        assert_eq!(versions(&between), ["1.5.0", "2.0.0"]);
+
+        assert!(breaking_changes_between(&changes, "3.0.0", "3.1.0").is_empty());
+        // Downgrades go through no breaking changes
+        assert!(breaking_changes_between(&changes, "3.0.0", "1.0.0").is_empty());
+    }
+
+    #[test]
+    fn unordered_versions_are_included() {
+        let changes = [change("1.0.0"), change("next"), change("2.0.0")];
+
+        let between = breaking_changes_between(&changes, "1.0.0", "1.1.0");
+        assert_eq!(versions(&between), ["next"]);
+
+        let between = breaking_changes_between(&changes, "main", "2.0.0");
+        assert_eq!(versions(&between), ["1.0.0", "next", "2.0.0"]);
+    }
+
+    #[test]
+    fn reports_changes_since_the_recorded_version() {
+        let provenance: ProjectProvenance = serde_yaml::from_str(
+            "_version: '1'\n\
+             _template: {name: api, version: 1.0.0, source: /templates/api}\n\
+             _generated: {timestamp: '2026-01-01T00:00:00Z', upgVersion: 0.9.0}\n",
+        )
+        .unwrap();
+        let manifest = |name: &str, version: &str| -> ManifestFile {
+            serde_yaml::from_str(&format!(
+                "metadata: {{name: {}, version: {}}}\n\
+                 template:\n  breaking_changes:\n    - {{version: 2.0.0, changes: [Renamed src]}}\n",
+                name, version
+            ))
+            .unwrap()
+        };
+
+        let report = breaking_changes_since(&provenance, &manifest("api", "2.1.0")).unwrap();
+        assert_eq!(report.from_version, "1.0.0");
+        assert_eq!(report.to_version, "2.1.0");
+        assert_eq!(versions(&report.changes), ["2.0.0"]);
+
+        assert!(breaking_changes_since(&provenance, &manifest("api", "1.5.0")).is_none());
+        assert!(breaking_changes_since(&provenance, &manifest("api", "1.0.0")).is_none());
+        assert!(breaking_changes_since(&provenance, &manifest("web", "2.1.0")).is_none());
+    }
+}
@@ -0,0 +1,505 @@
+//! UPG manifest (upg.yaml) parsing
+//!
+//! Typed, partial view of the manifest used by the backend. Only the sections the
+//! desktop acts on are deserialised; everything else is ignored.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Known manifest filenames, in lookup order (mirrors MANIFEST_FILENAMES in @wcnegentropy/shared)
+pub const MANIFEST_FILENAMES: &[&str] = &["upg.yaml", "upg.yml", ".upg.yaml", ".upg.yml"];
+
+/// YAML manifest metadata (partial parse for template info)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManifestMetadata {
+    pub name: String,
+    #[serde(default = "default_version")]
+    pub version: String,
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub icon: Option<String>,
+    #[serde(default)]
+    pub author: Option<String>,
+    #[serde(default = "default_lifecycle")]
+    pub lifecycle: String,
+}
+
+fn default_version() -> String {
+    "1.0.0".to_string()
+}
+
+fn default_lifecycle() -> String {
+    "production".to_string()
+}
+
+/// Prompt data type
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PromptType {
+    String,
+    Int,
+    Float,
+    Boolean,
+    Select,
+    Multiselect,
+    Secret,
+    /// Not a schema type; kept so the rest of the manifest still loads
+    #[serde(other)]
+    Unknown,
+}
+
+/// Choice of a select/multiselect prompt
+///
+/// Manifests may list plain values, which become choices labelled with their value.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(from = "RawChoice")]
+pub struct PromptChoice {
+    pub label: String,
+    pub value: String,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawChoice {
+    Labeled {
+        label: String,
+        value: serde_json::Value,
+    },
+    Plain(serde_json::Value),
+}
+
+/// Choice values as strings; YAML may read `3.11` or `true` as other scalars
+pub fn scalar_string(value: serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(s) => s,
+        other => other.to_string(),
+    }
+}
+
+impl From<RawChoice> for PromptChoice {
+    fn from(raw: RawChoice) -> Self {
+        match raw {
+            RawChoice::Labeled { label, value } => Self {
+                label,
+                value: scalar_string(value),
+            },
+            RawChoice::Plain(value) => {
+                let value = scalar_string(value);
+                Self {
+                    label: value.clone(),
+                    value,
+                }
+            }
+        }
+    }
+}
+
+/// Prompt definition
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManifestPrompt {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub prompt_type: PromptType,
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub message: String,
+    #[serde(default)]
+    pub help: Option<String>,
+    /// Literal value or Jinja2 expression
+    #[serde(default)]
+    pub default: Option<serde_json::Value>,
+    #[serde(default)]
+    pub required: bool,
+    /// Computed field, not shown in forms
+    #[serde(default)]
+    pub hidden: bool,
+    /// Regex the answer must match
+    #[serde(default)]
+    pub validator: Option<String>,
+    /// Shown instead of the generic message when `validator` fails
+    #[serde(default)]
+    pub error_message: Option<String>,
+    /// Jinja2 condition; the prompt is only asked when it holds
+    #[serde(default)]
+    pub when: Option<String>,
+    #[serde(default)]
+    pub choices: Vec<PromptChoice>,
+}
+
+impl ManifestPrompt {
+    /// Whether answers to this prompt must never be persisted
+    pub fn is_secret(&self) -> bool {
+        self.prompt_type == PromptType::Secret
+    }
+}
+
+/// Conflict resolution strategy for Smart Update
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ConflictResolution {
+    /// Report conflicts and leave the user's file untouched
+    #[default]
+    Manual,
+    /// Take the regenerated template version
+    AutoAcceptTemplate,
+    /// Keep the user's version
+    AutoAcceptUser,
+}
+
+/// `template.smart_update` configuration
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SmartUpdateConfig {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Files never overwritten by an update (glob patterns)
+    #[serde(default)]
+    pub preserve_files: Vec<String>,
+    /// Files always taken from the template (glob patterns)
+    #[serde(default)]
+    pub regenerate_files: Vec<String>,
+    #[serde(default)]
+    pub conflict_resolution: ConflictResolution,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Entry of `template.breaking_changes`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BreakingChange {
+    /// Template version that introduced the changes
+    pub version: String,
+    pub changes: Vec<String>,
+    #[serde(default)]
+    pub migration_notes: Option<String>,
+    #[serde(default)]
+    pub migration_url: Option<String>,
+}
+
+/// `template` section
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct TemplateConfig {
+    /// Files that identify projects generated from this template
+    #[serde(default)]
+    pub markers: Vec<String>,
+    #[serde(default)]
+    pub breaking_changes: Vec<BreakingChange>,
+    #[serde(default)]
+    pub smart_update: Option<SmartUpdateConfig>,
+}
+
+/// Error handling strategy for hooks and command actions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OnError {
+    /// Abort the operation
+    #[default]
+    Fail,
+    /// Continue, reporting a warning
+    Warn,
+    /// Continue silently
+    Ignore,
+}
+
+/// Hook configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HookConfig {
+    /// Script path, relative to the template directory
+    pub script: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub when: Option<String>,
+    #[serde(default)]
+    pub on_error: OnError,
+}
+
+/// `hooks` section
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ManifestHooks {
+    #[serde(default)]
+    pub post_generation: Option<HookConfig>,
+    #[serde(default)]
+    pub pre_migration: Option<HookConfig>,
+}
+
+/// `type: command` action
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommandAction {
+    /// Shell command, run in the generated project directory
+    pub command: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub when: Option<String>,
+    #[serde(default)]
+    pub on_error: OnError,
+}
+
+/// Entry of the `actions` list; only command actions are acted on by the desktop
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ManifestAction {
+    /// Render templates from `src` into `dest`
+    Generate {
+        src: String,
+        dest: String,
+        #[serde(default)]
+        exclude: Vec<String>,
+        #[serde(default)]
+        variables: BTreeMap<String, String>,
+    },
+    /// Copy files verbatim
+    Copy {
+        src: String,
+        dest: String,
+    },
+    /// Skip a path when `when` holds
+    Skip {
+        path: String,
+        #[serde(default)]
+        when: Option<String>,
+    },
+    Command(CommandAction),
+    CreateFile {
+        path: String,
+        content: String,
+    },
+    #[serde(other)]
+    Other,
+}
+
+/// `documentation.faq` entry
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FaqEntry {
+    #[serde(default)]
+    pub question: String,
+    #[serde(default)]
+    pub answer: String,
+}
+
+/// `documentation` section
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ManifestDocumentation {
+    #[serde(default)]
+    pub quickstart: Option<String>,
+    #[serde(default)]
+    pub custom_setup: Option<String>,
+    #[serde(default)]
+    pub customization_guide: Option<String>,
+    #[serde(default)]
+    pub faq: Vec<FaqEntry>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ManifestFile {
+    pub metadata: ManifestMetadata,
+    #[serde(default)]
+    pub template: Option<TemplateConfig>,
+    #[serde(default)]
+    pub prompts: Vec<ManifestPrompt>,
+    #[serde(default)]
+    pub actions: Vec<ManifestAction>,
+    #[serde(default)]
+    pub hooks: Option<ManifestHooks>,
+    #[serde(default)]
+    pub documentation: Option<ManifestDocumentation>,
+}
+
+/// Resolve a template path (manifest file or template directory) to the manifest file
+pub fn resolve_manifest_path(path: &Path) -> Option<PathBuf> {
+    if path.is_file() {
+        return Some(path.to_path_buf());
+    }
+
+    MANIFEST_FILENAMES
+        .iter()
+        .map(|name| path.join(name))
+        .find(|candidate| candidate.is_file())
+}
+
+/// Read and parse the manifest for a template path
+pub fn load_manifest(path: &Path) -> Result<ManifestFile, String> {
+    let manifest_path =
+        resolve_manifest_path(path).ok_or_else(|| format!("No manifest found at {:?}", path))?;
+
+    let content = fs::read_to_string(&manifest_path)
+        .map_err(|e| format!("Failed to read manifest {:?}: {}", manifest_path, e))?;
+
+    serde_yaml::from_str(&content)
+        .map_err(|e| format!("Failed to parse manifest {:?}: {}", manifest_path, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const FIXTURE: &str = r#"
+apiVersion: upg/v1
+metadata:
+  name: api
+  version: 2.1.0
+  title: API service
+  tags: [rust, http]
+prompts:
+  - id: python
+    type: select
+    message: Python version
+    choices: [3.11, "3.12", true]
+  - id: database
+    type: multiselect
+    choices:
+      - {label: PostgreSQL, value: postgres}
+      - {label: Port 5432, value: 5432}
+  - id: color
+    type: colour
+    default: blue
+actions:
+  - {type: generate, src: src, dest: ".", exclude: ["*.bak"]}
+  - {type: copy, src: assets, dest: public}
+  - {type: skip, path: docker, when: "not docker"}
+  - {type: command, command: cargo fmt, on_error: warn}
+  - {type: create_file, path: .env, content: "PORT=8080"}
+  - {type: notify, message: Done}
+hooks:
+  post_generation: {script: hooks/setup.sh, description: Install tools}
+documentation:
+  quickstart: cargo run
+  faq:
+    - {question: Why?, answer: Because}
+template:
+  smart_update:
+    preserve_files: [.env]
+    conflict_resolution: auto-accept-user
+"#;
+
+    fn fixture() -> ManifestFile {
+        serde_yaml::from_str(FIXTURE).unwrap()
+    }
+
+    #[test]
+    fn parses_plain_and_labeled_choices() {
+        let manifest = fixture();
+        let choices = |index: usize| -> Vec<(String, String)> {
+            manifest.prompts[index]
+                .choices
+                .iter()
+                .map(|c| (c.label.clone(), c.value.clone()))
+                .collect()
+        };
+        let pair = |label: &str, value: &str| (label.to_string(), value.to_string());
+
+        assert_eq!(
+            choices(0),
+            [
+                pair("3.11", "3.11"),
+                pair("3.12", "3.12"),
+                pair("true", "true")
+            ]
+        );
+        assert_eq!(
+            choices(1),
+            [pair("PostgreSQL", "postgres"), pair("Port 5432", "5432")]
+        );
+    }
+
+    #[test]
+    fn unknown_prompt_types_still_load() {
+        let manifest = fixture();
+        assert_eq!(manifest.prompts[0].prompt_type, PromptType::Select);
+        assert_eq!(manifest.prompts[1].prompt_type, PromptType::Multiselect);
+        assert_eq!(manifest.prompts[2].prompt_type, PromptType::Unknown);
+        assert_eq!(manifest.prompts[2].default, Some(json!("blue")));
+    }
+
+    #[test]
+    fn parses_every_action_type() {
+        let actions = fixture().actions;
+        assert_eq!(actions.len(), 6);
+
+        assert!(matches!(
+            &actions[0],
+            ManifestAction::Generate { src, dest, exclude, variables }
+                if src == "src" && dest == "." && exclude == &["*.bak"] && variables.is_empty()
+        ));
+        assert!(matches!(
+            &actions[1],
+            ManifestAction::Copy { src, dest } if src == "assets" && dest == "public"
+        ));
+        assert!(matches!(
+            &actions[2],
+            ManifestAction::Skip { path, when }
+                if path == "docker" && when.as_deref() == Some("not docker")
+        ));
+        assert!(matches!(
+            &actions[3],
+            ManifestAction::Command(CommandAction { command, on_error: OnError::Warn, .. })
+                if command == "cargo fmt"
+        ));
+        assert!(matches!(
+            &actions[4],
+            ManifestAction::CreateFile { path, content } if path == ".env" && content == "PORT=8080"
+        ));
+        // Action types the desktop does not act on are kept as `Other`
+        assert!(matches!(actions[5], ManifestAction::Other));
+    }
+
+    #[test]
+    fn a_malformed_action_drops_the_template_from_discovery() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("upg.yaml"),
+            "metadata: {name: api}\nactions:\n  - {type: copy, src: assets}\n",
+        )
+        .unwrap();
+
+        // The whole manifest must parse; discovery does not fall back to metadata alone
+        let err = load_manifest(dir.path()).unwrap_err();
+        assert!(err.contains("missing field `dest`"), "{}", err);
+        assert!(crate::templates::load_template(dir.path()).is_none());
+    }
+
+    #[test]
+    fn template_detail_round_trips() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("upg.yaml"), FIXTURE).unwrap();
+
+        let detail = tauri::async_runtime::block_on(crate::get_template_detail(
+            dir.path().join("upg.yaml").to_string_lossy().to_string(),
+        ))
+        .unwrap();
+        assert_eq!(detail.path, dir.path().to_string_lossy());
+
+        let detail = serde_json::to_value(&detail).unwrap();
+        assert_eq!(detail["metadata"]["name"], "api");
+        assert_eq!(detail["metadata"]["lifecycle"], "production");
+        assert_eq!(detail["prompts"][2]["type"], "unknown");
+        assert_eq!(
+            detail["prompts"][1]["choices"][1],
+            json!({"label": "Port 5432", "value": "5432"})
+        );
+        assert_eq!(detail["actions"][3]["type"], "command");
+        assert_eq!(detail["actions"][3]["on_error"], "warn");
+        assert_eq!(detail["actions"][5]["type"], "other");
+        assert_eq!(
+            detail["hooks"]["post_generation"]["script"],
+            "hooks/setup.sh"
+        );
+        assert_eq!(detail["documentation"]["faq"][0]["answer"], "Because");
+        assert_eq!(
+            detail["smart_update"]["conflict_resolution"],
+            "auto-accept-user"
+        );
+        assert_eq!(detail["smart_update"]["enabled"], true);
+    }
+}
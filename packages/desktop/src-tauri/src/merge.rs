@@ -0,0 +1,192 @@
+//! Line-based three-way merge
+//!
+//! diff3-style merge of a user's file ("current") and a regenerated file ("template")
+//! against the originally generated file ("base"). Changes made on only one side are
+//! applied; overlapping changes that differ become conflicts.
+
+use similar::{capture_diff_slices, Algorithm, DiffTag};
+use std::ops::Range;
+
+const MARKER_CURRENT: &str = "<<<<<<< current";
+const MARKER_BASE: &str = "||||||| base";
+const MARKER_SEPARATOR: &str = "=======";
+const MARKER_TEMPLATE: &str = ">>>>>>> template";
+
+/// Result of a three-way merge
+#[derive(Debug, Clone)]
+pub struct MergeOutcome {
+    /// Merged text; conflicting regions are wrapped in diff3 conflict markers
+    pub merged: String,
+    /// Number of conflicting regions
+    pub conflicts: usize,
+}
+
+/// A changed region of one side relative to base
+struct Hunk {
+    side: usize,
+    base: Range<usize>,
+    other: Range<usize>,
+}
+
+fn split_lines(text: &str) -> Vec<&str> {
+    text.split_inclusive('\n').collect()
+}
+
+fn hunks(side: usize, base: &[&str], other: &[&str]) -> Vec<Hunk> {
+    capture_diff_slices(Algorithm::Myers, base, other)
+        .iter()
+        .map(|op| op.as_tag_tuple())
+        .filter(|(tag, _, _)| *tag != DiffTag::Equal)
+        .map(|(_, base, other)| Hunk { side, base, other })
+        .collect()
+}
+
+fn push_lines(out: &mut String, lines: &[&str]) {
+    for line in lines {
+        out.push_str(line);
+    }
+}
+
+/// Push a conflict marker on its own line, even if the preceding region lacks a trailing newline
+fn push_marker(out: &mut String, marker: &str) {
+    if !out.is_empty() && !out.ends_with('\n') {
+        out.push('\n');
+    }
+    out.push_str(marker);
+    out.push('\n');
+}
+
+/// Three-way merge `current` and `template` against their common `base`
+pub fn merge3(base: &str, current: &str, template: &str) -> MergeOutcome {
+    let base_lines = split_lines(base);
+    let sides = [split_lines(current), split_lines(template)];
+
+    let mut all: Vec<Hunk> = hunks(0, &base_lines, &sides[0]);
+    all.extend(hunks(1, &base_lines, &sides[1]));
+    all.sort_by_key(|h| (h.base.start, h.base.end, h.side));
+
+    let mut merged = String::new();
+    let mut conflicts = 0;
+    let mut base_pos = 0;
+    // Cumulative (side length - base length) of each side's hunks already consumed
+    let mut offsets = [0isize; 2];
+
+    let mut i = 0;
+    while i < all.len() {
+        // Group hunks whose base ranges overlap or touch
+        let lo = all[i].base.start;
+        let mut hi = all[i].base.end;
+        let mut j = i + 1;
+        while j < all.len() && all[j].base.start <= hi {
+            hi = hi.max(all[j].base.end);
+            j += 1;
+        }
+
+        push_lines(&mut merged, &base_lines[base_pos..lo]);
+
+        let mut regions: [Option<Range<usize>>; 2] = [None, None];
+        for (side, region) in regions.iter_mut().enumerate() {
+            let delta: isize = all[i..j]
+                .iter()
+                .filter(|h| h.side == side)
+                .map(|h| h.other.len() as isize - h.base.len() as isize)
+                .sum();
+            if all[i..j].iter().any(|h| h.side == side) {
+                let start = (lo as isize + offsets[side]) as usize;
+                let end = (hi as isize + offsets[side] + delta) as usize;
+                *region = Some(start..end);
+            }
+            offsets[side] += delta;
+        }
+
+        match (&regions[0], &regions[1]) {
+            (Some(current), None) => push_lines(&mut merged, &sides[0][current.clone()]),
+            (None, Some(template)) => push_lines(&mut merged, &sides[1][template.clone()]),
+            (Some(current), Some(template)) => {
+                let ours = &sides[0][current.clone()];
+                let theirs = &sides[1][template.clone()];
+                if ours == theirs {
+                    push_lines(&mut merged, ours);
+                } else {
+                    conflicts += 1;
+                    push_marker(&mut merged, MARKER_CURRENT);
+                    push_lines(&mut merged, ours);
+                    push_marker(&mut merged, MARKER_BASE);
+                    push_lines(&mut merged, &base_lines[lo..hi]);
+                    push_marker(&mut merged, MARKER_SEPARATOR);
+                    push_lines(&mut merged, theirs);
+                    push_marker(&mut merged, MARKER_TEMPLATE);
+                }
+            }
+            (None, None) => unreachable!("every hunk group has at least one side"),
+        }
+
+        base_pos = hi;
+        i = j;
+    }
+
+    push_lines(&mut merged, &base_lines[base_pos..]);
+
+    MergeOutcome { merged, conflicts }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn applies_changes_from_both_sides() {
+        let base = "a\nb\nc\nd\n";
+        let current = "a\nB\nc\nd\n";
+        let template = "a\nb\nc\nD\n";
+
+        let outcome = merge3(base, current, template);
+        assert_eq!(outcome.conflicts, 0);
+        assert_eq!(outcome.merged, "a\nB\nc\nD\n");
+    }
+
+    #[test]
+    fn accepts_identical_changes() {
+        let outcome = merge3("a\nb\n", "a\nx\n", "a\nx\n");
+        assert_eq!(outcome.conflicts, 0);
+        assert_eq!(outcome.merged, "a\nx\n");
+    }
+
+    #[test]
+    fn keeps_insertions_and_deletions() {
+        let base = "a\nb\nc\n";
+        let current = "a\nb\nc\nappended\n";
+        let template = "a\nc\n";
+
+        let outcome = merge3(base, current, template);
+        assert_eq!(outcome.conflicts, 0);
+        assert_eq!(outcome.merged, "a\nc\nappended\n");
+    }
+
+    #[test]
+    fn marks_overlapping_changes() {
+        let outcome = merge3("a\nb\nc\n", "a\nmine\nc\n", "a\ntheirs\nc\n");
+        assert_eq!(outcome.conflicts, 1);
+        assert_eq!(
+            outcome.merged,
+            "a\n<<<<<<< current\nmine\n||||||| base\nb\n=======\ntheirs\n>>>>>>> template\nc\n"
+        );
+    }
+
+    #[test]
+    fn puts_markers_on_their_own_line() {
+        let outcome = merge3("a", "mine", "theirs");
+        assert_eq!(outcome.conflicts, 1);
+        assert_eq!(
+            outcome.merged,
+            "<<<<<<< current\nmine\n||||||| base\na\n=======\ntheirs\n>>>>>>> template\n"
+        );
+    }
+
+    #[test]
+    fn unchanged_sides_return_base() {
+        let outcome = merge3("a\nb\n", "a\nb\n", "a\nb\n");
+        assert_eq!(outcome.conflicts, 0);
+        assert_eq!(outcome.merged, "a\nb\n");
+    }
+}
@@ -0,0 +1,274 @@
+//! Streaming child process execution
+//!
+//! Runs a process with a timeout, feeding optional stdin and handing every stdout and
+//! stderr line to a callback as it arrives (used to forward output as Tauri events).
+//! If the timeout elapses, the process is killed along with everything it started: it
+//! runs in its own process group on Unix and in a job object on Windows.
+
+use serde::{Deserialize, Serialize};
+use std::process::Stdio;
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::process::{Child, Command};
+
+/// Which output stream a line came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputStream {
+    Stdout,
+    Stderr,
+}
+
+/// Outcome of a streamed process run
+#[derive(Debug, Clone)]
+pub struct ProcessOutcome {
+    /// Exit code (`None` if killed by a signal or timed out)
+    pub exit_code: Option<i32>,
+    pub success: bool,
+    pub timed_out: bool,
+    pub stdout: String,
+    pub stderr: String,
+    pub duration_ms: u64,
+}
+
+/// The process tree of a spawned child, killed as a whole on timeout
+#[cfg(unix)]
+struct ProcessTree {
+    pgid: Option<i32>,
+}
+
+#[cfg(unix)]
+impl ProcessTree {
+    /// Start the child as the leader of a new process group
+    fn prepare(command: &mut Command) {
+        command.process_group(0);
+    }
+
+    fn attach(child: &Child) -> Self {
+        Self {
+            pgid: child.id().map(|pid| pid as i32),
+        }
+    }
+
+    fn kill(&self) {
+        if let Some(pgid) = self.pgid {
+            // SAFETY: plain syscall; a negative pid addresses the whole process group
+            unsafe {
+                libc::kill(-pgid, libc::SIGKILL);
+            }
+        }
+    }
+}
+
+#[cfg(windows)]
+struct ProcessTree {
+    /// Job object handle (0 if it could not be created); stored as an integer so the
+    /// run future stays `Send`
+    job: isize,
+}
+
+#[cfg(windows)]
+impl ProcessTree {
+    fn prepare(_command: &mut Command) {}
+
+    fn attach(child: &Child) -> Self {
+        use windows_sys::Win32::System::JobObjects::{AssignProcessToJobObject, CreateJobObjectW};
+
+        let Some(process) = child.raw_handle() else {
+            return Self { job: 0 };
+        };
+        // SAFETY: the job handle is owned by this value and closed on drop; `process` is
+        // valid while `child` is alive
+        unsafe {
+            let job = CreateJobObjectW(std::ptr::null(), std::ptr::null());
+            if job.is_null() {
+                return Self { job: 0 };
+            }
+            // Processes the child starts from now on join the job with it
+            AssignProcessToJobObject(job, process as _);
+            Self { job: job as isize }
+        }
+    }
+
+    fn kill(&self) {
+        use windows_sys::Win32::System::JobObjects::TerminateJobObject;
+
+        if self.job != 0 {
+            // SAFETY: `job` is a live job object handle owned by this value
+            unsafe {
+                TerminateJobObject(self.job as _, 1);
+            }
+        }
+    }
+}
+
+#[cfg(windows)]
+impl Drop for ProcessTree {
+    fn drop(&mut self) {
+        if self.job != 0 {
+            // SAFETY: closes the handle created in `attach` exactly once
+            unsafe {
+                windows_sys::Win32::Foundation::CloseHandle(self.job as _);
+            }
+        }
+    }
+}
+
+/// Run a command line through the platform shell, as the CLI does
+pub fn shell_command(command: &str) -> Command {
+    if cfg!(windows) {
+        let mut cmd = Command::new("cmd");
+        cmd.arg("/C").arg(command);
+        cmd
+    } else {
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c").arg(command);
+        cmd
+    }
+}
+
+/// Run `command` to completion, streaming output lines to `on_line`
+///
+/// Returns an error only if the process could not be started.
+pub async fn run_streaming<F>(
+    mut command: Command,
+    stdin_data: Option<Vec<u8>>,
+    timeout: Duration,
+    on_line: F,
+) -> Result<ProcessOutcome, String>
+where
+    F: Fn(OutputStream, &str),
+{
+    let start = Instant::now();
+
+    command
+        .stdin(if stdin_data.is_some() {
+            Stdio::piped()
+        } else {
+            Stdio::null()
+        })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .env("NO_COLOR", "1")
+        .kill_on_drop(true);
+    ProcessTree::prepare(&mut command);
+
+    let mut child = command.spawn().map_err(|e| {
+        format!(
+            "Failed to start {:?}: {}",
+            command.as_std().get_program(),
+            e
+        )
+    })?;
+    let tree = ProcessTree::attach(&child);
+
+    if let (Some(data), Some(mut stdin)) = (stdin_data, child.stdin.take()) {
+        // Write from a separate task so a process that never reads stdin cannot block us;
+        // dropping the handle afterwards closes the pipe.
+        tokio::spawn(async move {
+            let _ = stdin.write_all(&data).await;
+        });
+    }
+
+    let mut out_lines = child.stdout.take().map(|s| BufReader::new(s).lines());
+    let mut err_lines = child.stderr.take().map(|s| BufReader::new(s).lines());
+    let mut stdout = String::new();
+    let mut stderr = String::new();
+
+    let run = async {
+        while out_lines.is_some() || err_lines.is_some() {
+            tokio::select! {
+                line = async { out_lines.as_mut().unwrap().next_line().await }, if out_lines.is_some() => {
+                    match line {
+                        Ok(Some(line)) => {
+                            on_line(OutputStream::Stdout, &line);
+                            stdout.push_str(&line);
+                            stdout.push('\n');
+                        }
+                        _ => out_lines = None,
+                    }
+                }
+                line = async { err_lines.as_mut().unwrap().next_line().await }, if err_lines.is_some() => {
+                    match line {
+                        Ok(Some(line)) => {
+                            on_line(OutputStream::Stderr, &line);
+                            stderr.push_str(&line);
+                            stderr.push('\n');
+                        }
+                        _ => err_lines = None,
+                    }
+                }
+            }
+        }
+        child.wait().await
+    };
+
+    let (exit_code, success, timed_out) = match tokio::time::timeout(timeout, run).await {
+        Ok(Ok(status)) => (status.code(), status.success(), false),
+        Ok(Err(e)) => return Err(format!("Failed to wait for process: {}", e)),
+        Err(_) => {
+            tree.kill();
+            let _ = child.kill().await;
+            (None, false, true)
+        }
+    };
+
+    Ok(ProcessOutcome {
+        exit_code,
+        success,
+        timed_out,
+        stdout,
+        stderr,
+        duration_ms: start.elapsed().as_millis() as u64,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn streams_output_lines() {
+        let lines = std::sync::Mutex::new(Vec::new());
+        let outcome = run_streaming(
+            shell_command("echo one && echo two >&2"),
+            None,
+            Duration::from_secs(10),
+            |stream, line| lines.lock().unwrap().push((stream, line.to_string())),
+        )
+        .await
+        .unwrap();
+
+        assert!(outcome.success);
+        assert_eq!(outcome.stdout, "one\n");
+        assert_eq!(outcome.stderr, "two\n");
+        assert_eq!(lines.lock().unwrap().len(), 2);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn timeout_kills_grandchildren() {
+        let outcome = run_streaming(
+            shell_command("sleep 30 & echo $!; wait"),
+            None,
+            Duration::from_millis(500),
+            |_, _| {},
+        )
+        .await
+        .unwrap();
+        assert!(outcome.timed_out);
+
+        let pid = outcome.stdout.trim().to_string();
+        let gone = || {
+            std::fs::read_to_string(format!("/proc/{}/stat", pid))
+                .map_or(true, |stat| stat.contains(") Z "))
+        };
+        for _ in 0..50 {
+            if gone() {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        panic!("background process {} survived the timeout", pid);
+    }
+}
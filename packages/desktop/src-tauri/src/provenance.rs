@@ -0,0 +1,469 @@
+//! Project provenance (.upg-answers.yaml)
+//!
+//! Every successful generation writes a provenance file into its output directory
+//! recording how the project was made: seed, constraints, enrichment, template and
+//! answers. Secret answers are never written. The file is what later lets the desktop
+//! recognise, update or reproduce a project.
+//!
+//! The file follows `UpgAnswers` in packages/shared/src/types/answers.ts: `_version`,
+//! `_template`, `_generated` and the answers as top-level keys. Seed generations record
+//! `_procedural` instead of `_template`; other `_` keys are preserved on rewrite.
+
+use crate::manifest::load_manifest;
+use crate::{EnrichmentConfig, GenerationMode, GenerationRequest, TechStackConfig};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Provenance filename (also the conventional `template.markers` entry)
+pub const PROVENANCE_FILE: &str = ".upg-answers.yaml";
+
+/// Current answers file schema version (`_version`)
+pub const PROVENANCE_VERSION: &str = "1";
+
+/// How a project was produced
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProvenanceMode {
+    /// Seed → stack → files via the procedural engine
+    Procedural,
+    /// UPG manifest template
+    Template,
+}
+
+/// Template identity recorded for template-based generations
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TemplateProvenance {
+    pub name: String,
+    pub version: String,
+    /// Template path as given at generation time (`_template.source` in the file)
+    pub path: String,
+}
+
+/// Contents of `.upg-answers.yaml`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(try_from = "AnswersFile", into = "AnswersFile")]
+pub struct ProjectProvenance {
+    pub provenance_version: String,
+    pub mode: ProvenanceMode,
+    #[serde(default)]
+    pub seed: Option<u64>,
+    /// Stack constraints as requested (not the resolved stack)
+    #[serde(default)]
+    pub stack: Option<TechStackConfig>,
+    #[serde(default)]
+    pub enrichment: Option<EnrichmentConfig>,
+    #[serde(default)]
+    pub template: Option<TemplateProvenance>,
+    /// Prompt answers, secrets excluded
+    #[serde(default)]
+    pub answers: BTreeMap<String, Value>,
+    /// Version reported by the upg CLI that generated the project
+    pub engine_version: String,
+    /// Desktop application version
+    pub app_version: String,
+    pub generated_at: String,
+    /// Other `_` keys found in the file (such as `_updateHistory`), kept as they were
+    pub extra: BTreeMap<String, Value>,
+}
+
+/// `UpgAnswers` (packages/shared/src/types/answers.ts) as laid out on disk
+#[derive(Serialize, Deserialize)]
+struct AnswersFile {
+    #[serde(rename = "_version")]
+    version: String,
+    #[serde(rename = "_template", default, skip_serializing_if = "Option::is_none")]
+    template: Option<TemplateInfo>,
+    #[serde(rename = "_generated")]
+    generated: GenerationInfo,
+    #[serde(
+        rename = "_procedural",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    procedural: Option<ProceduralInfo>,
+    #[serde(
+        rename = "_enrichment",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    enrichment: Option<EnrichmentConfig>,
+    /// Answers keyed by prompt id, plus any other `_` keys
+    #[serde(flatten)]
+    rest: BTreeMap<String, Value>,
+}
+
+/// `TemplateInfo`
+#[derive(Serialize, Deserialize)]
+struct TemplateInfo {
+    name: String,
+    version: String,
+    source: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    commit: Option<String>,
+}
+
+/// `GenerationMetadata`
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GenerationInfo {
+    timestamp: String,
+    /// Version reported by the upg CLI
+    upg_version: String,
+    #[serde(default)]
+    desktop_version: String,
+}
+
+/// `ProceduralInfo`
+#[derive(Serialize, Deserialize)]
+struct ProceduralInfo {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    seed: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    stack: Option<TechStackConfig>,
+}
+
+impl TryFrom<AnswersFile> for ProjectProvenance {
+    type Error = String;
+
+    fn try_from(file: AnswersFile) -> Result<Self, String> {
+        let mode = match (&file.template, &file.procedural) {
+            (Some(_), _) => ProvenanceMode::Template,
+            (None, Some(_)) => ProvenanceMode::Procedural,
+            (None, None) => return Err("expected _template or _procedural".to_string()),
+        };
+        let (extra, answers) = file
+            .rest
+            .into_iter()
+            .partition(|(key, _)| key.starts_with('_'));
+
+        Ok(Self {
+            provenance_version: file.version,
+            mode,
+            seed: file.procedural.as_ref().and_then(|p| p.seed),
+            stack: file.procedural.and_then(|p| p.stack),
+            enrichment: file.enrichment,
+            template: file.template.map(|t| TemplateProvenance {
+                name: t.name,
+                version: t.version,
+                path: t.source,
+            }),
+            answers,
+            engine_version: file.generated.upg_version,
+            app_version: file.generated.desktop_version,
+            generated_at: file.generated.timestamp,
+            extra,
+        })
+    }
+}
+
+impl From<ProjectProvenance> for AnswersFile {
+    fn from(provenance: ProjectProvenance) -> Self {
+        let procedural = match provenance.mode {
+            ProvenanceMode::Procedural => Some(ProceduralInfo {
+                seed: provenance.seed,
+                stack: provenance.stack,
+            }),
+            ProvenanceMode::Template => None,
+        };
+        let mut rest = provenance.extra;
+        rest.extend(provenance.answers);
+
+        Self {
+            version: provenance.provenance_version,
+            template: provenance.template.map(|t| TemplateInfo {
+                name: t.name,
+                version: t.version,
+                source: t.path,
+                commit: None,
+            }),
+            generated: GenerationInfo {
+                timestamp: provenance.generated_at,
+                upg_version: provenance.engine_version,
+                desktop_version: provenance.app_version,
+            },
+            procedural,
+            enrichment: provenance.enrichment,
+            rest,
+        }
+    }
+}
+
+/// Arguments for `generate_from_template` that reproduce a template project
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TemplateRequest {
+    /// `name@version` of the recorded template
+    pub template: String,
+    /// Template path recorded at generation time
+    pub template_path: String,
+    pub output_path: String,
+    /// Recorded answers as a JSON object (the `data` argument)
+    pub data: String,
+    pub enrichment: Option<EnrichmentConfig>,
+}
+
+impl ProjectProvenance {
+    /// Provenance for a procedural (seed) generation
+    pub fn procedural(request: &GenerationRequest, engine_version: String) -> Self {
+        Self {
+            provenance_version: PROVENANCE_VERSION.to_string(),
+            mode: ProvenanceMode::Procedural,
+            seed: request.seed,
+            stack: request.stack.clone(),
+            enrichment: request.enrichment.clone(),
+            template: None,
+            answers: BTreeMap::new(),
+            engine_version,
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+            generated_at: chrono::Utc::now().to_rfc3339(),
+            extra: BTreeMap::new(),
+        }
+    }
+
+    /// Provenance for a manifest template generation
+    ///
+    /// Answers to `secret` prompts are dropped before anything is recorded.
+    pub fn template(
+        template_path: &str,
+        data: Option<&str>,
+        enrichment: Option<EnrichmentConfig>,
+        engine_version: String,
+    ) -> Result<Self, String> {
+        let manifest = load_manifest(Path::new(template_path))?;
+
+        let mut answers: BTreeMap<String, Value> = match data {
+            Some(json) => {
+                serde_json::from_str(json).map_err(|e| format!("Invalid answers JSON: {}", e))?
+            }
+            None => BTreeMap::new(),
+        };
+        for prompt in manifest.prompts.iter().filter(|p| p.is_secret()) {
+            answers.remove(&prompt.id);
+        }
+
+        Ok(Self {
+            provenance_version: PROVENANCE_VERSION.to_string(),
+            mode: ProvenanceMode::Template,
+            seed: None,
+            stack: None,
+            enrichment,
+            template: Some(TemplateProvenance {
+                name: manifest.metadata.name,
+                version: manifest.metadata.version,
+                path: template_path.to_string(),
+            }),
+            answers,
+            engine_version,
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+            generated_at: chrono::Utc::now().to_rfc3339(),
+            extra: BTreeMap::new(),
+        })
+    }
+
+    /// Rebuild the generation request that produced this project (procedural mode only)
+    pub fn to_generation_request(&self, output_path: &str) -> Option<GenerationRequest> {
+        match self.mode {
+            ProvenanceMode::Procedural => Some(GenerationRequest {
+                mode: GenerationMode::Procedural,
+                seed: self.seed,
+                stack: self.stack.clone(),
+                output_path: output_path.to_string(),
+                enrichment: self.enrichment.clone(),
+            }),
+            ProvenanceMode::Template => None,
+        }
+    }
+
+    /// Rebuild the `generate_from_template` arguments that produced this project
+    /// (template mode only)
+    pub fn to_template_request(&self, output_path: &str) -> Option<TemplateRequest> {
+        let template = self.template.as_ref()?;
+        Some(TemplateRequest {
+            template: format!("{}@{}", template.name, template.version),
+            template_path: template.path.clone(),
+            output_path: output_path.to_string(),
+            data: serde_json::to_string(&self.answers).ok()?,
+            enrichment: self.enrichment.clone(),
+        })
+    }
+}
+
+/// Write `.upg-answers.yaml` into a project directory
+pub fn write_provenance(project_dir: &Path, provenance: &ProjectProvenance) -> Result<(), String> {
+    let yaml = serde_yaml::to_string(provenance)
+        .map_err(|e| format!("Failed to serialize provenance: {}", e))?;
+    let content = format!(
+        "# Generated by the Universal Project Generator. Used for updates; do not edit.\n{}",
+        yaml
+    );
+
+    fs::write(project_dir.join(PROVENANCE_FILE), content)
+        .map_err(|e| format!("Failed to write {}: {}", PROVENANCE_FILE, e))
+}
+
+/// Find the provenance file for a path, searching the path itself and then its ancestors
+pub fn find_provenance(path: &Path) -> Option<PathBuf> {
+    if path.is_file() {
+        return (path.file_name().and_then(|n| n.to_str()) == Some(PROVENANCE_FILE))
+            .then(|| path.to_path_buf());
+    }
+
+    path.ancestors()
+        .map(|dir| dir.join(PROVENANCE_FILE))
+        .find(|candidate| candidate.is_file())
+}
+
+/// Parse a provenance file
+pub fn load_provenance(file: &Path) -> Result<ProjectProvenance, String> {
+    let content =
+        fs::read_to_string(file).map_err(|e| format!("Failed to read {:?}: {}", file, e))?;
+
+    serde_yaml::from_str(&content).map_err(|e| format!("Corrupted {:?}: {}", file, e))
+}
+
+/// Provenance lookup result
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProvenanceReport {
+    /// Directory containing the provenance file
+    pub project_root: String,
+    pub provenance_path: String,
+    pub provenance: ProjectProvenance,
+    /// The original request, reconstructed for procedural projects
+    pub request: Option<GenerationRequest>,
+    /// The original template generation, reconstructed for template projects
+    pub template_request: Option<TemplateRequest>,
+}
+
+/// Read `.upg-answers.yaml` for a project and reconstruct the original request
+///
+/// `path` may be the project directory, any directory inside it, or the provenance file.
+#[tauri::command]
+pub async fn read_project_provenance(path: String) -> Result<ProvenanceReport, String> {
+    let file = find_provenance(Path::new(&path)).ok_or_else(|| {
+        format!(
+            "Not a UPG project: no {} found at or above {}",
+            PROVENANCE_FILE, path
+        )
+    })?;
+
+    let provenance = load_provenance(&file)?;
+    let project_root = file
+        .parent()
+        .map(|p| p.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let request = provenance.to_generation_request(&project_root);
+    let template_request = provenance.to_template_request(&project_root);
+
+    Ok(ProvenanceReport {
+        project_root,
+        provenance_path: file.to_string_lossy().to_string(),
+        provenance,
+        request,
+        template_request,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn template_provenance() -> ProjectProvenance {
+        ProjectProvenance {
+            provenance_version: PROVENANCE_VERSION.to_string(),
+            mode: ProvenanceMode::Template,
+            seed: None,
+            stack: None,
+            enrichment: None,
+            template: Some(TemplateProvenance {
+                name: "api".to_string(),
+                version: "1.2.0".to_string(),
+                path: "/templates/api".to_string(),
+            }),
+            answers: BTreeMap::from([
+                ("project_name".to_string(), Value::from("demo")),
+                ("port".to_string(), Value::from(8080)),
+            ]),
+            engine_version: "0.9.0".to_string(),
+            app_version: "0.1.0".to_string(),
+            generated_at: "2026-01-01T00:00:00+00:00".to_string(),
+            extra: BTreeMap::new(),
+        }
+    }
+
+    #[test]
+    fn writes_the_shared_answers_shape() {
+        let yaml = serde_yaml::to_string(&template_provenance()).unwrap();
+        let doc: Value = serde_yaml::from_str(&yaml).unwrap();
+
+        assert_eq!(doc["_version"], "1");
+        assert_eq!(doc["_template"]["name"], "api");
+        assert_eq!(doc["_template"]["version"], "1.2.0");
+        assert_eq!(doc["_template"]["source"], "/templates/api");
+        assert_eq!(doc["_generated"]["timestamp"], "2026-01-01T00:00:00+00:00");
+        assert_eq!(doc["_generated"]["upgVersion"], "0.9.0");
+        assert_eq!(doc["project_name"], "demo");
+        assert_eq!(doc["port"], 8080);
+        assert!(doc.get("_procedural").is_none());
+    }
+
+    #[test]
+    fn reads_answers_written_by_other_tools() {
+        let yaml = "_version: '1'\n\
+            _template: {name: api, version: 1.0.0, source: 'git@example.com:api.git', commit: abc}\n\
+            _generated: {timestamp: '2026-01-01T00:00:00Z', upgVersion: 0.8.0}\n\
+            _updateHistory: []\n\
+            project_name: demo\n";
+        let provenance: ProjectProvenance = serde_yaml::from_str(yaml).unwrap();
+
+        assert_eq!(provenance.mode, ProvenanceMode::Template);
+        assert_eq!(provenance.template.as_ref().unwrap().version, "1.0.0");
+        assert_eq!(provenance.engine_version, "0.8.0");
+        assert_eq!(provenance.app_version, "");
+        assert_eq!(provenance.answers.len(), 1);
+        assert!(provenance.extra.contains_key("_updateHistory"));
+
+        let rewritten: Value =
+            serde_yaml::from_str(&serde_yaml::to_string(&provenance).unwrap()).unwrap();
+        assert!(rewritten.get("_updateHistory").is_some());
+    }
+
+    #[test]
+    fn round_trips_procedural_projects() {
+        let provenance = ProjectProvenance {
+            mode: ProvenanceMode::Procedural,
+            seed: Some(42),
+            template: None,
+            answers: BTreeMap::new(),
+            ..template_provenance()
+        };
+        let yaml = serde_yaml::to_string(&provenance).unwrap();
+        let read: ProjectProvenance = serde_yaml::from_str(&yaml).unwrap();
+
+        assert_eq!(read.mode, ProvenanceMode::Procedural);
+        assert_eq!(read.seed, Some(42));
+        assert!(read.to_generation_request("/out").is_some());
+        assert!(read.to_template_request("/out").is_none());
+    }
+
+    #[test]
+    fn rejects_files_without_a_template_or_seed() {
+        let yaml = "_version: '1'\n_generated: {timestamp: now, upgVersion: 0.8.0}\n";
+        assert!(serde_yaml::from_str::<ProjectProvenance>(yaml).is_err());
+    }
+
+    #[test]
+    fn rebuilds_template_requests() {
+        let request = template_provenance().to_template_request("/out").unwrap();
+
+        assert_eq!(request.template, "api@1.2.0");
+        assert_eq!(request.template_path, "/templates/api");
+        assert_eq!(request.output_path, "/out");
+        let data: Value = serde_json::from_str(&request.data).unwrap();
+        assert_eq!(data["project_name"], "demo");
+        assert!(template_provenance()
+            .to_generation_request("/out")
+            .is_none());
+    }
+}
@@ -0,0 +1,495 @@
+//! Generation recipes
+//!
+//! A recipe is a portable YAML or JSON document capturing everything needed to
+//! reproduce a generation: mode, seed, stack constraints, enrichment, template and
+//! answers. Unlike `.upg-answers.yaml` it carries no output path, so it can be checked
+//! into a repository and replayed on any machine.
+//!
+//! Older documents are migrated on import. Files without `recipe_version` are treated
+//! as version 0: a provenance file or a bare `GenerationRequest`.
+
+use crate::manifest::load_manifest;
+use crate::provenance::{ProjectProvenance, ProvenanceMode};
+use crate::templates::{find_template, same_version};
+use crate::{
+    discover_templates, get_engine_version, resolve_template_path, EnrichmentConfig,
+    GenerationMode, GenerationRequest, TechStackConfig,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Current recipe schema version
+pub const RECIPE_VERSION: u32 = 1;
+
+const ENRICHMENT_DEPTHS: &[&str] = &["minimal", "standard", "full"];
+
+/// Template reference in a recipe
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RecipeTemplate {
+    /// Template path; relative paths resolve against the recipe file's directory first
+    pub path: String,
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Template version the recipe was written against
+    #[serde(default)]
+    pub version: Option<String>,
+}
+
+/// Recipe document (current schema)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Recipe {
+    pub recipe_version: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub mode: ProvenanceMode,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stack: Option<TechStackConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enrichment: Option<EnrichmentConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template: Option<RecipeTemplate>,
+    /// Prompt answers (template mode), secrets excluded
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub answers: BTreeMap<String, Value>,
+    /// Engine version that exported the recipe
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub engine_version: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<String>,
+}
+
+impl Recipe {
+    fn from_provenance(provenance: ProjectProvenance) -> Self {
+        Self {
+            recipe_version: RECIPE_VERSION,
+            name: None,
+            description: None,
+            mode: provenance.mode,
+            seed: provenance.seed,
+            stack: provenance.stack,
+            enrichment: provenance.enrichment,
+            template: provenance.template.map(|t| RecipeTemplate {
+                path: t.path,
+                name: Some(t.name),
+                version: Some(t.version),
+            }),
+            answers: provenance.answers,
+            engine_version: Some(provenance.engine_version),
+            created_at: Some(provenance.generated_at),
+        }
+    }
+
+    /// Schema checks serde cannot express; returns one message per problem
+    fn validate(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+
+        match self.mode {
+            ProvenanceMode::Procedural => {
+                if self.seed.is_none() {
+                    errors.push("seed: required in procedural mode".to_string());
+                }
+                if self.template.is_some() {
+                    errors.push("template: not allowed in procedural mode".to_string());
+                }
+                if !self.answers.is_empty() {
+                    errors.push("answers: not allowed in procedural mode".to_string());
+                }
+            }
+            ProvenanceMode::Template => {
+                match &self.template {
+                    None => errors.push("template: required in template mode".to_string()),
+                    Some(t) if t.path.trim().is_empty() => {
+                        errors.push("template.path: cannot be empty".to_string())
+                    }
+                    Some(_) => {}
+                }
+                if self.seed.is_some() {
+                    errors.push("seed: not allowed in template mode".to_string());
+                }
+                if self.stack.is_some() {
+                    errors.push("stack: not allowed in template mode".to_string());
+                }
+            }
+        }
+
+        if let Some(enrichment) = &self.enrichment {
+            if !ENRICHMENT_DEPTHS.contains(&enrichment.depth.as_str()) {
+                errors.push(format!(
+                    "enrichment.depth: '{}' is not one of {}",
+                    enrichment.depth,
+                    ENRICHMENT_DEPTHS.join(", ")
+                ));
+            }
+        }
+
+        errors
+    }
+}
+
+/// Recipe export request from frontend
+///
+/// Exactly one of `request` (procedural) or `template_path` (template) must be given.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecipeExportRequest {
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Procedural generation; its output path is not recorded
+    #[serde(default)]
+    pub request: Option<GenerationRequest>,
+    #[serde(default)]
+    pub template_path: Option<String>,
+    /// Template answers as a JSON object string
+    #[serde(default)]
+    pub data: Option<String>,
+    /// Enrichment for template mode
+    #[serde(default)]
+    pub enrichment: Option<EnrichmentConfig>,
+}
+
+/// Result of `import_recipe`, ready to hand to the generation commands
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportedRecipe {
+    pub recipe: Recipe,
+    /// Schema version of the file, when it had to be migrated
+    pub migrated_from: Option<u32>,
+    /// Procedural request with an empty `output_path` for the caller to fill in
+    pub request: Option<GenerationRequest>,
+    /// Resolved template path (template mode)
+    pub template_path: Option<String>,
+    /// Answers as a JSON object string, as `generate_from_template` expects
+    pub data: Option<String>,
+    /// Reproducibility concerns, e.g. a different engine or template version
+    pub warnings: Vec<String>,
+}
+
+/// Whether a recipe file should be written as JSON (otherwise YAML)
+fn is_json(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| e.eq_ignore_ascii_case("json"))
+}
+
+/// Upgrade a recipe document to the current schema, one version at a time
+///
+/// Returns the migrated document and the version it started at.
+fn migrate(mut doc: Value) -> Result<(Value, u32), String> {
+    let original = match doc.get("recipe_version") {
+        None => 0,
+        Some(v) => v
+            .as_u64()
+            .map(|v| v as u32)
+            .ok_or_else(|| format!("recipe_version: expected an integer, got {}", v))?,
+    };
+    if original > RECIPE_VERSION {
+        return Err(format!(
+            "Recipe version {} is newer than supported version {}; update the desktop app",
+            original, RECIPE_VERSION
+        ));
+    }
+
+    let mut version = original;
+    while version < RECIPE_VERSION {
+        doc = match version {
+            0 => migrate_v0(doc)?,
+            _ => unreachable!("no migration from recipe version {}", version),
+        };
+        version += 1;
+    }
+    Ok((doc, original))
+}
+
+/// v0 → v1: a provenance file or a bare `GenerationRequest`
+fn migrate_v0(doc: Value) -> Result<Value, String> {
+    if doc.get("_generated").is_some() {
+        let provenance: ProjectProvenance = serde_json::from_value(doc)
+            .map_err(|e| format!("Invalid provenance document: {}", e))?;
+        return serde_json::to_value(Recipe::from_provenance(provenance))
+            .map_err(|e| e.to_string());
+    }
+
+    let Value::Object(mut fields) = doc else {
+        return Err("Expected a recipe document (a mapping)".to_string());
+    };
+    // Machine-specific; recipes are replayed into a destination chosen on import
+    fields.remove("output_path");
+    fields.insert("recipe_version".to_string(), Value::from(1));
+    Ok(Value::Object(fields))
+}
+
+/// Parse, migrate and validate recipe file contents
+fn parse_recipe(content: &str) -> Result<(Recipe, u32), String> {
+    // YAML is a superset of JSON, so one parser handles both formats
+    let doc: Value =
+        serde_yaml::from_str(content).map_err(|e| format!("Not valid YAML/JSON: {}", e))?;
+    let (doc, original) = migrate(doc)?;
+    let recipe: Recipe = serde_json::from_value(doc).map_err(|e| e.to_string())?;
+
+    let errors = recipe.validate();
+    if !errors.is_empty() {
+        return Err(errors.join("; "));
+    }
+    Ok((recipe, original))
+}
+
+/// Template path as stored in a recipe: relative to the recipe's directory when inside it
+fn portable_template_path(template: &Path, recipe_file: &Path) -> String {
+    recipe_file
+        .parent()
+        .and_then(|dir| template.strip_prefix(dir).ok())
+        .map(|rel| {
+            rel.components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/")
+        })
+        .filter(|rel| !rel.is_empty())
+        .unwrap_or_else(|| template.to_string_lossy().to_string())
+}
+
+/// Resolve a recipe's template path: recipe directory first, then the usual resolution
+fn resolve_recipe_template(
+    app: &tauri::AppHandle,
+    template_path: &str,
+    recipe_file: &Path,
+) -> Result<PathBuf, String> {
+    if Path::new(template_path).is_relative() {
+        if let Some(dir) = recipe_file.parent() {
+            let candidate = dir.join(template_path);
+            if candidate.exists() {
+                return Ok(candidate);
+            }
+        }
+    }
+    resolve_template_path(template_path, app)
+}
+
+/// Template a recipe replays with
+///
+/// The recorded path is kept while it still holds the recorded `name@version`; otherwise
+/// that exact version is looked up among the discovered templates. Only when it is not
+/// installed does the recorded path stand in, with a warning.
+fn locate_template(
+    app: &tauri::AppHandle,
+    template: &RecipeTemplate,
+    recipe_file: &Path,
+    warnings: &mut Vec<String>,
+) -> Result<PathBuf, String> {
+    let by_path = resolve_recipe_template(app, &template.path, recipe_file);
+    let manifest = by_path.as_ref().ok().map(|path| load_manifest(path));
+
+    let (Some(name), Some(version)) = (&template.name, &template.version) else {
+        if let Some(Err(e)) = manifest {
+            warnings.push(e);
+        }
+        return by_path;
+    };
+    if let Some(Ok(manifest)) = &manifest {
+        if manifest.metadata.name == *name && same_version(&manifest.metadata.version, version) {
+            return by_path;
+        }
+    }
+
+    let discovered = discover_templates(app);
+    if let Some(index) = find_template(&discovered, name, Some(version)) {
+        return Ok(discovered[index].path.clone());
+    }
+
+    match manifest {
+        Some(Ok(manifest)) => warnings.push(format!(
+            "Recipe targets {} {}, which is not installed; using {} {}",
+            name, version, manifest.metadata.name, manifest.metadata.version
+        )),
+        Some(Err(e)) => warnings.push(e),
+        None => {}
+    }
+    by_path
+}
+
+/// Write a recipe file capturing a generation
+///
+/// The format follows the file extension: `.json` writes JSON, anything else YAML.
+/// Answers to `secret` prompts are never written. Returns the recipe as written.
+#[tauri::command]
+pub async fn export_recipe(
+    app: tauri::AppHandle,
+    path: String,
+    request: RecipeExportRequest,
+) -> Result<Recipe, String> {
+    let file = PathBuf::from(&path);
+    let engine_version = get_engine_version(&app);
+
+    let provenance = match (&request.request, &request.template_path) {
+        (Some(generation), None) => ProjectProvenance::procedural(generation, engine_version),
+        (None, Some(template_path)) => {
+            let resolved = resolve_template_path(template_path, &app)?;
+            let mut provenance = ProjectProvenance::template(
+                &resolved.to_string_lossy(),
+                request.data.as_deref(),
+                request.enrichment.clone(),
+                engine_version,
+            )?;
+            if let Some(template) = provenance.template.as_mut() {
+                template.path = portable_template_path(&resolved, &file);
+            }
+            provenance
+        }
+        _ => {
+            return Err(
+                "Provide either a procedural request or a template path to export".to_string(),
+            )
+        }
+    };
+
+    let mut recipe = Recipe::from_provenance(provenance);
+    recipe.name = request.name;
+    recipe.description = request.description;
+
+    let errors = recipe.validate();
+    if !errors.is_empty() {
+        return Err(format!("Cannot export recipe: {}", errors.join("; ")));
+    }
+
+    let content = if is_json(&file) {
+        serde_json::to_string_pretty(&recipe).map_err(|e| e.to_string())?
+    } else {
+        serde_yaml::to_string(&recipe).map_err(|e| e.to_string())?
+    };
+    fs::write(&file, content).map_err(|e| format!("Failed to write {}: {}", path, e))?;
+
+    Ok(recipe)
+}
+
+/// Read, migrate and validate a recipe file
+pub fn load_recipe(app: &tauri::AppHandle, file: &Path) -> Result<ImportedRecipe, String> {
+    let path = file.to_string_lossy();
+    let content =
+        fs::read_to_string(file).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    let (recipe, original) =
+        parse_recipe(&content).map_err(|e| format!("Invalid recipe {}: {}", path, e))?;
+
+    let mut warnings = Vec::new();
+    let engine_version = get_engine_version(app);
+    if let Some(recorded) = &recipe.engine_version {
+        if *recorded != engine_version {
+            warnings.push(format!(
+                "Recipe was exported with engine {}, current engine is {}",
+                recorded, engine_version
+            ));
+        }
+    }
+
+    let mut imported = ImportedRecipe {
+        recipe: recipe.clone(),
+        migrated_from: (original != RECIPE_VERSION).then_some(original),
+        request: None,
+        template_path: None,
+        data: None,
+        warnings,
+    };
+
+    match recipe.mode {
+        ProvenanceMode::Procedural => {
+            imported.request = Some(GenerationRequest {
+                mode: GenerationMode::Procedural,
+                seed: recipe.seed,
+                stack: recipe.stack,
+                output_path: String::new(),
+                enrichment: recipe.enrichment,
+            });
+        }
+        ProvenanceMode::Template => {
+            let Some(template) = recipe.template else {
+                return Err(format!("Invalid recipe {}: template: required", path));
+            };
+            let resolved = locate_template(app, &template, file, &mut imported.warnings)?;
+
+            imported.template_path = Some(resolved.to_string_lossy().to_string());
+            imported.data =
+                Some(serde_json::to_string(&recipe.answers).map_err(|e| e.to_string())?);
+        }
+    }
+
+    Ok(imported)
+}
+
+/// Read a recipe file, migrating older versions and validating it against the schema
+///
+/// Returns the request (procedural) or template path and answers (template) needed
+/// to reproduce the generation, plus warnings where the result may differ.
+#[tauri::command]
+pub async fn import_recipe(app: tauri::AppHandle, path: String) -> Result<ImportedRecipe, String> {
+    load_recipe(&app, Path::new(&path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn migrates_a_provenance_file() {
+        let doc: Value = serde_yaml::from_str(
+            "_version: '1'\n\
+             _template: {name: api, version: 1.2.0, source: /templates/api}\n\
+             _generated: {timestamp: '2026-01-01T00:00:00Z', upgVersion: 0.9.0}\n\
+             project_name: demo\n",
+        )
+        .unwrap();
+
+        let recipe: Recipe = serde_json::from_value(migrate_v0(doc).unwrap()).unwrap();
+
+        assert_eq!(recipe.recipe_version, 1);
+        assert_eq!(recipe.mode, ProvenanceMode::Template);
+        let template = recipe.template.unwrap();
+        assert_eq!(template.path, "/templates/api");
+        assert_eq!(template.name.as_deref(), Some("api"));
+        assert_eq!(template.version.as_deref(), Some("1.2.0"));
+        assert_eq!(recipe.answers["project_name"], "demo");
+        assert_eq!(recipe.engine_version.as_deref(), Some("0.9.0"));
+        assert_eq!(recipe.created_at.as_deref(), Some("2026-01-01T00:00:00Z"));
+    }
+
+    #[test]
+    fn migrates_a_generation_request() {
+        let doc = serde_json::json!({
+            "mode": "procedural",
+            "seed": 42,
+            "output_path": "/home/me/project",
+        });
+
+        let migrated = migrate_v0(doc).unwrap();
+
+        assert!(migrated.get("output_path").is_none());
+        assert_eq!(migrated["recipe_version"], 1);
+        assert_eq!(migrated["seed"], 42);
+    }
+
+    #[test]
+    fn rejects_a_non_mapping() {
+        assert!(migrate_v0(Value::from(3)).is_err());
+    }
+
+    #[test]
+    fn parses_and_validates_recipes() {
+        let (recipe, original) = parse_recipe("mode: procedural\nseed: 7\n").unwrap();
+        assert_eq!(original, 0);
+        assert_eq!(recipe.seed, Some(7));
+
+        let err = parse_recipe("recipe_version: 1\nmode: template\nseed: 7\n").unwrap_err();
+        assert!(err.contains("template: required in template mode"));
+        assert!(err.contains("seed: not allowed in template mode"));
+
+        let err = parse_recipe("recipe_version: 2\nmode: procedural\n").unwrap_err();
+        assert!(err.contains("newer than supported"), "{}", err);
+    }
+}
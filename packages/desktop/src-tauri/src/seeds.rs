@@ -0,0 +1,156 @@
+//! Seed catalog
+//!
+//! Seeds whose generated projects have been validated locally are recorded in the
+//! `seed-catalog.json` store and served to the Seed Gallery. Entries are keyed by seed
+//! and resolved stack, since one seed yields different projects under different stack
+//! constraints. A failed run never replaces a passing entry; it is noted on it as the
+//! last failure instead.
+
+use crate::files::sha256_hex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri_plugin_store::StoreExt;
+
+/// Store file holding the catalog
+pub const SEED_CATALOG_STORE: &str = "seed-catalog.json";
+
+/// Catalog entry (camelCase, as the Seed Gallery expects)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SeedCatalogEntry {
+    pub seed: u64,
+    /// Resolved stack as reported by the engine
+    pub stack: Value,
+    pub files: Vec<String>,
+    pub validated_at: String,
+    pub tags: Vec<String>,
+    /// Whether a local validation passed (`validated_at` is then the latest pass)
+    pub passed: bool,
+    pub engine_version: String,
+    /// Latest failed validation, kept next to an earlier pass
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_failure: Option<SeedFailure>,
+}
+
+/// A failed validation run of a catalog entry
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SeedFailure {
+    pub validated_at: String,
+    pub engine_version: String,
+}
+
+/// Store key of an entry: the seed and a hash of its resolved stack
+fn catalog_key(entry: &SeedCatalogEntry) -> String {
+    let stack = serde_json::to_string(&entry.stack).unwrap_or_default();
+    format!("{}-{}", entry.seed, &sha256_hex(stack.as_bytes())[..16])
+}
+
+/// Combine a new validation run with the stored entry for the same seed and stack
+fn merge_run(existing: Option<SeedCatalogEntry>, run: SeedCatalogEntry) -> SeedCatalogEntry {
+    if run.passed {
+        return SeedCatalogEntry {
+            last_failure: existing.and_then(|e| e.last_failure),
+            ..run
+        };
+    }
+    let failure = SeedFailure {
+        validated_at: run.validated_at.clone(),
+        engine_version: run.engine_version.clone(),
+    };
+    match existing {
+        Some(passing) if passing.passed => SeedCatalogEntry {
+            last_failure: Some(failure),
+            ..passing
+        },
+        _ => SeedCatalogEntry {
+            last_failure: Some(failure),
+            ..run
+        },
+    }
+}
+
+/// Record a validation run in the catalog entry for its seed and stack
+pub fn record_seed(app: &tauri::AppHandle, run: &SeedCatalogEntry) -> Result<(), String> {
+    let store = app.store(SEED_CATALOG_STORE).map_err(|e| e.to_string())?;
+    let key = catalog_key(run);
+    let existing = store
+        .get(&key)
+        .and_then(|value| serde_json::from_value(value).ok());
+    let entry = merge_run(existing, run.clone());
+    let value = serde_json::to_value(&entry).map_err(|e| e.to_string())?;
+    store.set(key, value);
+    store
+        .save()
+        .map_err(|e| format!("Failed to save seed catalog: {}", e))
+}
+
+/// Get validated seeds from the catalog, ordered by seed (one entry per resolved stack)
+#[tauri::command]
+pub async fn get_seeds(app: tauri::AppHandle) -> Result<Vec<SeedCatalogEntry>, String> {
+    let store = app.store(SEED_CATALOG_STORE).map_err(|e| e.to_string())?;
+    let mut seeds: Vec<SeedCatalogEntry> = store
+        .entries()
+        .into_iter()
+        .filter_map(|(_, value)| serde_json::from_value(value).ok())
+        .filter(|entry: &SeedCatalogEntry| entry.passed)
+        .collect();
+    seeds.sort_by_key(|entry| entry.seed);
+    Ok(seeds)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(stack: Value, passed: bool, at: &str) -> SeedCatalogEntry {
+        SeedCatalogEntry {
+            seed: 42,
+            stack,
+            files: vec!["README.md".to_string()],
+            validated_at: at.to_string(),
+            tags: vec![],
+            passed,
+            engine_version: "1.0.0".to_string(),
+            last_failure: None,
+        }
+    }
+
+    #[test]
+    fn keys_entries_by_seed_and_stack() {
+        let rust = run(serde_json::json!({ "language": "rust" }), true, "t1");
+        let go = run(serde_json::json!({ "language": "go" }), true, "t1");
+
+        assert!(catalog_key(&rust).starts_with("42-"));
+        assert_ne!(catalog_key(&rust), catalog_key(&go));
+        assert_eq!(catalog_key(&rust), catalog_key(&rust.clone()));
+    }
+
+    #[test]
+    fn failures_do_not_replace_a_pass() {
+        let stack = serde_json::json!({ "language": "rust" });
+        let passing = merge_run(None, run(stack.clone(), true, "t1"));
+
+        let merged = merge_run(Some(passing), run(stack.clone(), false, "t2"));
+        assert!(merged.passed);
+        assert_eq!(merged.validated_at, "t1");
+        assert_eq!(merged.last_failure.as_ref().unwrap().validated_at, "t2");
+
+        let merged = merge_run(Some(merged), run(stack, true, "t3"));
+        assert!(merged.passed);
+        assert_eq!(merged.validated_at, "t3");
+        assert_eq!(merged.last_failure.unwrap().validated_at, "t2");
+    }
+
+    #[test]
+    fn failures_are_recorded_without_a_pass() {
+        let stack = serde_json::json!({ "language": "go" });
+        let failed = merge_run(None, run(stack.clone(), false, "t1"));
+        assert!(!failed.passed);
+        assert_eq!(failed.last_failure.as_ref().unwrap().validated_at, "t1");
+
+        let failed = merge_run(Some(failed), run(stack, false, "t2"));
+        assert!(!failed.passed);
+        assert_eq!(failed.last_failure.unwrap().validated_at, "t2");
+    }
+}
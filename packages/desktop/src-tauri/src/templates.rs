@@ -0,0 +1,524 @@
+//! Template roots and discovery
+//!
+//! Templates are discovered under the roots listed in the `templateRoots` setting: the
+//! bundled templates, the user's templates in the app data directory and any number of
+//! extra folders. Each root is scanned recursively down to its depth limit; a directory
+//! holding a manifest is a template and is not descended into. Different versions of a
+//! template are kept side by side (the library installs them as `<name>/<version>/`);
+//! when several roots provide the same name and version, the root listed first wins.
+//! A template's default version is the newest one in the first root providing it, and
+//! `name@version` selects any other.
+
+use crate::catalog::CatalogMetadata;
+use crate::manifest::{resolve_manifest_path, ManifestFile, MANIFEST_FILENAMES};
+use crate::{read_setting, templates_dir, AppPaths};
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Settings key holding the root list
+pub const TEMPLATE_ROOTS_SETTING: &str = "templateRoots";
+
+/// Directory under the app data dir holding the user's templates
+const USER_TEMPLATES_DIR: &str = "templates";
+
+const DEFAULT_MAX_DEPTH: usize = 3;
+
+/// Upper bound for `maxDepth`, whatever the setting says
+const MAX_DEPTH_LIMIT: usize = 10;
+
+/// Directories never scanned for templates
+const SKIPPED_DIRS: &[&str] = &["node_modules", "target", "dist", "build", "__pycache__"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TemplateRootKind {
+    /// Templates shipped with the app
+    Bundled,
+    /// `templates/` in the app data directory
+    User,
+    /// Any folder chosen by the user
+    Folder,
+    /// Listed by path in a template catalog (see `catalog`); not a configurable root
+    Catalog,
+}
+
+/// Entry of the `templateRoots` setting (camelCase, as stored by the frontend)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TemplateRootConfig {
+    pub kind: TemplateRootKind,
+    /// Folder path (`folder` roots only); relative paths resolve against the home directory
+    #[serde(default)]
+    pub path: Option<String>,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// How many directory levels below the root to search (0 = the root itself)
+    #[serde(default)]
+    pub max_depth: Option<usize>,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// A resolved template root, as reported on template entries
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TemplateRoot {
+    pub kind: TemplateRootKind,
+    pub path: String,
+    /// Position in the root list; lower wins on duplicate template names
+    pub priority: usize,
+    pub max_depth: usize,
+}
+
+/// A template found on disk together with its parsed manifest
+pub struct DiscoveredTemplate {
+    pub path: PathBuf,
+    pub manifest: ManifestFile,
+    pub root: TemplateRoot,
+    /// Lower-priority templates with the same name and version that this one hides
+    pub shadows: Vec<String>,
+    /// Metadata from a catalog entry, overriding the manifest's
+    pub catalog: Option<CatalogMetadata>,
+}
+
+/// The user's templates directory (`templates/` in the app data directory)
+pub fn user_templates_dir(paths: &AppPaths) -> Option<PathBuf> {
+    paths
+        .app_data_dir
+        .as_ref()
+        .map(|dir| dir.join(USER_TEMPLATES_DIR))
+}
+
+/// Root list used when the setting is absent: user templates override bundled ones
+pub fn default_root_configs() -> Vec<TemplateRootConfig> {
+    [TemplateRootKind::User, TemplateRootKind::Bundled]
+        .into_iter()
+        .map(|kind| TemplateRootConfig {
+            kind,
+            path: None,
+            enabled: true,
+            max_depth: None,
+        })
+        .collect()
+}
+
+/// Root list from the `templateRoots` setting value (defaults when absent or invalid)
+pub fn root_configs(value: Option<serde_json::Value>) -> Vec<TemplateRootConfig> {
+    match value {
+        Some(value) if !value.is_null() => serde_json::from_value(value).unwrap_or_else(|e| {
+            eprintln!("Ignoring invalid {} setting: {}", TEMPLATE_ROOTS_SETTING, e);
+            default_root_configs()
+        }),
+        _ => default_root_configs(),
+    }
+}
+
+/// Resolve enabled roots to directories, in priority order
+///
+/// Roots that cannot be resolved are skipped; a directory listed twice keeps its first
+/// position.
+pub fn resolve_roots(paths: &AppPaths, configs: &[TemplateRootConfig]) -> Vec<TemplateRoot> {
+    let mut seen = HashSet::new();
+    let mut roots = Vec::new();
+
+    for config in configs.iter().filter(|c| c.enabled) {
+        let dir = match config.kind {
+            TemplateRootKind::Bundled => templates_dir(paths).ok(),
+            TemplateRootKind::User => user_templates_dir(paths),
+            TemplateRootKind::Folder => config.path.as_deref().and_then(|p| {
+                let path = PathBuf::from(p);
+                if path.is_absolute() {
+                    Some(path)
+                } else {
+                    paths.home_dir.as_ref().map(|home| home.join(path))
+                }
+            }),
+            TemplateRootKind::Catalog => None,
+        };
+        let Some(dir) = dir else {
+            eprintln!("Skipping unresolvable template root {:?}", config);
+            continue;
+        };
+        if !seen.insert(dir.clone()) {
+            continue;
+        }
+
+        roots.push(TemplateRoot {
+            kind: config.kind,
+            path: dir.to_string_lossy().to_string(),
+            priority: roots.len(),
+            max_depth: config
+                .max_depth
+                .unwrap_or(DEFAULT_MAX_DEPTH)
+                .min(MAX_DEPTH_LIMIT),
+        });
+    }
+
+    roots
+}
+
+fn is_manifest_name(name: &str) -> bool {
+    MANIFEST_FILENAMES.contains(&name)
+}
+
+/// Collect template directories under `dir`
+fn scan(
+    dir: &Path,
+    depth: usize,
+    max_depth: usize,
+    visited: &mut HashSet<PathBuf>,
+    found: &mut Vec<PathBuf>,
+) {
+    // Guards against symlink cycles
+    let Ok(canonical) = dir.canonicalize() else {
+        return;
+    };
+    if !visited.insert(canonical) {
+        return;
+    }
+
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    let mut subdirs = Vec::new();
+    let mut is_template = false;
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        let path = entry.path();
+        if path.is_dir() {
+            if !name.starts_with('.') && !SKIPPED_DIRS.contains(&name.as_str()) {
+                subdirs.push(path);
+            }
+        } else if is_manifest_name(&name) {
+            is_template = true;
+        }
+    }
+
+    if is_template {
+        found.push(dir.to_path_buf());
+        return;
+    }
+    if depth >= max_depth {
+        return;
+    }
+
+    subdirs.sort();
+    for subdir in subdirs {
+        scan(&subdir, depth + 1, max_depth, visited, found);
+    }
+}
+
+/// Template directories at or below `dir`, at most `max_depth` levels down
+pub fn find_template_dirs(dir: &Path, max_depth: usize) -> Vec<PathBuf> {
+    let mut found = Vec::new();
+    scan(dir, 0, max_depth, &mut HashSet::new(), &mut found);
+    found
+}
+
+/// Whether a scan of `root` would reach `dir` (within the depth limit, not under a
+/// skipped or hidden directory)
+pub fn is_discoverable(root: &TemplateRoot, dir: &Path) -> bool {
+    let Ok(relative) = dir.strip_prefix(&root.path) else {
+        return false;
+    };
+    let names: Vec<String> = relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().to_string())
+        .collect();
+    names.len() <= root.max_depth
+        && names
+            .iter()
+            .all(|n| !n.starts_with('.') && !SKIPPED_DIRS.contains(&n.as_str()))
+}
+
+/// Parse the manifest of a template directory, logging failures
+pub fn load_template(dir: &Path) -> Option<ManifestFile> {
+    let manifest_path = resolve_manifest_path(dir)?;
+    let content = match fs::read_to_string(&manifest_path) {
+        Ok(c) => c,
+        Err(e) => {
+            eprintln!("Failed to read manifest {:?}: {}", manifest_path, e);
+            return None;
+        }
+    };
+    match serde_yaml::from_str(&content) {
+        Ok(m) => Some(m),
+        Err(e) => {
+            eprintln!("Failed to parse manifest {:?}: {}", manifest_path, e);
+            None
+        }
+    }
+}
+
+/// Discover the templates under a set of roots, keeping the highest-priority template
+/// for each name and version
+pub fn discover_in_roots(roots: &[TemplateRoot]) -> Vec<DiscoveredTemplate> {
+    let mut templates: Vec<DiscoveredTemplate> = Vec::new();
+    let mut by_version: HashMap<(String, String), usize> = HashMap::new();
+
+    for root in roots {
+        let mut found = Vec::new();
+        scan(
+            Path::new(&root.path),
+            0,
+            root.max_depth,
+            &mut HashSet::new(),
+            &mut found,
+        );
+
+        for path in found {
+            let Some(manifest) = load_template(&path) else {
+                continue;
+            };
+            let key = (
+                manifest.metadata.name.clone(),
+                manifest.metadata.version.clone(),
+            );
+            if let Some(&winner) = by_version.get(&key) {
+                templates[winner]
+                    .shadows
+                    .push(path.to_string_lossy().to_string());
+                continue;
+            }
+
+            by_version.insert(key, templates.len());
+            templates.push(DiscoveredTemplate {
+                path,
+                manifest,
+                root: root.clone(),
+                shadows: vec![],
+                catalog: None,
+            });
+        }
+    }
+
+    templates
+}
+
+/// Parse a version leniently: `v2` and `2.1` read as `2.0.0` and `2.1.0`
+pub fn parse_version(raw: &str) -> Option<Version> {
+    let raw = raw.trim();
+    let raw = raw.strip_prefix('v').unwrap_or(raw);
+    ["", ".0", ".0.0"]
+        .iter()
+        .find_map(|padding| Version::parse(&format!("{}{}", raw, padding)).ok())
+}
+
+/// Whether two version strings name the same version (`2` and `2.0.0` do)
+pub fn same_version(a: &str, b: &str) -> bool {
+    a == b || matches!((parse_version(a), parse_version(b)), (Some(a), Some(b)) if a == b)
+}
+
+/// Order versions newest first; versions that do not parse come last
+pub fn newest_first(a: &str, b: &str) -> Ordering {
+    match (parse_version(a), parse_version(b)) {
+        (Some(a), Some(b)) => b.cmp(&a),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => a.cmp(b),
+    }
+}
+
+/// Order versions of one template: first root first, then newest first
+pub fn default_order(a: &DiscoveredTemplate, b: &DiscoveredTemplate) -> Ordering {
+    a.root
+        .priority
+        .cmp(&b.root.priority)
+        .then_with(|| newest_first(&a.manifest.metadata.version, &b.manifest.metadata.version))
+}
+
+/// Split a `name@version` template reference
+pub fn parse_template_ref(reference: &str) -> Option<(&str, &str)> {
+    let (name, version) = reference.split_once('@')?;
+    let valid_name = name
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_lowercase() || c.is_ascii_digit())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-');
+    let valid_version = !version.is_empty() && !version.contains(['/', '\\']);
+    (valid_name && valid_version).then_some((name, version))
+}
+
+/// Index of template `name` at `version`, or of its default version
+pub fn find_template(
+    templates: &[DiscoveredTemplate],
+    name: &str,
+    version: Option<&str>,
+) -> Option<usize> {
+    templates
+        .iter()
+        .enumerate()
+        .filter(|(_, t)| {
+            let meta = &t.manifest.metadata;
+            meta.name == name && version.map_or(true, |v| same_version(&meta.version, v))
+        })
+        .min_by(|(_, a), (_, b)| default_order(a, b))
+        .map(|(i, _)| i)
+}
+
+/// Status of a configured root
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TemplateRootStatus {
+    #[serde(flatten)]
+    pub root: TemplateRoot,
+    pub exists: bool,
+}
+
+/// Get the resolved template roots in priority order
+#[tauri::command]
+pub async fn get_template_roots(app: tauri::AppHandle) -> Result<Vec<TemplateRootStatus>, String> {
+    let configs = root_configs(read_setting(&app, TEMPLATE_ROOTS_SETTING));
+    let roots = resolve_roots(&AppPaths::from_app(&app), &configs);
+    Ok(roots
+        .into_iter()
+        .map(|root| TemplateRootStatus {
+            exists: Path::new(&root.path).is_dir(),
+            root,
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_template(dir: &Path, name: &str, version: &str) {
+        fs::create_dir_all(dir).unwrap();
+        fs::write(
+            dir.join("upg.yaml"),
+            format!("metadata:\n  name: {}\n  version: \"{}\"\n", name, version),
+        )
+        .unwrap();
+    }
+
+    fn root(kind: TemplateRootKind, path: &Path, priority: usize) -> TemplateRoot {
+        TemplateRoot {
+            kind,
+            path: path.to_string_lossy().to_string(),
+            priority,
+            max_depth: 2,
+        }
+    }
+
+    #[test]
+    fn earlier_roots_shadow_the_same_version() {
+        let user = tempfile::tempdir().unwrap();
+        let bundled = tempfile::tempdir().unwrap();
+        write_template(&user.path().join("api"), "api", "1.0.0");
+        write_template(&bundled.path().join("api"), "api", "1.0.0");
+        write_template(&bundled.path().join("api-2"), "api", "2.0.0");
+
+        let found = discover_in_roots(&[
+            root(TemplateRootKind::User, user.path(), 0),
+            root(TemplateRootKind::Bundled, bundled.path(), 1),
+        ]);
+
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0].path, user.path().join("api"));
+        assert_eq!(found[0].root.kind, TemplateRootKind::User);
+        assert_eq!(
+            found[0].shadows,
+            vec![bundled.path().join("api").to_string_lossy().to_string()]
+        );
+        assert_eq!(found[1].manifest.metadata.version, "2.0.0");
+        assert!(found[1].shadows.is_empty());
+    }
+
+    #[test]
+    fn scans_within_the_depth_limit() {
+        let dir = tempfile::tempdir().unwrap();
+        write_template(&dir.path().join("a/b"), "shallow", "1.0.0");
+        write_template(&dir.path().join("a/b/c"), "nested", "1.0.0");
+        write_template(&dir.path().join("x/y/z"), "deep", "1.0.0");
+        write_template(&dir.path().join("node_modules/pkg"), "skipped", "1.0.0");
+        write_template(&dir.path().join(".hidden"), "hidden", "1.0.0");
+
+        let found = discover_in_roots(&[root(TemplateRootKind::Folder, dir.path(), 0)]);
+        let names: Vec<&str> = found
+            .iter()
+            .map(|t| t.manifest.metadata.name.as_str())
+            .collect();
+
+        // A template directory is not searched further
+        assert_eq!(names, vec!["shallow"]);
+    }
+
+    #[test]
+    fn parses_versions_leniently() {
+        assert_eq!(parse_version("1.2.3"), Some(Version::new(1, 2, 3)));
+        assert_eq!(parse_version(" v2 "), Some(Version::new(2, 0, 0)));
+        assert_eq!(parse_version("2.1"), Some(Version::new(2, 1, 0)));
+        assert_eq!(
+            parse_version("1.0.0-beta.1").map(|v| v.pre.to_string()),
+            Some("beta.1".to_string())
+        );
+        assert_eq!(parse_version("latest"), None);
+        assert_eq!(parse_version(""), None);
+    }
+
+    #[test]
+    fn compares_and_orders_versions() {
+        assert!(same_version("2", "2.0.0"));
+        assert!(same_version("v1.1", "1.1.0"));
+        assert!(same_version("latest", "latest"));
+        assert!(!same_version("1.0.0", "1.0.1"));
+
+        let mut versions = vec!["1.2.0", "dev", "10.0.0", "2", "1.10.0", "alpha"];
+        versions.sort_by(|a, b| newest_first(a, b));
+        assert_eq!(
+            versions,
+            vec!["10.0.0", "2", "1.10.0", "1.2.0", "alpha", "dev"]
+        );
+    }
+
+    #[test]
+    fn parses_template_refs() {
+        assert_eq!(parse_template_ref("api@1.2.0"), Some(("api", "1.2.0")));
+        assert_eq!(parse_template_ref("2d-game@v2"), Some(("2d-game", "v2")));
+        assert_eq!(parse_template_ref("api"), None);
+        assert_eq!(parse_template_ref("api@"), None);
+        assert_eq!(parse_template_ref("@1.0.0"), None);
+        assert_eq!(parse_template_ref("-api@1.0.0"), None);
+        assert_eq!(parse_template_ref("Api@1.0.0"), None);
+        assert_eq!(parse_template_ref("api@../1.0.0"), None);
+        // Paths that happen to contain '@'
+        assert_eq!(parse_template_ref("/home/me@work/api"), None);
+        assert_eq!(parse_template_ref("scope@x/api"), None);
+    }
+
+    #[test]
+    fn finds_a_version_or_the_default() {
+        let user = tempfile::tempdir().unwrap();
+        let bundled = tempfile::tempdir().unwrap();
+        write_template(&user.path().join("api-1"), "api", "1.0.0");
+        write_template(&bundled.path().join("api-2"), "api", "2.0.0");
+        write_template(&bundled.path().join("api-3"), "api", "3.0.0-rc.1");
+        write_template(&bundled.path().join("cli"), "cli", "1.0.0");
+        let found = discover_in_roots(&[
+            root(TemplateRootKind::User, user.path(), 0),
+            root(TemplateRootKind::Bundled, bundled.path(), 1),
+        ]);
+        let version =
+            |index: Option<usize>| index.map(|i| found[i].manifest.metadata.version.as_str());
+
+        // The first root wins over newer versions further down
+        assert_eq!(version(find_template(&found, "api", None)), Some("1.0.0"));
+        assert_eq!(
+            version(find_template(&found, "api", Some("2"))),
+            Some("2.0.0")
+        );
+        assert_eq!(
+            version(find_template(&found, "api", Some("3.0.0-rc.1"))),
+            Some("3.0.0-rc.1")
+        );
+        assert_eq!(find_template(&found, "api", Some("4.0.0")), None);
+        assert_eq!(find_template(&found, "web", None), None);
+    }
+}
@@ -0,0 +1,882 @@
+//! Smart update of existing projects
+//!
+//! Regenerates a project twice from its `.upg-answers.yaml` provenance: once with the
+//! recorded settings (the base) and once with the requested template or seed changes.
+//! The project's current files are then three-way merged against the base, honoring the
+//! template's `smart_update` preserve/regenerate lists and conflict resolution strategy.
+//!
+//! The project's provenance moves to the new settings only once no conflict remains:
+//! while some are left for manual resolution, the new provenance waits in
+//! `.upg-answers.pending.yaml` and `resolve_update_conflicts` writes it when the last
+//! conflicted file is resolved.
+
+use crate::error_codes;
+use crate::files::{project_file, read_tree};
+use crate::hooks::{run_hook, template_dir, HookKind, HookResult, DEFAULT_HOOK_TIMEOUT_SECS};
+use crate::lifecycle::{breaking_changes_since, BreakingChangeReport};
+use crate::manifest::{load_manifest, ConflictResolution, SmartUpdateConfig};
+use crate::merge::merge3;
+use crate::provenance::{
+    find_provenance, load_provenance, write_provenance, ProjectProvenance, ProvenanceMode,
+    PROVENANCE_FILE,
+};
+use crate::{
+    build_cli_args, build_template_cli_args, execute_cli_internal, get_cli_command,
+    get_engine_version, resolve_template_path, resolve_template_ref, EnrichmentConfig,
+    GenerationMode, GenerationRequest, TechStackConfig,
+};
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+use tauri::Manager;
+
+/// Update request from frontend
+///
+/// Every field except `project_path` is optional; omitted settings keep the values
+/// recorded in the project's provenance.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateRequest {
+    /// Project directory (or any path inside it)
+    pub project_path: String,
+    /// Template to update to, as a path or `name@version` (template projects; defaults to
+    /// the recorded path)
+    #[serde(default)]
+    pub template_path: Option<String>,
+    /// New seed (procedural projects)
+    #[serde(default)]
+    pub seed: Option<u64>,
+    /// New stack constraints (procedural projects)
+    #[serde(default)]
+    pub stack: Option<TechStackConfig>,
+    #[serde(default)]
+    pub enrichment: Option<EnrichmentConfig>,
+    /// Answer overrides merged over the recorded answers (template projects)
+    #[serde(default)]
+    pub answers: Option<BTreeMap<String, Value>>,
+    /// Compute the update without touching the project
+    #[serde(default)]
+    pub dry_run: bool,
+    /// Run the new template's `pre_migration` hook before applying changes (listed by
+    /// `get_template_hooks` for approval)
+    #[serde(default)]
+    pub run_hooks: bool,
+    /// Proceed although the new template version documents breaking changes
+    #[serde(default)]
+    pub acknowledge_breaking_changes: bool,
+}
+
+/// What happened to a single file during an update
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UpdateAction {
+    /// New file from the template
+    Added,
+    /// Untouched by the user; replaced with the template version
+    Updated,
+    /// Changed on both sides and merged cleanly
+    Merged,
+    /// Removed by the template and untouched by the user
+    Removed,
+    /// Template changed it, but it is listed in `preserve_files`
+    Preserved,
+    /// Conflict resolved in favour of the user's version (auto-accept-user)
+    KeptCurrent,
+    /// Conflict resolved in favour of the template (auto-accept-template)
+    TookTemplate,
+    /// Conflict left for manual resolution
+    Conflict,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileUpdate {
+    pub path: String,
+    pub action: UpdateAction,
+}
+
+/// A conflict awaiting manual resolution
+///
+/// Contents are `None` when the file is absent on that side or not valid UTF-8.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MergeConflict {
+    pub path: String,
+    pub base: Option<String>,
+    pub current: Option<String>,
+    pub template: Option<String>,
+    /// Merge result with diff3 conflict markers, when both sides are text
+    pub merged: Option<String>,
+}
+
+/// Update result
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateResult {
+    pub success: bool,
+    /// UPG error code (NO_UPDATES, MERGE_CONFLICT, HOOK_FAILED) when applicable
+    pub code: Option<String>,
+    pub message: String,
+    pub project_path: String,
+    pub dry_run: bool,
+    pub files: Vec<FileUpdate>,
+    pub conflicts: Vec<MergeConflict>,
+    pub warnings: Vec<String>,
+    /// Hooks that ran before the changes were applied
+    pub hooks: Vec<HookResult>,
+    /// Breaking changes between the recorded and the new template version
+    pub breaking_changes: Option<BreakingChangeReport>,
+    pub duration_ms: u64,
+}
+
+/// Holds the next provenance while conflicts await manual resolution
+pub const PENDING_UPDATE_FILE: &str = ".upg-answers.pending.yaml";
+
+/// Contents of `.upg-answers.pending.yaml`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct PendingUpdate {
+    /// Conflicted files not resolved yet
+    conflicts: BTreeSet<String>,
+    /// Provenance to write once `conflicts` is empty
+    provenance: ProjectProvenance,
+}
+
+/// Settings for one regeneration
+pub(crate) enum GenerationSpec {
+    Procedural {
+        seed: u64,
+        stack: Option<TechStackConfig>,
+        enrichment: Option<EnrichmentConfig>,
+    },
+    Template {
+        path: String,
+        answers: BTreeMap<String, Value>,
+        enrichment: Option<EnrichmentConfig>,
+    },
+}
+
+/// Regenerate a project into a temporary directory and read it back
+fn regenerate(
+    app: &tauri::AppHandle,
+    spec: &GenerationSpec,
+) -> Result<BTreeMap<String, Vec<u8>>, String> {
+    regenerate_with(app, get_cli_command(app)?, spec)
+}
+
+/// `regenerate` with a specific CLI command (see `get_cli_command`)
+pub(crate) fn regenerate_with(
+    app: &tauri::AppHandle,
+    cli: (String, Vec<String>),
+    spec: &GenerationSpec,
+) -> Result<BTreeMap<String, Vec<u8>>, String> {
+    let temp = tempfile::tempdir().map_err(|e| format!("Failed to create temp dir: {}", e))?;
+    let output = temp.path().join("project");
+    let output_str = output.to_string_lossy().to_string();
+
+    let (cmd, mut args) = cli;
+    match spec {
+        GenerationSpec::Procedural {
+            seed,
+            stack,
+            enrichment,
+        } => args.extend(build_cli_args(*seed, &output_str, stack, enrichment)),
+        GenerationSpec::Template {
+            path,
+            answers,
+            enrichment,
+        } => {
+            let data = serde_json::to_string(answers)
+                .map_err(|e| format!("Failed to serialize answers: {}", e))?;
+            args.extend(build_template_cli_args(
+                path,
+                &output_str,
+                &Some(data),
+                true,
+                false,
+                true,
+                enrichment,
+            ));
+        }
+    }
+
+    // Same working directory as generation, so relative template paths resolve identically
+    let working_dir = app.path().home_dir().map_err(|e| e.to_string())?;
+    let (success, stdout, stderr, exit_code) = execute_cli_internal(&cmd, args, &working_dir)?;
+
+    let reported_failure = serde_json::from_str::<Value>(&stdout)
+        .ok()
+        .filter(|r| r.get("success").and_then(|v| v.as_bool()) == Some(false))
+        .map(|r| {
+            r.get("error")
+                .and_then(|v| v.as_str())
+                .unwrap_or("Generation failed")
+                .to_string()
+        });
+
+    if let Some(error) = reported_failure {
+        return Err(format!("Regeneration failed: {}", error));
+    }
+    if !success {
+        let detail = stderr
+            .lines()
+            .last()
+            .map(String::from)
+            .unwrap_or_else(|| format!("CLI exited with code {:?}", exit_code));
+        return Err(format!("Regeneration failed: {}", detail));
+    }
+
+    let mut files = read_tree(&output, &[])?;
+    files.remove(PROVENANCE_FILE);
+    files.remove(PENDING_UPDATE_FILE);
+    Ok(files)
+}
+
+fn build_globset(patterns: &[String]) -> Result<GlobSet, String> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        let glob =
+            Glob::new(pattern).map_err(|e| format!("Invalid pattern '{}': {}", pattern, e))?;
+        builder.add(glob);
+    }
+    builder
+        .build()
+        .map_err(|e| format!("Invalid smart_update patterns: {}", e))
+}
+
+fn as_text(content: Option<&Vec<u8>>) -> Option<String> {
+    content.and_then(|bytes| String::from_utf8(bytes.clone()).ok())
+}
+
+/// Outcome of planning an update
+#[derive(Default)]
+struct UpdatePlan {
+    files: Vec<FileUpdate>,
+    /// Content to write (`None` deletes the file)
+    writes: Vec<(String, Option<Vec<u8>>)>,
+    conflicts: Vec<MergeConflict>,
+}
+
+impl UpdatePlan {
+    fn apply(&mut self, path: &str, action: UpdateAction, content: Option<Vec<u8>>) {
+        self.files.push(FileUpdate {
+            path: path.to_string(),
+            action,
+        });
+        self.writes.push((path.to_string(), content));
+    }
+
+    fn note(&mut self, path: &str, action: UpdateAction) {
+        self.files.push(FileUpdate {
+            path: path.to_string(),
+            action,
+        });
+    }
+}
+
+/// Decide what happens to every file present in either regeneration
+fn plan_update(
+    project_root: &Path,
+    base: &BTreeMap<String, Vec<u8>>,
+    next: &BTreeMap<String, Vec<u8>>,
+    config: &SmartUpdateConfig,
+) -> Result<UpdatePlan, String> {
+    let preserve = build_globset(&config.preserve_files)?;
+    let regenerate = build_globset(&config.regenerate_files)?;
+    let mut plan = UpdatePlan::default();
+
+    let paths: BTreeSet<&String> = base.keys().chain(next.keys()).collect();
+    for path in paths {
+        let b = base.get(path);
+        let t = next.get(path);
+        let current = fs::read(project_file(project_root, path)?).ok();
+        let c = current.as_ref();
+
+        if c == t {
+            continue;
+        }
+
+        let added_or_updated = if c.is_none() {
+            UpdateAction::Added
+        } else if t.is_none() {
+            UpdateAction::Removed
+        } else {
+            UpdateAction::Updated
+        };
+
+        if preserve.is_match(path.as_str()) {
+            if b != t {
+                plan.note(path, UpdateAction::Preserved);
+            }
+            continue;
+        }
+
+        if regenerate.is_match(path.as_str()) {
+            plan.apply(path, added_or_updated, t.cloned());
+            continue;
+        }
+
+        // Template output for this file is unchanged: the user's version stands
+        if b == t {
+            continue;
+        }
+
+        // User never touched the file: take the template version
+        if c == b {
+            plan.apply(path, added_or_updated, t.cloned());
+            continue;
+        }
+
+        // Both sides changed: try a line merge when both exist as text
+        let base_text = if b.is_some() {
+            as_text(b)
+        } else {
+            Some(String::new())
+        };
+        let current_text = as_text(c);
+        let template_text = as_text(t);
+
+        let merged = match (&base_text, &current_text, &template_text) {
+            (Some(base_text), Some(current_text), Some(template_text)) => {
+                Some(merge3(base_text, current_text, template_text))
+            }
+            _ => None,
+        };
+
+        if let Some(outcome) = merged.as_ref().filter(|o| o.conflicts == 0) {
+            plan.apply(
+                path,
+                UpdateAction::Merged,
+                Some(outcome.merged.clone().into_bytes()),
+            );
+            continue;
+        }
+
+        match config.conflict_resolution {
+            ConflictResolution::AutoAcceptTemplate => {
+                plan.apply(path, UpdateAction::TookTemplate, t.cloned());
+            }
+            ConflictResolution::AutoAcceptUser => {
+                plan.note(path, UpdateAction::KeptCurrent);
+            }
+            ConflictResolution::Manual => {
+                plan.note(path, UpdateAction::Conflict);
+                plan.conflicts.push(MergeConflict {
+                    path: path.clone(),
+                    base: as_text(b),
+                    current: current_text,
+                    template: template_text,
+                    merged: merged.map(|o| o.merged),
+                });
+            }
+        }
+    }
+
+    Ok(plan)
+}
+
+/// Write planned changes into the project
+fn apply_writes(project_root: &Path, writes: &[(String, Option<Vec<u8>>)]) -> Result<(), String> {
+    for (path, content) in writes {
+        let target = project_file(project_root, path)?;
+        match content {
+            Some(bytes) => {
+                if let Some(parent) = target.parent() {
+                    fs::create_dir_all(parent)
+                        .map_err(|e| format!("Failed to create {:?}: {}", parent, e))?;
+                }
+                fs::write(&target, bytes)
+                    .map_err(|e| format!("Failed to write {}: {}", path, e))?;
+            }
+            None => {
+                if target.exists() {
+                    fs::remove_file(&target)
+                        .map_err(|e| format!("Failed to remove {}: {}", path, e))?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Update an existing project to a newer template or different seed settings
+///
+/// Regenerates the original output and the new output from the project's provenance,
+/// three-way merges the project's current files, and returns any conflicts for manual
+/// resolution (see `resolve_update_conflicts`). Non-conflicting changes are applied
+/// unless `dry_run` is set.
+#[tauri::command]
+pub async fn update_project(
+    app: tauri::AppHandle,
+    request: UpdateRequest,
+) -> Result<UpdateResult, String> {
+    let start = std::time::Instant::now();
+
+    let provenance_file = find_provenance(Path::new(&request.project_path)).ok_or_else(|| {
+        format!(
+            "Not a UPG project: no {} found at or above {}",
+            PROVENANCE_FILE, request.project_path
+        )
+    })?;
+    let project_root = provenance_file
+        .parent()
+        .ok_or("Invalid provenance location")?
+        .to_path_buf();
+    let recorded = load_provenance(&provenance_file)?;
+    let engine_version = get_engine_version(&app);
+    let mut warnings = Vec::new();
+
+    if recorded.engine_version != engine_version {
+        warnings.push(format!(
+            "Project was generated with engine {}; base reconstructed with {}. Unchanged files may show as conflicts.",
+            recorded.engine_version, engine_version
+        ));
+    }
+
+    let mut hook_target = None;
+    let mut breaking_changes = None;
+    let (base_spec, next_spec, config, next_provenance) = match recorded.mode {
+        ProvenanceMode::Procedural => {
+            let seed = recorded
+                .seed
+                .ok_or("Provenance is missing the seed for a procedural project")?;
+            let next_request = GenerationRequest {
+                mode: GenerationMode::Procedural,
+                seed: Some(request.seed.unwrap_or(seed)),
+                stack: request.stack.clone().or_else(|| recorded.stack.clone()),
+                output_path: project_root.to_string_lossy().to_string(),
+                enrichment: request
+                    .enrichment
+                    .clone()
+                    .or_else(|| recorded.enrichment.clone()),
+            };
+            let next_provenance = Ok(ProjectProvenance::procedural(
+                &next_request,
+                engine_version.clone(),
+            ));
+
+            (
+                GenerationSpec::Procedural {
+                    seed,
+                    stack: recorded.stack.clone(),
+                    enrichment: recorded.enrichment.clone(),
+                },
+                GenerationSpec::Procedural {
+                    seed: next_request.seed.unwrap_or(seed),
+                    stack: next_request.stack,
+                    enrichment: next_request.enrichment,
+                },
+                SmartUpdateConfig::default(),
+                next_provenance,
+            )
+        }
+        ProvenanceMode::Template => {
+            let template = recorded
+                .template
+                .clone()
+                .ok_or("Provenance is missing the template for a template project")?;
+            let next_path = match &request.template_path {
+                Some(path) => resolve_template_path(path, &app)?
+                    .to_string_lossy()
+                    .to_string(),
+                None => template.path.clone(),
+            };
+
+            // The recorded path may hold another version by now; prefer the recorded
+            // version wherever it is installed
+            let base_path = match load_manifest(Path::new(&template.path)) {
+                Ok(m) if m.metadata.version == template.version => template.path.clone(),
+                original => match resolve_template_ref(&app, &template.name, &template.version) {
+                    Ok(path) => path.to_string_lossy().to_string(),
+                    Err(_) => match original {
+                        Ok(m) => {
+                            warnings.push(format!(
+                                "Template {} {} is no longer available at {}; base reconstructed from {}",
+                                template.name, template.version, template.path, m.metadata.version
+                            ));
+                            template.path.clone()
+                        }
+                        Err(e) => return Err(format!("Cannot reconstruct original output: {}", e)),
+                    },
+                },
+            };
+
+            let manifest = load_manifest(Path::new(&next_path))?;
+            let config = manifest
+                .template
+                .clone()
+                .and_then(|t| t.smart_update)
+                .unwrap_or_default();
+            if !config.enabled {
+                return Err(format!(
+                    "Smart update is disabled for template {}",
+                    manifest.metadata.name
+                ));
+            }
+            breaking_changes = breaking_changes_since(&recorded, &manifest);
+
+            let mut answers = recorded.answers.clone();
+            answers.extend(request.answers.clone().unwrap_or_default());
+            let enrichment = request
+                .enrichment
+                .clone()
+                .or_else(|| recorded.enrichment.clone());
+            let data = serde_json::to_string(&answers)
+                .map_err(|e| format!("Failed to serialize answers: {}", e))?;
+            let next_provenance = ProjectProvenance::template(
+                &next_path,
+                Some(&data),
+                enrichment.clone(),
+                engine_version.clone(),
+            );
+            hook_target = Some((
+                next_path.clone(),
+                manifest,
+                answers
+                    .clone()
+                    .into_iter()
+                    .collect::<serde_json::Map<_, _>>(),
+            ));
+
+            (
+                GenerationSpec::Template {
+                    path: base_path,
+                    answers: recorded.answers.clone(),
+                    enrichment: recorded.enrichment.clone(),
+                },
+                GenerationSpec::Template {
+                    path: next_path,
+                    answers,
+                    enrichment,
+                },
+                config,
+                next_provenance,
+            )
+        }
+    };
+
+    let project_path = project_root.to_string_lossy().to_string();
+    if let Some(report) = breaking_changes
+        .as_ref()
+        .filter(|_| !request.acknowledge_breaking_changes && !request.dry_run)
+    {
+        return Ok(UpdateResult {
+            success: false,
+            code: Some(error_codes::INCOMPATIBLE_VERSION.to_string()),
+            message: format!(
+                "{} {} has breaking changes since {}; review them before updating",
+                report.template_name, report.to_version, report.from_version
+            ),
+            project_path,
+            dry_run: request.dry_run,
+            files: vec![],
+            conflicts: vec![],
+            warnings,
+            hooks: vec![],
+            breaking_changes,
+            duration_ms: start.elapsed().as_millis() as u64,
+        });
+    }
+
+    let base = regenerate(&app, &base_spec)?;
+    let next = regenerate(&app, &next_spec)?;
+    let plan = plan_update(&project_root, &base, &next, &config)?;
+
+    if plan.writes.is_empty() && plan.conflicts.is_empty() {
+        return Ok(UpdateResult {
+            success: true,
+            code: Some(error_codes::NO_UPDATES.to_string()),
+            message: "Project is already up to date".to_string(),
+            project_path,
+            dry_run: request.dry_run,
+            files: plan.files,
+            conflicts: vec![],
+            warnings,
+            hooks: vec![],
+            breaking_changes,
+            duration_ms: start.elapsed().as_millis() as u64,
+        });
+    }
+
+    let mut hooks = Vec::new();
+    if let Some((path, manifest, answers)) = hook_target.filter(|_| !request.dry_run) {
+        let dir = template_dir(Path::new(&path))?;
+        let timeout = Duration::from_secs(DEFAULT_HOOK_TIMEOUT_SECS);
+        if let Some(result) = run_hook(
+            &app,
+            HookKind::PreMigration,
+            &dir,
+            &manifest,
+            &project_root,
+            &answers,
+            request.run_hooks,
+            timeout,
+        )
+        .await
+        {
+            hooks.push(result);
+        }
+
+        if let Some(failed) = hooks.iter().find(|h| !h.success) {
+            return Ok(UpdateResult {
+                success: false,
+                code: failed.code.clone(),
+                message: format!("Update aborted: {}", failed.message),
+                project_path,
+                dry_run: request.dry_run,
+                files: plan.files,
+                conflicts: vec![],
+                warnings,
+                hooks,
+                breaking_changes,
+                duration_ms: start.elapsed().as_millis() as u64,
+            });
+        }
+    }
+
+    if !request.dry_run {
+        apply_writes(&project_root, &plan.writes)?;
+        let pending = PendingUpdate {
+            conflicts: plan.conflicts.iter().map(|c| c.path.clone()).collect(),
+            provenance: next_provenance?,
+        };
+        finish_or_defer(&project_root, pending)?;
+    }
+
+    let has_conflicts = !plan.conflicts.is_empty();
+    let message = if has_conflicts {
+        format!(
+            "{} file(s) changed, {} conflict(s) need manual resolution",
+            plan.writes.len(),
+            plan.conflicts.len()
+        )
+    } else {
+        format!("{} file(s) changed", plan.writes.len())
+    };
+
+    Ok(UpdateResult {
+        success: !has_conflicts,
+        code: has_conflicts.then(|| error_codes::MERGE_CONFLICT.to_string()),
+        message,
+        project_path,
+        dry_run: request.dry_run,
+        files: plan.files,
+        conflicts: plan.conflicts,
+        warnings,
+        hooks,
+        breaking_changes,
+        duration_ms: start.elapsed().as_millis() as u64,
+    })
+}
+
+/// Write the new provenance when no conflict remains, or keep it pending until they are
+/// resolved
+fn finish_or_defer(project_root: &Path, pending: PendingUpdate) -> Result<(), String> {
+    let pending_file = project_root.join(PENDING_UPDATE_FILE);
+    if pending.conflicts.is_empty() {
+        write_provenance(project_root, &pending.provenance)?;
+        if pending_file.exists() {
+            fs::remove_file(&pending_file)
+                .map_err(|e| format!("Failed to remove {}: {}", PENDING_UPDATE_FILE, e))?;
+        }
+        return Ok(());
+    }
+
+    let yaml = serde_yaml::to_string(&pending)
+        .map_err(|e| format!("Failed to serialize pending update: {}", e))?;
+    fs::write(&pending_file, yaml)
+        .map_err(|e| format!("Failed to write {}: {}", PENDING_UPDATE_FILE, e))
+}
+
+/// Write the user's chosen content for conflicted files (`None` deletes the file)
+///
+/// Once every conflict of the pending update is resolved, the project's provenance is
+/// updated to the new template or seed settings.
+#[tauri::command]
+pub async fn resolve_update_conflicts(
+    project_path: String,
+    resolutions: HashMap<String, Option<String>>,
+) -> Result<(), String> {
+    let project_root = Path::new(&project_path);
+    let writes: Vec<(String, Option<Vec<u8>>)> = resolutions
+        .into_iter()
+        .map(|(path, content)| (path, content.map(String::into_bytes)))
+        .collect();
+
+    apply_writes(project_root, &writes)?;
+
+    let pending_file = project_root.join(PENDING_UPDATE_FILE);
+    if !pending_file.is_file() {
+        return Ok(());
+    }
+    let content = fs::read_to_string(&pending_file)
+        .map_err(|e| format!("Failed to read {}: {}", PENDING_UPDATE_FILE, e))?;
+    let mut pending: PendingUpdate = serde_yaml::from_str(&content)
+        .map_err(|e| format!("Corrupted {}: {}", PENDING_UPDATE_FILE, e))?;
+    for (path, _) in &writes {
+        pending.conflicts.remove(path);
+    }
+    finish_or_defer(project_root, pending)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::provenance::TemplateProvenance;
+
+    fn tree(files: &[(&str, &str)]) -> BTreeMap<String, Vec<u8>> {
+        files
+            .iter()
+            .map(|(path, content)| (path.to_string(), content.as_bytes().to_vec()))
+            .collect()
+    }
+
+    fn project(files: &[(&str, &str)]) -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        for (path, content) in files {
+            let target = dir.path().join(path);
+            fs::create_dir_all(target.parent().unwrap()).unwrap();
+            fs::write(target, content).unwrap();
+        }
+        dir
+    }
+
+    fn actions(plan: &UpdatePlan) -> BTreeMap<&str, UpdateAction> {
+        plan.files
+            .iter()
+            .map(|f| (f.path.as_str(), f.action))
+            .collect()
+    }
+
+    fn provenance(version: &str) -> ProjectProvenance {
+        ProjectProvenance {
+            provenance_version: "1".to_string(),
+            mode: ProvenanceMode::Template,
+            seed: None,
+            stack: None,
+            enrichment: None,
+            template: Some(TemplateProvenance {
+                name: "api".to_string(),
+                version: version.to_string(),
+                path: "/templates/api".to_string(),
+            }),
+            answers: BTreeMap::new(),
+            engine_version: "0.9.0".to_string(),
+            app_version: "0.1.0".to_string(),
+            generated_at: "2026-01-01T00:00:00+00:00".to_string(),
+            extra: BTreeMap::new(),
+        }
+    }
+
+    #[test]
+    fn plans_template_changes_against_user_edits() {
+        let base = tree(&[
+            ("untouched.txt", "old\n"),
+            ("edited.txt", "a\nb\nc\n"),
+            ("kept.txt", "same\n"),
+            ("conflict.txt", "x\n"),
+            ("removed.txt", "gone\n"),
+        ]);
+        let next = tree(&[
+            ("untouched.txt", "new\n"),
+            ("edited.txt", "a\nb\nC\n"),
+            ("kept.txt", "same\n"),
+            ("conflict.txt", "template\n"),
+            ("added.txt", "hello\n"),
+        ]);
+        let dir = project(&[
+            ("untouched.txt", "old\n"),
+            ("edited.txt", "A\nb\nc\n"),
+            ("kept.txt", "user\n"),
+            ("conflict.txt", "user\n"),
+            ("removed.txt", "gone\n"),
+        ]);
+
+        let plan = plan_update(dir.path(), &base, &next, &SmartUpdateConfig::default()).unwrap();
+        let actions = actions(&plan);
+
+        assert_eq!(actions["untouched.txt"], UpdateAction::Updated);
+        assert_eq!(actions["edited.txt"], UpdateAction::Merged);
+        assert_eq!(actions["conflict.txt"], UpdateAction::Conflict);
+        assert_eq!(actions["removed.txt"], UpdateAction::Removed);
+        assert_eq!(actions["added.txt"], UpdateAction::Added);
+        assert!(!actions.contains_key("kept.txt"));
+
+        let writes: BTreeMap<_, _> = plan.writes.iter().cloned().collect();
+        assert_eq!(writes["edited.txt"], Some(b"A\nb\nC\n".to_vec()));
+        assert_eq!(writes["removed.txt"], None);
+        assert!(!writes.contains_key("conflict.txt"));
+        assert_eq!(plan.conflicts.len(), 1);
+        assert_eq!(plan.conflicts[0].current.as_deref(), Some("user\n"));
+    }
+
+    #[test]
+    fn honours_preserve_and_regenerate_lists() {
+        let base = tree(&[("config.toml", "a\n"), ("generated.rs", "a\n")]);
+        let next = tree(&[("config.toml", "b\n"), ("generated.rs", "b\n")]);
+        let dir = project(&[("config.toml", "a\n"), ("generated.rs", "user\n")]);
+        let config = SmartUpdateConfig {
+            enabled: true,
+            preserve_files: vec!["*.toml".to_string()],
+            regenerate_files: vec!["*.rs".to_string()],
+            conflict_resolution: ConflictResolution::Manual,
+        };
+
+        let plan = plan_update(dir.path(), &base, &next, &config).unwrap();
+        let actions = actions(&plan);
+
+        assert_eq!(actions["config.toml"], UpdateAction::Preserved);
+        assert_eq!(actions["generated.rs"], UpdateAction::Updated);
+        assert_eq!(
+            plan.writes,
+            vec![("generated.rs".to_string(), Some(b"b\n".to_vec()))]
+        );
+    }
+
+    #[test]
+    fn resolves_conflicts_automatically_when_configured() {
+        let base = tree(&[("file.txt", "x\n")]);
+        let next = tree(&[("file.txt", "template\n")]);
+        let dir = project(&[("file.txt", "user\n")]);
+
+        for (resolution, expected) in [
+            (
+                ConflictResolution::AutoAcceptTemplate,
+                UpdateAction::TookTemplate,
+            ),
+            (
+                ConflictResolution::AutoAcceptUser,
+                UpdateAction::KeptCurrent,
+            ),
+        ] {
+            let config = SmartUpdateConfig {
+                conflict_resolution: resolution,
+                ..SmartUpdateConfig::default()
+            };
+            let plan = plan_update(dir.path(), &base, &next, &config).unwrap();
+            assert_eq!(actions(&plan)["file.txt"], expected);
+            assert!(plan.conflicts.is_empty());
+        }
+    }
+
+    #[test]
+    fn keeps_provenance_pending_until_conflicts_are_resolved() {
+        let dir = project(&[]);
+        let pending = PendingUpdate {
+            conflicts: BTreeSet::from(["a.txt".to_string()]),
+            provenance: provenance("2.0.0"),
+        };
+
+        finish_or_defer(dir.path(), pending.clone()).unwrap();
+        assert!(!dir.path().join(PROVENANCE_FILE).exists());
+        assert!(dir.path().join(PENDING_UPDATE_FILE).exists());
+
+        let resolved = PendingUpdate {
+            conflicts: BTreeSet::new(),
+            ..pending
+        };
+        finish_or_defer(dir.path(), resolved).unwrap();
+        assert!(!dir.path().join(PENDING_UPDATE_FILE).exists());
+        let written = load_provenance(&dir.path().join(PROVENANCE_FILE)).unwrap();
+        assert_eq!(written.template.unwrap().version, "2.0.0");
+    }
+}
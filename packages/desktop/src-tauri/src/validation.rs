@@ -0,0 +1,724 @@
+//! Local build/test validation of generated projects
+//!
+//! Detects a project's ecosystems from its files (the same checks as the sweeper's
+//! `validate.sh`) and runs the install, typecheck, build and test steps for each,
+//! with per-step timeouts taken from the sweeper's `detectBuildSteps`. Output is
+//! streamed as `validation://output` events and the verdict is recorded in the
+//! generation history and, for procedural projects, the seed catalog.
+
+use crate::files::{walk_files, IGNORED_DIRS};
+use crate::history::{record_validation, ValidationVerdict};
+use crate::process::{run_streaming, shell_command, OutputStream};
+use crate::provenance::{find_provenance, load_provenance, ProvenanceMode, PROVENANCE_FILE};
+use crate::seeds::{record_seed, SeedCatalogEntry};
+use crate::{get_engine_version, run_preview};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+use tauri::Emitter;
+
+/// Event carrying one line of step output
+pub const VALIDATION_OUTPUT_EVENT: &str = "validation://output";
+/// Event emitted when a step finishes
+pub const VALIDATION_STEP_EVENT: &str = "validation://step";
+
+/// Step output kept in results (the full output is streamed)
+const MAX_STEP_OUTPUT_BYTES: usize = 64 * 1024;
+
+/// Project type, detected from its build files
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Ecosystem {
+    Node,
+    Python,
+    Rust,
+    Go,
+    Gradle,
+    Maven,
+    Dotnet,
+    Make,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StepPhase {
+    Install,
+    Format,
+    Lint,
+    Typecheck,
+    Build,
+    Test,
+}
+
+/// A command to run as part of validation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValidationStep {
+    pub ecosystem: Ecosystem,
+    pub phase: StepPhase,
+    pub command: String,
+    pub timeout_secs: u64,
+    /// Whether a failure fails the verdict (format and lint steps are advisory)
+    pub required: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StepStatus {
+    Passed,
+    Failed,
+    TimedOut,
+    /// An earlier required step failed
+    NotRun,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StepResult {
+    pub step: ValidationStep,
+    pub status: StepStatus,
+    pub exit_code: Option<i32>,
+    /// Tail of stdout (at most 64 KiB)
+    pub stdout: String,
+    /// Tail of stderr (at most 64 KiB)
+    pub stderr: String,
+    pub duration_ms: u64,
+}
+
+/// Validation request from frontend
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValidationRequest {
+    /// Project directory
+    pub project_path: String,
+    /// Correlates streamed events with this run
+    #[serde(default)]
+    pub validation_id: Option<String>,
+    /// Skip dependency installation (dependencies already present)
+    #[serde(default)]
+    pub skip_install: bool,
+    /// Also run advisory format and lint steps
+    #[serde(default)]
+    pub include_lint: bool,
+    /// Override every step's timeout
+    #[serde(default)]
+    pub timeout_secs: Option<u64>,
+}
+
+/// Payload of `validation://output` events
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValidationOutputEvent {
+    pub validation_id: String,
+    pub command: String,
+    pub stream: OutputStream,
+    pub line: String,
+}
+
+/// Payload of `validation://step` events
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValidationStepEvent {
+    pub validation_id: String,
+    pub index: usize,
+    pub total: usize,
+    pub result: StepResult,
+}
+
+/// Result of `validate_generated_project`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectValidationResult {
+    pub project_path: String,
+    pub validation_id: String,
+    pub ecosystems: Vec<Ecosystem>,
+    pub steps: Vec<StepResult>,
+    pub passed: bool,
+    pub message: String,
+    /// Whether the verdict was attached to a generation history entry
+    pub recorded_in_history: bool,
+    /// Seed recorded in the seed catalog (procedural projects)
+    pub catalog_seed: Option<u64>,
+    pub duration_ms: u64,
+}
+
+/// Detect the ecosystems present at the project root
+pub fn detect_ecosystems(dir: &Path) -> Vec<Ecosystem> {
+    let has = |name: &str| dir.join(name).is_file();
+    let has_ext = |ext: &str| {
+        fs::read_dir(dir).is_ok_and(|entries| {
+            entries
+                .flatten()
+                .any(|e| e.path().extension().is_some_and(|x| x == ext))
+        })
+    };
+
+    let mut found = Vec::new();
+    if has("package.json") {
+        found.push(Ecosystem::Node);
+    }
+    if has("requirements.txt") || has("pyproject.toml") {
+        found.push(Ecosystem::Python);
+    }
+    if has("Cargo.toml") {
+        found.push(Ecosystem::Rust);
+    }
+    if has("go.mod") {
+        found.push(Ecosystem::Go);
+    }
+    if has("build.gradle") || has("build.gradle.kts") {
+        found.push(Ecosystem::Gradle);
+    }
+    if has("pom.xml") {
+        found.push(Ecosystem::Maven);
+    }
+    if has_ext("csproj") || has_ext("sln") {
+        found.push(Ecosystem::Dotnet);
+    }
+    if found.is_empty() && has("Makefile") {
+        found.push(Ecosystem::Make);
+    }
+    found
+}
+
+fn step(
+    ecosystem: Ecosystem,
+    phase: StepPhase,
+    command: impl Into<String>,
+    timeout_secs: u64,
+) -> ValidationStep {
+    ValidationStep {
+        ecosystem,
+        phase,
+        command: command.into(),
+        timeout_secs,
+        required: !matches!(phase, StepPhase::Format | StepPhase::Lint),
+    }
+}
+
+fn node_steps(dir: &Path) -> Vec<ValidationStep> {
+    let scripts: Vec<String> = fs::read_to_string(dir.join("package.json"))
+        .ok()
+        .and_then(|s| serde_json::from_str::<Value>(&s).ok())
+        .and_then(|pkg| {
+            pkg.get("scripts")?
+                .as_object()
+                .map(|s| s.keys().cloned().collect())
+        })
+        .unwrap_or_default();
+    let has_script = |name: &str| scripts.iter().any(|s| s == name);
+
+    let (pm, install) = if dir.join("pnpm-lock.yaml").is_file() {
+        ("pnpm", "pnpm install --frozen-lockfile")
+    } else if dir.join("bun.lockb").is_file() {
+        ("bun", "bun install --frozen-lockfile")
+    } else if dir.join("yarn.lock").is_file() {
+        ("yarn", "yarn install --frozen-lockfile")
+    } else if dir.join("package-lock.json").is_file() {
+        ("npm", "npm ci")
+    } else {
+        ("npm", "npm install")
+    };
+
+    let mut steps = vec![step(Ecosystem::Node, StepPhase::Install, install, 120)];
+    for (script, phase, timeout) in [
+        ("typecheck", StepPhase::Typecheck, 60),
+        ("lint", StepPhase::Lint, 60),
+        ("build", StepPhase::Build, 120),
+        ("test", StepPhase::Test, 120),
+    ] {
+        if has_script(script) {
+            steps.push(step(
+                Ecosystem::Node,
+                phase,
+                format!("{} run {}", pm, script),
+                timeout,
+            ));
+        }
+    }
+    steps
+}
+
+fn python_steps(dir: &Path) -> Vec<ValidationStep> {
+    let python = if cfg!(windows) {
+        r".venv\Scripts\python"
+    } else {
+        ".venv/bin/python"
+    };
+    let bootstrap = if cfg!(windows) { "python" } else { "python3" };
+
+    let mut steps = vec![step(
+        Ecosystem::Python,
+        StepPhase::Install,
+        format!("{} -m venv .venv", bootstrap),
+        120,
+    )];
+    if dir.join("requirements.txt").is_file() {
+        steps.push(step(
+            Ecosystem::Python,
+            StepPhase::Install,
+            format!("{} -m pip install -r requirements.txt", python),
+            120,
+        ));
+    } else {
+        steps.push(step(
+            Ecosystem::Python,
+            StepPhase::Install,
+            format!("{} -m pip install -e .", python),
+            120,
+        ));
+    }
+    steps.push(step(
+        Ecosystem::Python,
+        StepPhase::Lint,
+        format!("{} -m ruff check .", python),
+        60,
+    ));
+    steps.push(step(
+        Ecosystem::Python,
+        StepPhase::Lint,
+        format!("{} -m mypy .", python),
+        60,
+    ));
+    steps.push(step(
+        Ecosystem::Python,
+        StepPhase::Build,
+        format!("{} -m compileall -q -x .venv .", python),
+        60,
+    ));
+
+    let has_tests = dir.join("tests").is_dir()
+        || walk_files(dir, IGNORED_DIRS).iter().any(|f| {
+            let name = f.rsplit('/').next().unwrap_or(f);
+            name.starts_with("test_") && name.ends_with(".py")
+        });
+    if has_tests {
+        steps.push(step(
+            Ecosystem::Python,
+            StepPhase::Test,
+            format!("{} -m pytest", python),
+            120,
+        ));
+    }
+    steps
+}
+
+fn makefile_has_target(dir: &Path, target: &str) -> bool {
+    fs::read_to_string(dir.join("Makefile"))
+        .map(|m| m.lines().any(|l| l.starts_with(&format!("{}:", target))))
+        .unwrap_or(false)
+}
+
+/// Validation steps for an ecosystem, in run order
+pub fn ecosystem_steps(dir: &Path, ecosystem: Ecosystem) -> Vec<ValidationStep> {
+    use Ecosystem::*;
+    use StepPhase::*;
+
+    match ecosystem {
+        Node => node_steps(dir),
+        Python => python_steps(dir),
+        Rust => vec![
+            step(Rust, Format, "cargo fmt --check", 30),
+            step(Rust, Lint, "cargo clippy -- -D warnings", 120),
+            step(Rust, Build, "cargo build", 300),
+            step(Rust, Test, "cargo test", 120),
+        ],
+        Go => vec![
+            step(Go, Install, "go mod download", 120),
+            step(Go, Lint, "golangci-lint run", 60),
+            step(Go, Typecheck, "go vet ./...", 60),
+            step(Go, Build, "go build ./...", 120),
+            step(Go, Test, "go test ./...", 120),
+        ],
+        Gradle => {
+            let gradle = if dir.join("gradlew").is_file() || dir.join("gradlew.bat").is_file() {
+                if cfg!(windows) {
+                    r".\gradlew"
+                } else {
+                    "./gradlew"
+                }
+            } else {
+                "gradle"
+            };
+            vec![
+                step(Gradle, Build, format!("{} build -x test", gradle), 300),
+                step(Gradle, Test, format!("{} test", gradle), 120),
+            ]
+        }
+        Maven => vec![
+            step(Maven, Build, "mvn -B -DskipTests package", 300),
+            step(Maven, Test, "mvn -B test", 120),
+        ],
+        Dotnet => vec![
+            step(Dotnet, Install, "dotnet restore", 120),
+            step(Dotnet, Build, "dotnet build --no-restore", 120),
+            step(Dotnet, Test, "dotnet test --no-build", 120),
+        ],
+        Make => ["build", "test"]
+            .into_iter()
+            .filter(|t| makefile_has_target(dir, t))
+            .map(|t| {
+                let phase = if t == "build" { Build } else { Test };
+                step(
+                    Make,
+                    phase,
+                    format!("make {}", t),
+                    if t == "build" { 300 } else { 120 },
+                )
+            })
+            .collect(),
+    }
+}
+
+/// Keep the last `max` bytes of `s`, cut at a character boundary
+fn tail(s: String, max: usize) -> String {
+    if s.len() <= max {
+        return s;
+    }
+    let mut start = s.len() - max;
+    while !s.is_char_boundary(start) {
+        start += 1;
+    }
+    s[start..].to_string()
+}
+
+/// Run the project's typecheck/build/test steps locally and record the verdict
+///
+/// Steps run sequentially in the project directory; the first failing required step
+/// stops the run. The verdict is attached to the project's generation history entry
+/// and, for procedural projects, the seed is recorded in the seed catalog.
+#[tauri::command]
+pub async fn validate_generated_project(
+    app: tauri::AppHandle,
+    request: ValidationRequest,
+) -> Result<ProjectValidationResult, String> {
+    let start = std::time::Instant::now();
+    let dir = Path::new(&request.project_path);
+    if !dir.is_dir() {
+        return Err(format!("Not a directory: {}", request.project_path));
+    }
+
+    let validation_id = request
+        .validation_id
+        .clone()
+        .unwrap_or_else(|| format!("validation-{}", chrono::Utc::now().timestamp_millis()));
+
+    let ecosystems = detect_ecosystems(dir);
+    if ecosystems.is_empty() {
+        return Err(format!(
+            "Could not detect a project type in {} (no package.json, Cargo.toml, go.mod, pyproject.toml, pom.xml, ...)",
+            request.project_path
+        ));
+    }
+
+    let steps: Vec<ValidationStep> = ecosystems
+        .iter()
+        .flat_map(|e| ecosystem_steps(dir, *e))
+        .filter(|s| !(request.skip_install && s.phase == StepPhase::Install))
+        .filter(|s| request.include_lint || s.required)
+        .collect();
+
+    let total = steps.len();
+    let mut results = Vec::with_capacity(total);
+    let mut failed_step = None;
+
+    for (index, step) in steps.into_iter().enumerate() {
+        let result = if failed_step.is_some() {
+            StepResult {
+                step,
+                status: StepStatus::NotRun,
+                exit_code: None,
+                stdout: String::new(),
+                stderr: String::new(),
+                duration_ms: 0,
+            }
+        } else {
+            let timeout = Duration::from_secs(request.timeout_secs.unwrap_or(step.timeout_secs));
+            let mut command = shell_command(&step.command);
+            command.current_dir(dir).env("CI", "1");
+
+            let outcome = run_streaming(command, None, timeout, |stream, line| {
+                let event = ValidationOutputEvent {
+                    validation_id: validation_id.clone(),
+                    command: step.command.clone(),
+                    stream,
+                    line: line.to_string(),
+                };
+                if let Err(e) = app.emit(VALIDATION_OUTPUT_EVENT, event) {
+                    eprintln!("Failed to emit {}: {}", VALIDATION_OUTPUT_EVENT, e);
+                }
+            })
+            .await;
+
+            let result = match outcome {
+                Ok(o) => StepResult {
+                    status: if o.timed_out {
+                        StepStatus::TimedOut
+                    } else if o.success {
+                        StepStatus::Passed
+                    } else {
+                        StepStatus::Failed
+                    },
+                    exit_code: o.exit_code,
+                    stdout: tail(o.stdout, MAX_STEP_OUTPUT_BYTES),
+                    stderr: tail(o.stderr, MAX_STEP_OUTPUT_BYTES),
+                    duration_ms: o.duration_ms,
+                    step,
+                },
+                Err(e) => StepResult {
+                    step,
+                    status: StepStatus::Failed,
+                    exit_code: None,
+                    stdout: String::new(),
+                    stderr: e,
+                    duration_ms: 0,
+                },
+            };
+
+            if result.status != StepStatus::Passed && result.step.required {
+                failed_step = Some(result.step.command.clone());
+            }
+            result
+        };
+
+        let event = ValidationStepEvent {
+            validation_id: validation_id.clone(),
+            index,
+            total,
+            result: result.clone(),
+        };
+        if let Err(e) = app.emit(VALIDATION_STEP_EVENT, event) {
+            eprintln!("Failed to emit {}: {}", VALIDATION_STEP_EVENT, e);
+        }
+        results.push(result);
+    }
+
+    let passed = failed_step.is_none();
+    let duration_ms = start.elapsed().as_millis() as u64;
+    let validated_at = chrono::Utc::now().to_rfc3339();
+    let verdict = ValidationVerdict {
+        passed,
+        ecosystems: ecosystems
+            .iter()
+            .filter_map(|e| serde_json::to_value(e).ok()?.as_str().map(String::from))
+            .collect(),
+        failed_step: failed_step.clone(),
+        validated_at: validated_at.clone(),
+        duration_ms,
+    };
+
+    let provenance_file = find_provenance(dir);
+    let project_root = provenance_file
+        .as_deref()
+        .and_then(Path::parent)
+        .unwrap_or(dir)
+        .to_path_buf();
+
+    let recorded_in_history = record_validation(&app, &project_root, verdict).unwrap_or_else(|e| {
+        eprintln!("Failed to record validation in history: {}", e);
+        false
+    });
+
+    let mut catalog_seed = None;
+    let provenance = provenance_file.and_then(|f| load_provenance(&f).ok());
+    if let Some(p) = provenance.filter(|p| p.mode == ProvenanceMode::Procedural) {
+        if let Some(preview_request) = p.to_generation_request(&project_root.to_string_lossy()) {
+            // The provenance holds the requested constraints; the catalog wants the resolved stack
+            let preview_app = app.clone();
+            let resolved =
+                tokio::task::spawn_blocking(move || run_preview(&preview_app, &preview_request))
+                    .await
+                    .ok()
+                    .and_then(Result::ok)
+                    .and_then(|preview| preview.stack);
+            let stack = resolved
+                .or_else(|| serde_json::to_value(&p.stack).ok())
+                .unwrap_or(Value::Null);
+
+            let mut tags: Vec<String> = ["archetype", "language", "framework"]
+                .iter()
+                .filter_map(|k| stack.get(k)?.as_str().map(String::from))
+                .collect();
+            tags.push("validated".to_string());
+
+            if let Some(seed) = p.seed {
+                let files = walk_files(&project_root, IGNORED_DIRS)
+                    .into_iter()
+                    .filter(|f| f != PROVENANCE_FILE)
+                    .collect();
+                let entry = SeedCatalogEntry {
+                    seed,
+                    stack,
+                    files,
+                    validated_at,
+                    tags,
+                    passed,
+                    engine_version: get_engine_version(&app),
+                    last_failure: None,
+                };
+                match record_seed(&app, &entry) {
+                    Ok(()) => catalog_seed = Some(seed),
+                    Err(e) => eprintln!("Failed to record seed {} in catalog: {}", seed, e),
+                }
+            }
+        }
+    }
+
+    let message = match &failed_step {
+        None => format!("{} step(s) passed in {}ms", results.len(), duration_ms),
+        Some(command) => format!("Validation failed at `{}`", command),
+    };
+
+    Ok(ProjectValidationResult {
+        project_path: project_root.to_string_lossy().to_string(),
+        validation_id,
+        ecosystems,
+        steps: results,
+        passed,
+        message,
+        recorded_in_history,
+        catalog_seed,
+        duration_ms,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture(files: &[(&str, &str)]) -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        for (path, content) in files {
+            let path = dir.path().join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        }
+        dir
+    }
+
+    fn commands(steps: &[ValidationStep]) -> Vec<&str> {
+        steps.iter().map(|s| s.command.as_str()).collect()
+    }
+
+    #[test]
+    fn detects_ecosystems_from_build_files() {
+        let dir = fixture(&[
+            ("package.json", "{}"),
+            ("Cargo.toml", ""),
+            ("go.mod", ""),
+            ("pyproject.toml", ""),
+            ("pom.xml", ""),
+            ("Makefile", "build:\n"),
+        ]);
+        assert_eq!(
+            detect_ecosystems(dir.path()),
+            [
+                Ecosystem::Node,
+                Ecosystem::Python,
+                Ecosystem::Rust,
+                Ecosystem::Go,
+                Ecosystem::Maven,
+            ]
+        );
+
+        let dir = fixture(&[("App.csproj", ""), ("build.gradle.kts", "")]);
+        assert_eq!(
+            detect_ecosystems(dir.path()),
+            [Ecosystem::Gradle, Ecosystem::Dotnet]
+        );
+
+        // Make only counts when nothing else was found
+        let dir = fixture(&[("Makefile", "build:\n")]);
+        assert_eq!(detect_ecosystems(dir.path()), [Ecosystem::Make]);
+
+        let dir = fixture(&[("src/Cargo.toml", ""), ("README.md", "")]);
+        assert!(detect_ecosystems(dir.path()).is_empty());
+    }
+
+    #[test]
+    fn node_steps_follow_the_lockfile_and_scripts() {
+        let dir = fixture(&[
+            (
+                "package.json",
+                r#"{"scripts": {"build": "tsc", "test": "vitest", "dev": "vite"}}"#,
+            ),
+            ("pnpm-lock.yaml", ""),
+        ]);
+        assert_eq!(
+            commands(&ecosystem_steps(dir.path(), Ecosystem::Node)),
+            [
+                "pnpm install --frozen-lockfile",
+                "pnpm run build",
+                "pnpm run test"
+            ]
+        );
+
+        let dir = fixture(&[
+            ("package.json", r#"{"scripts": {"lint": "eslint ."}}"#),
+            ("package-lock.json", "{}"),
+        ]);
+        let steps = ecosystem_steps(dir.path(), Ecosystem::Node);
+        assert_eq!(commands(&steps), ["npm ci", "npm run lint"]);
+        assert!(!steps[1].required);
+
+        let dir = fixture(&[("package.json", "not json")]);
+        assert_eq!(
+            commands(&ecosystem_steps(dir.path(), Ecosystem::Node)),
+            ["npm install"]
+        );
+    }
+
+    #[test]
+    fn python_steps_follow_the_project_layout() {
+        let dir = fixture(&[("requirements.txt", ""), ("app/test_main.py", "")]);
+        let steps = ecosystem_steps(dir.path(), Ecosystem::Python);
+        let phases: Vec<StepPhase> = steps.iter().map(|s| s.phase).collect();
+        assert_eq!(
+            phases,
+            [
+                StepPhase::Install,
+                StepPhase::Install,
+                StepPhase::Lint,
+                StepPhase::Lint,
+                StepPhase::Build,
+                StepPhase::Test,
+            ]
+        );
+        assert!(steps[1]
+            .command
+            .ends_with("-m pip install -r requirements.txt"));
+
+        let dir = fixture(&[("pyproject.toml", "")]);
+        let steps = ecosystem_steps(dir.path(), Ecosystem::Python);
+        assert!(steps[1].command.ends_with("-m pip install -e ."));
+        assert!(steps.iter().all(|s| s.phase != StepPhase::Test));
+    }
+
+    #[test]
+    fn only_format_and_lint_steps_are_advisory() {
+        let dir = fixture(&[("Cargo.toml", ""), ("go.mod", ""), ("pom.xml", "")]);
+        for ecosystem in [Ecosystem::Rust, Ecosystem::Go, Ecosystem::Maven] {
+            for step in ecosystem_steps(dir.path(), ecosystem) {
+                let advisory = matches!(step.phase, StepPhase::Format | StepPhase::Lint);
+                assert_eq!(step.required, !advisory, "{}", step.command);
+                assert_eq!(step.ecosystem, ecosystem);
+            }
+        }
+        assert_eq!(
+            commands(&ecosystem_steps(dir.path(), Ecosystem::Maven)),
+            ["mvn -B -DskipTests package", "mvn -B test"]
+        );
+    }
+
+    #[test]
+    fn make_steps_need_their_targets() {
+        let dir = fixture(&[("Makefile", "test:\n\tgo test\nbuild-docs:\n")]);
+        let steps = ecosystem_steps(dir.path(), Ecosystem::Make);
+        assert_eq!(commands(&steps), ["make test"]);
+        assert_eq!(steps[0].phase, StepPhase::Test);
+    }
+
+    #[test]
+    fn tail_keeps_the_end_on_a_char_boundary() {
+        assert_eq!(tail("short".to_string(), 10), "short");
+        assert_eq!(tail("abcdef".to_string(), 3), "def");
+        assert_eq!(tail("aé".to_string(), 1), "");
+        assert_eq!(tail("aéb".to_string(), 3), "éb");
+    }
+}
@@ -0,0 +1,459 @@
+//! Live template updates
+//!
+//! While the app runs, every template root and every template added by a catalog is
+//! watched. Changes are debounced; each batch re-parses and re-validates only the
+//! manifests of the templates it touched and is emitted as a `templates://changed`
+//! event. Changes are per template directory, so per version: an entry lists only its
+//! own version in `versions`, and the frontend merges it into the template listed
+//! under the change's `name`. Name clashes between roots are only re-resolved by the
+//! next `get_templates`; until then, changes to templates hidden by a higher-priority
+//! copy are ignored.
+//! The `templateLifecycle` policy applies as in `get_templates`: a template that becomes
+//! hidden is reported as removed, and one that stops being hidden as added.
+//! The watcher restarts when the root, catalog or lifecycle settings change.
+
+use crate::lifecycle::{
+    apply_lifecycle_policy, lifecycle_policy, LifecyclePolicy, TEMPLATE_LIFECYCLE_SETTING,
+};
+use crate::manifest::{resolve_manifest_path, MANIFEST_FILENAMES};
+use crate::templates::{
+    find_template_dirs, is_discoverable, load_template, resolve_roots, root_configs,
+    DiscoveredTemplate, TemplateRoot, TemplateRootKind, TEMPLATE_ROOTS_SETTING,
+};
+use crate::{
+    discover_templates, read_setting, template_entry, validate_manifest, AppPaths, TemplateEntry,
+    ValidationResult,
+};
+use notify_debouncer_mini::notify::{RecommendedWatcher, RecursiveMode};
+use notify_debouncer_mini::{new_debouncer, DebounceEventResult, Debouncer};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{Emitter, Manager};
+
+/// Event carrying the templates changed by one debounced batch
+pub const TEMPLATES_CHANGED_EVENT: &str = "templates://changed";
+
+/// Quiet period before a batch of file changes is processed
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TemplateChangeKind {
+    Added,
+    Changed,
+    Removed,
+}
+
+/// A template touched by a batch of file changes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TemplateChange {
+    pub kind: TemplateChangeKind,
+    /// Template directory, which holds one version of the template
+    pub path: String,
+    /// Template name (absent when a new manifest does not parse)
+    pub name: Option<String>,
+    /// Updated entry for this version alone (absent when removed or when the manifest no longer parses)
+    pub entry: Option<TemplateEntry>,
+    /// Manifest validation (absent when removed)
+    pub validation: Option<ValidationResult>,
+}
+
+/// The running watcher, replaced on restart
+#[derive(Default)]
+pub struct TemplateWatcher(Mutex<Option<Debouncer<RecommendedWatcher>>>);
+
+/// What the watcher knows about the watched templates
+struct Known {
+    roots: Vec<TemplateRoot>,
+    policy: LifecyclePolicy,
+    /// Entries before the lifecycle policy, hidden ones included
+    templates: HashMap<PathBuf, TemplateEntry>,
+    /// Templates hidden by a higher-priority copy of the same name and version
+    shadowed: HashSet<PathBuf>,
+}
+
+impl Known {
+    fn new(
+        roots: Vec<TemplateRoot>,
+        discovered: Vec<DiscoveredTemplate>,
+        policy: LifecyclePolicy,
+    ) -> Self {
+        let shadowed = discovered
+            .iter()
+            .flat_map(|t| t.shadows.iter().map(PathBuf::from))
+            .collect();
+        let templates = discovered
+            .into_iter()
+            .map(|t| (t.path.clone(), template_entry(t)))
+            .collect();
+        Self {
+            roots,
+            policy,
+            templates,
+            shadowed,
+        }
+    }
+
+    /// `entry` as listed under the lifecycle policy, `None` when hidden
+    fn listed(&self, entry: &TemplateEntry) -> Option<TemplateEntry> {
+        apply_lifecycle_policy(vec![entry.clone()], &self.policy).pop()
+    }
+
+    /// Template directories a changed path belongs to
+    fn affected_dirs(&self, path: &Path) -> Vec<PathBuf> {
+        if self.shadowed.iter().any(|dir| path.starts_with(dir)) {
+            return vec![];
+        }
+
+        let known = self
+            .templates
+            .keys()
+            .filter(|dir| path.starts_with(dir))
+            .max_by_key(|dir| dir.components().count());
+        if let Some(dir) = known {
+            return vec![dir.clone()];
+        }
+
+        // A manifest appearing outside known templates makes a new one
+        let is_manifest = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .is_some_and(|n| MANIFEST_FILENAMES.contains(&n));
+        if is_manifest {
+            return path.parent().map(Path::to_path_buf).into_iter().collect();
+        }
+
+        // Moving a directory in or out of a root reports the directory alone
+        if path.is_dir() {
+            let Some(root) = self.root_for(path) else {
+                return vec![];
+            };
+            let depth = path
+                .strip_prefix(&root.path)
+                .map_or(0, |relative| relative.components().count());
+            find_template_dirs(path, root.max_depth.saturating_sub(depth))
+        } else {
+            self.templates
+                .keys()
+                .filter(|dir| dir.starts_with(path))
+                .cloned()
+                .collect()
+        }
+    }
+
+    fn root_for(&self, dir: &Path) -> Option<TemplateRoot> {
+        self.roots
+            .iter()
+            .find(|root| is_discoverable(root, dir))
+            .cloned()
+    }
+
+    /// Re-parse and re-validate one template directory
+    fn refresh(&mut self, dir: PathBuf) -> Option<TemplateChange> {
+        let path = dir.to_string_lossy().to_string();
+        let existing = self.templates.get(&dir).cloned();
+        let was_listed = existing.as_ref().is_some_and(|e| self.listed(e).is_some());
+        let known_name = existing.as_ref().map(|e| e.name.clone());
+
+        let Some(manifest_path) = resolve_manifest_path(&dir) else {
+            self.templates.remove(&dir)?;
+            return was_listed.then_some(TemplateChange {
+                kind: TemplateChangeKind::Removed,
+                path,
+                name: known_name,
+                entry: None,
+                validation: None,
+            });
+        };
+
+        let (root, shadows, catalog) = match &existing {
+            Some(entry) => (
+                entry.source_root.clone(),
+                entry.shadows.clone(),
+                entry.catalog.clone(),
+            ),
+            None => (self.root_for(&dir)?, vec![], None),
+        };
+        let entry = load_template(&dir).map(|manifest| {
+            template_entry(DiscoveredTemplate {
+                path: dir.clone(),
+                manifest,
+                root,
+                shadows,
+                catalog,
+            })
+        });
+
+        // A template whose manifest stops parsing stays known, so fixing it is a change
+        let listed = match &entry {
+            Some(entry) => {
+                self.templates.insert(dir, entry.clone());
+                let listed = self.listed(entry);
+                if listed.is_none() {
+                    return was_listed.then_some(TemplateChange {
+                        kind: TemplateChangeKind::Removed,
+                        path,
+                        name: known_name,
+                        entry: None,
+                        validation: None,
+                    });
+                }
+                listed
+            }
+            // Hidden templates stay hidden while their manifest is broken
+            None if existing.is_some() && !was_listed => return None,
+            None => None,
+        };
+
+        let validation = tauri::async_runtime::block_on(validate_manifest(
+            manifest_path.to_string_lossy().to_string(),
+        ))
+        .ok();
+        Some(TemplateChange {
+            kind: if was_listed {
+                TemplateChangeKind::Changed
+            } else {
+                TemplateChangeKind::Added
+            },
+            path,
+            name: entry.map(|e| e.name).or(known_name),
+            entry: listed,
+            validation,
+        })
+    }
+}
+
+fn handle_events(app: &tauri::AppHandle, known: &mut Known, result: DebounceEventResult) {
+    let events = match result {
+        Ok(events) => events,
+        Err(e) => {
+            eprintln!("Template watcher error: {}", e);
+            return;
+        }
+    };
+
+    let dirs: BTreeSet<PathBuf> = events
+        .iter()
+        .flat_map(|event| known.affected_dirs(&event.path))
+        .collect();
+    let changes: Vec<TemplateChange> = dirs
+        .into_iter()
+        .filter_map(|dir| known.refresh(dir))
+        .collect();
+
+    if !changes.is_empty() {
+        if let Err(e) = app.emit(TEMPLATES_CHANGED_EVENT, &changes) {
+            eprintln!("Failed to emit template changes: {}", e);
+        }
+    }
+}
+
+fn start(app: &tauri::AppHandle) -> Result<Debouncer<RecommendedWatcher>, String> {
+    let paths = AppPaths::from_app(app);
+    let roots = resolve_roots(
+        &paths,
+        &root_configs(read_setting(app, TEMPLATE_ROOTS_SETTING)),
+    );
+    // The user root may not exist before the first install; create it so it is watched
+    if let Some(user) = roots.iter().find(|r| r.kind == TemplateRootKind::User) {
+        let _ = fs::create_dir_all(&user.path);
+    }
+    let discovered = discover_templates(app);
+
+    // Templates added by catalogs live outside the roots; watch each as its own root
+    let catalog_dirs: Vec<PathBuf> = discovered
+        .iter()
+        .filter(|t| t.root.kind == TemplateRootKind::Catalog)
+        .map(|t| t.path.clone())
+        .collect();
+    let watched: Vec<PathBuf> = roots
+        .iter()
+        .map(|root| PathBuf::from(&root.path))
+        .chain(catalog_dirs.iter().cloned())
+        .filter(|dir| dir.is_dir())
+        .collect();
+
+    let policy = lifecycle_policy(read_setting(app, TEMPLATE_LIFECYCLE_SETTING));
+    let handle = app.clone();
+    let mut known = Known::new(roots, discovered, policy);
+    let mut debouncer = new_debouncer(DEBOUNCE, move |result| {
+        handle_events(&handle, &mut known, result)
+    })
+    .map_err(|e| format!("Failed to create template watcher: {}", e))?;
+
+    for dir in watched {
+        if let Err(e) = debouncer.watcher().watch(&dir, RecursiveMode::Recursive) {
+            eprintln!("Failed to watch {}: {}", dir.display(), e);
+        }
+    }
+    Ok(debouncer)
+}
+
+/// (Re)start watching the configured template roots, off the calling thread
+pub fn restart(app: &tauri::AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let state = app.state::<TemplateWatcher>();
+        let mut current = state.0.lock().unwrap();
+        // Stop the old watcher before discovering, so its events cannot race the new one
+        current.take();
+        match start(&app) {
+            Ok(debouncer) => *current = Some(debouncer),
+            Err(e) => eprintln!("{}", e),
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lifecycle::LifecycleVisibility;
+    use crate::templates::discover_in_roots;
+
+    fn write_template(dir: &Path) {
+        fs::create_dir_all(dir).unwrap();
+        fs::write(
+            dir.join("upg.yaml"),
+            "metadata:\n  name: api\n  version: \"1.0.0\"\n",
+        )
+        .unwrap();
+    }
+
+    fn root(kind: TemplateRootKind, path: &Path, priority: usize) -> TemplateRoot {
+        TemplateRoot {
+            kind,
+            path: path.to_string_lossy().to_string(),
+            priority,
+            max_depth: 2,
+        }
+    }
+
+    #[test]
+    fn shadowed_templates_are_not_reported() {
+        let user = tempfile::tempdir().unwrap();
+        let bundled = tempfile::tempdir().unwrap();
+        let winner = user.path().join("api");
+        let shadowed = bundled.path().join("api");
+        write_template(&winner);
+        write_template(&shadowed);
+        let roots = vec![
+            root(TemplateRootKind::User, user.path(), 0),
+            root(TemplateRootKind::Bundled, bundled.path(), 1),
+        ];
+
+        let known = Known::new(
+            roots.clone(),
+            discover_in_roots(&roots),
+            LifecyclePolicy::default(),
+        );
+
+        assert!(known.affected_dirs(&shadowed.join("upg.yaml")).is_empty());
+        assert!(known
+            .affected_dirs(&shadowed.join("src/main.rs"))
+            .is_empty());
+        assert_eq!(
+            known.affected_dirs(&winner.join("src/main.rs")),
+            vec![winner.clone()]
+        );
+        let added = bundled.path().join("cli");
+        assert_eq!(known.affected_dirs(&added.join("upg.yaml")), [added]);
+    }
+
+    #[test]
+    fn templates_moved_into_a_root_are_added() {
+        let staging = tempfile::tempdir().unwrap();
+        let library = tempfile::tempdir().unwrap();
+        let roots = vec![root(TemplateRootKind::User, library.path(), 0)];
+        let mut known = Known::new(
+            roots.clone(),
+            discover_in_roots(&roots),
+            LifecyclePolicy::default(),
+        );
+
+        // As `install_template` does: build next to the destination, then rename
+        let partial = staging.path().join(".1.0.0.partial");
+        write_template(&partial);
+        let installed = library.path().join("api").join("1.0.0");
+        fs::create_dir_all(installed.parent().unwrap()).unwrap();
+        fs::rename(&partial, &installed).unwrap();
+
+        let dirs = known.affected_dirs(&installed);
+        assert_eq!(dirs, vec![installed.clone()]);
+        let change = known.refresh(installed.clone()).unwrap();
+        assert_eq!(change.kind, TemplateChangeKind::Added);
+        assert_eq!(change.name.as_deref(), Some("api"));
+        let entry = change.entry.unwrap();
+        assert_eq!(entry.name, "api");
+        // Each change carries its own version only
+        assert_eq!(entry.versions.len(), 1);
+
+        // A directory holding several templates adds each of them
+        let pack = staging.path().join("pack");
+        write_template(&pack.join("one"));
+        write_template(&pack.join("two"));
+        fs::rename(&pack, library.path().join("pack")).unwrap();
+        assert_eq!(
+            known.affected_dirs(&library.path().join("pack")),
+            [
+                library.path().join("pack/one"),
+                library.path().join("pack/two")
+            ]
+        );
+
+        // Moving it back out removes what was known under it
+        fs::rename(library.path().join("api"), staging.path().join("api")).unwrap();
+        assert_eq!(
+            known.affected_dirs(&library.path().join("api")),
+            vec![installed.clone()]
+        );
+        assert_eq!(
+            known.refresh(installed).unwrap().kind,
+            TemplateChangeKind::Removed
+        );
+    }
+
+    #[test]
+    fn templates_hidden_by_the_lifecycle_policy_are_not_reported() {
+        let library = tempfile::tempdir().unwrap();
+        let dir = library.path().join("api");
+        write_template(&dir);
+        let roots = vec![root(TemplateRootKind::User, library.path(), 0)];
+        let policy = LifecyclePolicy {
+            deprecated: LifecycleVisibility::Hide,
+            ..LifecyclePolicy::default()
+        };
+        let mut known = Known::new(roots.clone(), discover_in_roots(&roots), policy);
+        let deprecate = |lifecycle: &str| {
+            fs::write(
+                dir.join("upg.yaml"),
+                format!(
+                    "metadata:\n  name: api\n  version: \"1.0.0\"\n  lifecycle: {}\n",
+                    lifecycle
+                ),
+            )
+            .unwrap()
+        };
+
+        deprecate("deprecated");
+        let change = known.refresh(dir.clone()).unwrap();
+        assert_eq!(change.kind, TemplateChangeKind::Removed);
+        assert_eq!(change.name.as_deref(), Some("api"));
+        assert!(change.entry.is_none());
+        assert!(known.refresh(dir.clone()).is_none());
+
+        deprecate("experimental");
+        let change = known.refresh(dir.clone()).unwrap();
+        assert_eq!(change.kind, TemplateChangeKind::Added);
+        assert!(change.entry.unwrap().lifecycle_notice.is_some());
+
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(
+            known.refresh(dir).unwrap().kind,
+            TemplateChangeKind::Removed
+        );
+    }
+}
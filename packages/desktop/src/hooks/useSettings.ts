@@ -25,6 +25,35 @@ export interface Settings {
   // Validation
   autoValidate: boolean;
   validationTimeout: number;
+
+  // Templates (list order is priority)
+  templateRoots: TemplateRootSetting[];
+  /** Catalog files, or directories of catalog files */
+  templateCatalogs: string[];
+  /** How experimental and deprecated templates are listed */
+  templateLifecycle: TemplateLifecycleSetting;
+}
+
+/**
+ * `show` lists templates normally, `flag` lists them with a notice, `hide` leaves them out
+ */
+export type LifecycleVisibility = 'show' | 'flag' | 'hide';
+
+export interface TemplateLifecycleSetting {
+  experimental: LifecycleVisibility;
+  deprecated: LifecycleVisibility;
+}
+
+/**
+ * Template root entry of the `templateRoots` setting
+ */
+export interface TemplateRootSetting {
+  kind: 'bundled' | 'user' | 'folder';
+  /** Folder path, for `folder` roots */
+  path?: string;
+  enabled: boolean;
+  /** Directory levels searched below the root (default 3) */
+  maxDepth?: number;
 }
 
 export const DEFAULT_SETTINGS: Settings = {
@@ -41,6 +70,12 @@ export const DEFAULT_SETTINGS: Settings = {
   defaultEnrichDepth: 'standard',
   autoValidate: false,
   validationTimeout: 300,
+  templateRoots: [
+    { kind: 'user', enabled: true },
+    { kind: 'bundled', enabled: true },
+  ],
+  templateCatalogs: [],
+  templateLifecycle: { experimental: 'flag', deprecated: 'flag' },
 };
 
 /**
@@ -274,11 +274,12 @@ function SeedGalleryPage() {
               )}
             </div>
           ) : (
-            filteredSeeds.map(entry => (
+            // A seed appears once per resolved stack it was validated with
+            filteredSeeds.map((entry, index) => (
               <SeedCard
-                key={entry.seed}
+                key={`${entry.seed}-${index}`}
                 entry={entry}
-                isSelected={selectedSeed?.seed === entry.seed}
+                isSelected={selectedSeed === entry}
                 onSelect={() => setSelectedSeed(entry)}
                 onUse={() => handleUseSeed(entry.seed)}
               />
@@ -4,15 +4,48 @@ import EnrichmentPanel, { createDefaultEnrichmentConfig } from '../components/En
 import { isTauri } from '../hooks/useTauriGenerate';
 import { useSettings } from '../hooks/useSettings';
 import { useStatus } from '../hooks/useStatus';
-import type { TemplateEntry, EnrichmentConfig } from '../types';
+import type {
+  TemplateEntry,
+  EnrichmentConfig,
+  GeneratedFile,
+  GeneratedFileSummary,
+} from '../types';
+
+/** Manifest command action as it would run (from get_template_commands) */
+interface PlannedCommand {
+  index: number;
+  command: string;
+  description: string | null;
+  will_run: boolean;
+}
+
+/** Manifest hook as it would run (from get_template_hooks) */
+interface PlannedHook {
+  hook: 'post_generation' | 'pre_migration';
+  script: string;
+  description: string | null;
+  will_run: boolean;
+}
+
+/** Outcome of a manifest command action */
+interface ActionResult {
+  index: number;
+  command: string;
+  status: 'succeeded' | 'failed' | 'timed_out' | 'skipped' | 'not_approved' | 'not_run';
+  success: boolean;
+  message: string;
+}
 
 /** Result from template generation */
 interface TemplateGenerationResult {
   success: boolean;
   message: string;
   files_generated: string[];
+  files: GeneratedFile[];
+  summary: GeneratedFileSummary;
   output_path: string;
   duration_ms: number;
+  actions: ActionResult[];
 }
 
 /**
@@ -115,6 +148,31 @@ function TemplateSelectorPage() {
 
     try {
       const { invoke } = await import('@tauri-apps/api/core');
+
+      // Commands only run with explicit consent, shown exactly as they will execute
+      const planned = await invoke<PlannedCommand[]>('get_template_commands', {
+        templatePath: selectedTemplate.path,
+        data: null,
+      });
+      const commands = planned.filter(c => c.will_run).map(c => c.command);
+      const approved =
+        commands.length > 0 &&
+        window.confirm(
+          `This template runs the following commands in the generated project:\n\n${commands.join('\n')}\n\nRun them?`
+        );
+
+      // The post-generation hook script needs the same consent
+      const hooks = await invoke<PlannedHook[]>('get_template_hooks', {
+        templatePath: selectedTemplate.path,
+        data: null,
+      });
+      const hook = hooks.find(h => h.hook === 'post_generation' && h.will_run);
+      const runHooks =
+        hook !== undefined &&
+        window.confirm(
+          `This template runs the script ${hook.script} in the generated project${hook.description ? ` (${hook.description})` : ''}.\n\nRun it?`
+        );
+
       const result = await invoke<TemplateGenerationResult>('generate_from_template', {
         templatePath: selectedTemplate.path,
         outputPath,
@@ -122,6 +180,8 @@ function TemplateSelectorPage() {
         useDefaults: true,
         force: false,
         enrichmentConfig: enrichment.enabled ? enrichment : null,
+        approvedCommands: approved ? commands : [],
+        runHooks,
       });
       setGenerationResult(result);
       if (result.success) {
@@ -377,6 +437,12 @@ function TemplateSelectorPage() {
                     </div>
                   </>
                 )}
+                {generationResult.actions.map(action => (
+                  <div key={action.index} style={{ fontSize: '10px' }}>
+                    <code>{action.command}</code>: {action.status.replace('_', ' ')}
+                    {!action.success && ` — ${action.message}`}
+                  </div>
+                ))}
               </div>
             )}
 
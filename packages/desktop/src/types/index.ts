@@ -94,11 +94,36 @@ export interface GenerationRequest {
   enrichment?: EnrichmentConfig;
 }
 
+/** Metadata for one generated file */
+export interface GeneratedFile {
+  path: string;
+  size: number;
+  sha256: string;
+  language: string | null;
+  binary: boolean;
+  executable: boolean;
+  operation: 'created' | 'overwritten' | 'skipped';
+}
+
+/** Totals over the generated files */
+export interface GeneratedFileSummary {
+  total_files: number;
+  total_bytes: number;
+  created: number;
+  overwritten: number;
+  skipped: number;
+  binary_files: number;
+  executable_files: number;
+  languages: Record<string, number>;
+}
+
 /** Generation result from Tauri backend */
 export interface GenerationResult {
   success: boolean;
   message: string;
   files_generated: string[];
+  files: GeneratedFile[];
+  summary: GeneratedFileSummary;
   output_path: string;
   duration_ms: number;
 }
@@ -134,7 +159,220 @@ export interface TemplateEntry {
   icon?: string;
   author?: string;
   lifecycle: 'experimental' | 'production' | 'deprecated';
+  /** Set for experimental/deprecated templates when the lifecycle setting flags them */
+  lifecycle_notice: string | null;
+  path: string;
+  /** Root the template was discovered under */
+  source_root: TemplateRoot;
+  /** Paths of lower-priority templates with the same name and version */
+  shadows: string[];
+  /** Catalog entry describing the template; its fields already override the above */
+  catalog: CatalogMetadata | null;
+  /**
+   * Every version found, this one first; pass `name@version` as the template path to
+   * generate with another
+   */
+  versions: TemplateVersion[];
+}
+
+/** One installed version of a template */
+export interface TemplateVersion {
+  version: string;
+  path: string;
+  lifecycle: string;
+  source_root: TemplateRoot;
+}
+
+/** Prompt data type */
+export type PromptType =
+  | 'string'
+  | 'int'
+  | 'float'
+  | 'boolean'
+  | 'select'
+  | 'multiselect'
+  | 'secret'
+  | 'unknown';
+
+/** Choice of a select/multiselect prompt (plain values arrive labelled with themselves) */
+export interface PromptChoice {
+  label: string;
+  value: string;
+}
+
+/** Prompt definition from a template manifest */
+export interface TemplatePrompt {
+  id: string;
+  type: PromptType;
+  title: string | null;
+  message: string;
+  help: string | null;
+  /** Literal value or Jinja2 expression */
+  default: unknown;
+  required: boolean;
+  hidden: boolean;
+  validator: string | null;
+  error_message: string | null;
+  when: string | null;
+  choices: PromptChoice[];
+}
+
+/** Problem with one prompt answer (`validate_answers`, keyed by prompt id) */
+export interface AnswerError {
+  /** UPG-100-004 missing, UPG-100-005 wrong type, UPG-100-006 invalid value */
+  code: string;
+  message: string;
+}
+
+/** Prompts shown for the current answers (`get_visible_prompts`) */
+export interface VisiblePrompts {
+  /** Prompt ids in manifest order */
+  visible: string[];
+  /** `when` conditions that do not parse; their prompts are shown */
+  errors: { prompt_id: string; expression: string; message: string; position: number }[];
+}
+
+/** Prompt defaults rendered against the current answers (`resolve_prompt_defaults`) */
+export interface ResolvedDefaults {
+  /** Keyed by prompt id; null when the expression is undefined */
+  defaults: Record<string, unknown>;
+  /** Unknown or malformed filters, which are skipped */
+  warnings: { prompt_id: string; message: string }[];
+}
+
+/** Manifest action */
+export type TemplateAction =
+  | { type: 'generate'; src: string; dest: string; exclude: string[]; variables: Record<string, string> }
+  | { type: 'copy'; src: string; dest: string }
+  | { type: 'skip'; path: string; when: string | null }
+  | { type: 'command'; command: string; description: string | null; when: string | null; on_error: 'fail' | 'warn' | 'ignore' }
+  | { type: 'create_file'; path: string; content: string }
+  | { type: 'other' };
+
+export interface TemplateHook {
+  script: string;
+  description: string | null;
+  when: string | null;
+  on_error: 'fail' | 'warn' | 'ignore';
+}
+
+/** Full template definition (`get_template_detail`) */
+export interface TemplateDetail {
+  /** Template directory */
+  path: string;
+  metadata: {
+    name: string;
+    version: string;
+    title: string | null;
+    description: string | null;
+    tags: string[];
+    icon: string | null;
+    author: string | null;
+    lifecycle: string;
+  };
+  prompts: TemplatePrompt[];
+  actions: TemplateAction[];
+  hooks: { post_generation: TemplateHook | null; pre_migration: TemplateHook | null } | null;
+  documentation: {
+    quickstart: string | null;
+    custom_setup: string | null;
+    customization_guide: string | null;
+    faq: { question: string; answer: string }[];
+  } | null;
+  smart_update: {
+    enabled: boolean;
+    preserve_files: string[];
+    regenerate_files: string[];
+    conflict_resolution: 'manual' | 'auto-accept-template' | 'auto-accept-user';
+  } | null;
+}
+
+/** Entry of a manifest's `template.breaking_changes` */
+export interface BreakingChange {
+  version: string;
+  changes: string[];
+  migration_notes: string | null;
+  migration_url: string | null;
+}
+
+/**
+ * Breaking changes between a project's recorded template version and the one it is
+ * regenerated or updated with (`check_breaking_changes`, and on generation/update results
+ * that stopped for them)
+ */
+export interface BreakingChangeReport {
+  template_name: string;
+  from_version: string;
+  to_version: string;
+  changes: BreakingChange[];
+}
+
+/** Template metadata from a catalog file */
+export interface CatalogMetadata {
+  /** Catalog file the entry came from */
+  catalog: string;
+  title: string | null;
+  description: string | null;
+  tags: string[];
+  lifecycle: 'experimental' | 'production' | 'deprecated';
+  owner: string;
+  type: string;
+  category: string | null;
+  featured: boolean;
+  links: { url: string; title?: string | null; icon?: string | null; type?: string | null }[];
+}
+
+/** Manifest validation result (`validate_manifest`) */
+export interface ValidationResult {
+  valid: boolean;
+  path: string;
+  errors: string[];
+  warnings: string[];
+}
+
+/** Template touched by file changes (`templates://changed` carries a list of these) */
+export interface TemplateChange {
+  kind: 'added' | 'changed' | 'removed';
+  /** Template directory, which holds one version of the template */
+  path: string;
+  /** Template name; null when a new manifest does not parse */
+  name: string | null;
+  /** Updated entry for this version alone; null when removed or the manifest no longer parses */
+  entry: TemplateEntry | null;
+  validation: ValidationResult | null;
+}
+
+/** Install manifest of a template in the user library */
+export interface InstallRecord {
+  name: string;
+  version: string;
+  source: string;
+  source_kind: 'directory' | 'zip' | 'tar_gz' | 'git';
+  /** Commit checked out, for git sources */
+  revision: string | null;
+  sha256: string;
+  installed_at: string;
+  path: string;
+}
+
+/** Outcome of loading a catalog file */
+export interface CatalogReport {
+  path: string;
+  /** Templates the catalog described or added */
+  applied: number;
+  errors: string[];
+}
+
+/** Where templates are discovered from */
+export type TemplateRootKind = 'bundled' | 'user' | 'folder' | 'catalog';
+
+/** Resolved template root */
+export interface TemplateRoot {
+  kind: TemplateRootKind;
   path: string;
+  /** Lower wins when two roots provide the same template name */
+  priority: number;
+  max_depth: number;
 }
 
 /** Registry seed entry */
@@ -15,12 +15,18 @@ export interface UpgAnswers {
   /** Schema version for answers file */
   _version: string;
 
-  /** Template information */
-  _template: TemplateInfo;
+  /** Template information (absent for procedural projects) */
+  _template?: TemplateInfo;
 
   /** Generation metadata */
   _generated: GenerationMetadata;
 
+  /** Seed and constraints, for projects generated procedurally by the desktop */
+  _procedural?: ProceduralInfo;
+
+  /** Enrichment (Pass 2) configuration, when enrichment ran */
+  _enrichment?: Record<string, unknown>;
+
   /** User's answers to prompts (keyed by prompt id) */
   [key: string]: unknown;
 }
@@ -39,6 +45,16 @@ export interface TemplateInfo {
   commit?: string;
 }
 
+/**
+ * Procedural generation information stored in answers
+ */
+export interface ProceduralInfo {
+  /** Seed passed to the procedural engine */
+  seed?: number;
+  /** Stack constraints given with the seed */
+  stack?: Record<string, unknown>;
+}
+
 /**
  * Generation metadata
  */
@@ -53,6 +69,8 @@ export interface GenerationMetadata {
   sidecarVersion?: string;
   /** Generation duration in ms */
   durationMs?: number;
+  /** Desktop application version, when generated from the desktop */
+  desktopVersion?: string;
 }
 
 /**